@@ -1,6 +1,6 @@
 extern crate ears;
 
-use ears::{AudioController, ReverbEffect, ReverbPreset, Sound};
+use ears::{AudioController, Curve, ReverbEffect, ReverbPreset, Sound};
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -98,11 +98,12 @@ fn main() {
     sound.play();
 
     // fade out
-    for i in 0..3000 {
-        let v = 1.0 - (i as f32 / 3000.0);
+    ears::start_ticker(Duration::from_millis(10));
+    let fade = wind.fade_out_and_stop(Duration::from_secs(3), Curve::Linear);
+    water.fade_out_and_stop(Duration::from_secs(3), Curve::Linear);
 
-        wind.set_volume(v);
-        water.set_volume(v);
-        sleep(Duration::from_millis(1));
+    while fade.is_running() {
+        sleep(Duration::from_millis(10));
     }
+    ears::stop_ticker();
 }
@@ -0,0 +1,206 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Large emitters following the listener.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::openal::{al, ffi};
+
+/**
+ * The shape of a large emitter, like a river, a crowd or a waterfall.
+ *
+ * An Audio Source spread over an Area is positioned on the point of the
+ * Area closest to the listener each time `listener::set_position` is
+ * called, so it is heard from the right direction and at the right distance
+ * wherever the listener stands. Combine it with
+ * `AudioController::set_radius` so the Audio Source surrounds the listener
+ * instead of collapsing to a dot when they get close.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{listener, Area, AudioController, Sound, SoundError};
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     // A river flowing along the x axis
+ *     let mut snd = Sound::new("path/to/the/river.ogg")?;
+ *     snd.set_looping(true);
+ *     snd.set_radius(3.);
+ *     snd.set_area(Some(Area::Segment {
+ *         start: [-50., 0., 10.],
+ *         end: [50., 0., 10.],
+ *     }));
+ *     snd.play();
+ *
+ *     // The river is now heard from [4.0, 0.0, 10.0]
+ *     listener::set_position([4., 0., 0.]);
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Area {
+    /// A line segment between two points [x, y, z].
+    Segment { start: [f32; 3], end: [f32; 3] },
+    /// An axis aligned box between its minimum and maximum corners
+    /// [x, y, z].
+    Box { min: [f32; 3], max: [f32; 3] },
+}
+
+impl Area {
+    /**
+     * Get the point of the Area closest to a position.
+     *
+     * # Argument
+     * * `position` - The position [x, y, z]
+     *
+     * # Return
+     * The closest point [x, y, z], the position itself when it is inside a
+     * Box
+     */
+    pub fn closest_point(&self, position: [f32; 3]) -> [f32; 3] {
+        match *self {
+            Area::Segment { start, end } => {
+                let segment = [end[0] - start[0], end[1] - start[1], end[2] - start[2]];
+                let length = dot(segment, segment);
+                if length == 0. {
+                    return start;
+                }
+
+                let offset = [
+                    position[0] - start[0],
+                    position[1] - start[1],
+                    position[2] - start[2],
+                ];
+                let progress = (dot(offset, segment) / length).clamp(0., 1.);
+                [
+                    start[0] + segment[0] * progress,
+                    start[1] + segment[1] * progress,
+                    start[2] + segment[2] * progress,
+                ]
+            }
+            Area::Box { min, max } => [
+                position[0].max(min[0]).min(max[0]),
+                position[1].max(min[1]).min(max[1]),
+                position[2].max(min[2]).min(max[2]),
+            ],
+        }
+    }
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+lazy_static! {
+    static ref AREAS: Mutex<HashMap<u32, Area>> = Mutex::new(HashMap::new());
+}
+
+fn apply(al_source: u32, area: &Area) {
+    let mut relative = 0;
+    al::alGetSourcei(al_source, ffi::AL_SOURCE_RELATIVE, &mut relative);
+
+    let listener = if relative == ffi::AL_TRUE as i32 {
+        [0.; 3]
+    } else {
+        let mut listener = [0.; 3];
+        al::alGetListenerfv(ffi::AL_POSITION, &mut listener[0]);
+        listener
+    };
+
+    let position = area.closest_point(listener);
+    al::alSourcefv(al_source, ffi::AL_POSITION, &position[0]);
+}
+
+/// Spread a source over an Area, or stop following the listener with None.
+pub(crate) fn set(al_source: u32, area: Option<Area>) {
+    if let Ok(mut areas) = AREAS.lock() {
+        match area {
+            Some(area) => {
+                apply(al_source, &area);
+                areas.insert(al_source, area);
+            }
+            None => {
+                areas.remove(&al_source);
+            }
+        }
+    }
+}
+
+/// The Area a source is spread over.
+pub(crate) fn area(al_source: u32) -> Option<Area> {
+    match AREAS.lock() {
+        Ok(areas) => areas.get(&al_source).cloned(),
+        Err(_) => None,
+    }
+}
+
+/// Stop tracking a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut areas) = AREAS.lock() {
+        areas.remove(&al_source);
+    }
+}
+
+/// Move the sources spread over an Area to the point closest to the
+/// listener.
+pub(crate) fn update() {
+    if let Ok(areas) = AREAS.lock() {
+        for (al_source, area) in areas.iter() {
+            apply(*al_source, area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::area::Area;
+
+    #[test]
+    fn area_segment_closest_point_OK() {
+        let area = Area::Segment {
+            start: [0., 0., 0.],
+            end: [10., 0., 0.],
+        };
+
+        assert_eq!(area.closest_point([4., 5., 0.]), [4., 0., 0.]);
+        assert_eq!(area.closest_point([-3., 1., 1.]), [0., 0., 0.]);
+        assert_eq!(area.closest_point([20., 0., -2.]), [10., 0., 0.]);
+    }
+
+    #[test]
+    fn area_box_closest_point_OK() {
+        let area = Area::Box {
+            min: [0., 0., 0.],
+            max: [10., 2., 10.],
+        };
+
+        assert_eq!(area.closest_point([5., 1., 5.]), [5., 1., 5.]);
+        assert_eq!(area.closest_point([-5., 3., 5.]), [0., 2., 5.]);
+    }
+}
@@ -20,11 +20,43 @@
 // CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 //! The functionnality that a Sound or a Music should provide.
+//!
+//! The setters of an AudioController never fail: out of range values are
+//! silently rejected by OpenAL. Each of them has a `try_` variant returning a
+//! `Result`, which validates the value and reports the OpenAL errors.
 
+use std::fmt;
 use std::time::Duration;
 
+use crate::area;
+use crate::area::Area;
+use crate::automation;
+use crate::automation::{Automation, PlaybackClock};
+use crate::bus;
+use crate::bus::Bus;
+use crate::clock;
+use crate::cone::Cone;
+use crate::distance_model;
+use crate::distance_model::DistanceModel;
+use crate::error::SoundError;
+use crate::fade;
+use crate::fade::{Curve, FadeHandle, Target};
+use crate::filter;
+use crate::filter::Filter;
+use crate::internal::OpenAlData;
+use crate::openal::al;
+use crate::resampler;
+use crate::resampler::Resampler;
+use crate::reverb_effect;
 use crate::reverb_effect::ReverbEffect;
+use crate::reverb_zone;
+use crate::rolloff;
+use crate::rolloff::RolloffCurve;
+use crate::sends;
+use crate::source_properties::SourceProperties;
+use crate::spatialize::Spatialize;
 use crate::states::State;
+use crate::ticker;
 
 /// The functionnality that an Audio Source should provide.
 pub trait AudioController {
@@ -188,7 +220,7 @@ pub trait AudioController {
      * # Return
      * True if the Audio Source is relative to the listener false otherwise
      */
-    fn is_relative(&mut self) -> bool;
+    fn is_relative(&self) -> bool;
 
     /**
      * Set the Audio Source location in three dimensional space.
@@ -216,6 +248,29 @@ pub trait AudioController {
      */
     fn get_position(&self) -> [f32; 3];
 
+    /**
+     * Set the velocity of the Audio Source.
+     *
+     * The velocity is only used for the Doppler effect, it doesn't move the
+     * Audio Source. See `listener::set_doppler_factor`.
+     *
+     * Default velocity is [0.0, 0.0, 0.0].
+     *
+     * # Argument
+     * * `velocity` - A three dimensional vector of f32 containing the velocity
+     *   of the Audio Source [x, y, z].
+     */
+    fn set_velocity(&mut self, velocity: [f32; 3]) -> ();
+
+    /**
+     * Get the velocity of the Audio Source.
+     *
+     * # Return
+     * A three dimensional vector of f32 containing the velocity
+     * of the Audio Source [x, y, z].
+     */
+    fn get_velocity(&self) -> [f32; 3];
+
     /**
      * Set the direction of the Audio Source.
      *
@@ -236,6 +291,26 @@ pub trait AudioController {
      */
     fn get_direction(&self) -> [f32; 3];
 
+    /**
+     * Set the sound cone of the Audio Source.
+     *
+     * The cone only has an effect once a direction is set.
+     *
+     * The default cone is omnidirectional, see `Cone::default`.
+     *
+     * # Argument
+     * `cone` - The new cone of the Audio Source.
+     */
+    fn set_cone(&mut self, cone: Cone) -> ();
+
+    /**
+     * Get the sound cone of the Audio Source.
+     *
+     * # Return
+     * The current cone of the Audio Source.
+     */
+    fn get_cone(&self) -> Cone;
+
     /**
      * Set the maximum distance of the Audio Source.
      *
@@ -243,6 +318,9 @@ pub trait AudioController {
      * clamped distance model, or where attenuation reaches 0.0 gain for linear
      * distance models with a default rolloff factor.
      *
+     * The attenuation depends on the distance model of the Audio Source, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     *
      * The default maximum distance is +inf.
      *
      * # Argument
@@ -265,6 +343,9 @@ pub trait AudioController {
      * At 0.0, no distance attenuation ever occurs on non-linear
      * attenuation models.
      *
+     * The attenuation depends on the distance model of the Audio Source, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     *
      * The default distance reference is 1.
      *
      * # Argument
@@ -286,6 +367,9 @@ pub trait AudioController {
      * Multiplier to exaggerate or diminish distance attenuation.
      * At 0.0, no distance attenuation ever occurs.
      *
+     * The attenuation depends on the distance model of the Audio Source, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     *
      * The default attenuation is 1.
      *
      * # Arguments
@@ -339,8 +423,1185 @@ pub trait AudioController {
      */
     fn get_direct_channel(&self) -> bool;
 
+    /**
+     * Set whether the Audio Source is positioned in the scene.
+     *
+     * With `Spatialize::On` a stereo or multichannel Audio Source is
+     * positioned like a mono one, its channels placed around its position,
+     * instead of playing on the matching speakers. Applies only when the
+     * AL_SOFT_source_spatialize extension exists.
+     *
+     * [https://openal-soft.org/openal-extensions/SOFT_source_spatialize.txt]()
+     *
+     * The default is `Spatialize::Auto`.
+     *
+     * # Argument
+     * * `spatialize` - The new spatialization mode
+     */
+    fn set_spatialize(&mut self, spatialize: Spatialize) -> ();
+
+    /**
+     * Get whether the Audio Source is positioned in the scene.
+     *
+     * Will always return `Spatialize::Auto` if the AL_SOFT_source_spatialize
+     * extension is not present.
+     *
+     * # Return
+     * The current spatialization mode
+     */
+    fn get_spatialize(&self) -> Spatialize;
+
+    /**
+     * Set the angles of the channels of a stereo Audio Source.
+     *
+     * The angles are in radians, counter-clockwise from the front of the
+     * listener: positive angles are on the left. Spreading the channels
+     * widens a stereo ambience, setting both angles to the same value
+     * places both channels at the same spot. Applies only when the
+     * AL_EXT_STEREO_ANGLES extension exists and when playing stereo
+     * buffers which aren't spatialized (see `set_spatialize`).
+     *
+     * The default is [PI / 6, -PI / 6], 30 degrees on each side.
+     *
+     * # Argument
+     * * `angles` - The angles of the [left, right] channels
+     */
+    fn set_stereo_angles(&mut self, angles: [f32; 2]) -> ();
+
+    /**
+     * Get the angles of the channels of a stereo Audio Source.
+     *
+     * Will always return the default angles if the AL_EXT_STEREO_ANGLES
+     * extension is not present.
+     *
+     * # Return
+     * The angles of the [left, right] channels, in radians
+     */
+    fn get_stereo_angles(&self) -> [f32; 2];
+
+    /**
+     * Set the radius of the Audio Source.
+     *
+     * An Audio Source with a radius is a sphere instead of a point: as the
+     * listener gets inside the sphere the sound spreads around them instead
+     * of coming from a single direction. Applies only when the
+     * AL_EXT_SOURCE_RADIUS extension exists.
+     *
+     * The default radius is 0.0.
+     *
+     * # Argument
+     * * `radius` - The new radius, in the range [0.0, +inf]
+     */
+    fn set_radius(&mut self, radius: f32) -> ();
+
+    /**
+     * Get the radius of the Audio Source.
+     *
+     * Will always return 0.0 if the AL_EXT_SOURCE_RADIUS extension is not
+     * present.
+     *
+     * # Return
+     * The current radius of the Audio Source
+     */
+    fn get_radius(&self) -> f32;
+
     /**
      * Returns the duration
      */
     fn get_duration(&self) -> Duration;
+
+    /// The internal OpenAL source identifier of the Audio Source.
+    #[doc(hidden)]
+    fn al_source(&self) -> u32;
+
+    /// Where the playback position of the Audio Source is read from.
+    #[doc(hidden)]
+    fn playback_clock(&self) -> PlaybackClock;
+
+    /// Get the Audio Source ready to start, without starting it.
+    #[doc(hidden)]
+    fn cue(&mut self) -> ();
+
+    /**
+     * Set the distance model of the Audio Source.
+     *
+     * By default an Audio Source follows the distance model of the scene,
+     * see `listener::set_distance_model`. Requires the
+     * AL_EXT_source_distance_model extension, without it the Audio Source
+     * keeps following the distance model of the scene.
+     *
+     * # Argument
+     * * `model` - The distance model of the Audio Source, or None to follow
+     *   the distance model of the scene
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, DistanceModel, Sound, SoundError};
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+     *     snd.set_distance_model(Some(DistanceModel::LinearClamped));
+     *     snd.set_max_distance(50.);
+     *     Ok(())
+     * }
+     * ```
+     */
+    fn set_distance_model(&mut self, model: Option<DistanceModel>) {
+        check_openal_context!(());
+
+        distance_model::set_source(self.al_source(), model);
+    }
+
+    /**
+     * Get the distance model of the Audio Source.
+     *
+     * # Return
+     * The distance model of the Audio Source, or None if it follows the
+     * distance model of the scene.
+     */
+    fn get_distance_model(&self) -> Option<DistanceModel> {
+        distance_model::source(self.al_source())
+    }
+
+    /**
+     * Attenuate the Audio Source with a custom curve instead of the
+     * distance model.
+     *
+     * While a curve is set, the OpenAL distance attenuation is disabled:
+     * `set_attenuation` only changes the attenuation applied once the curve
+     * is removed. The curve is evaluated when `ears::update` is
+     * called, or by the background ticker.
+     *
+     * # Argument
+     * * `curve` - The RolloffCurve to use, or None to go back to the
+     *   distance model
+     */
+    fn set_rolloff_curve(&mut self, curve: Option<RolloffCurve>) {
+        check_openal_context!(());
+
+        rolloff::set(self.al_source(), curve);
+    }
+
+    /**
+     * Get the custom attenuation curve of the Audio Source.
+     *
+     * # Return
+     * The RolloffCurve of the Audio Source, or None if it uses the distance
+     * model.
+     */
+    fn get_rolloff_curve(&self) -> Option<RolloffCurve> {
+        rolloff::curve(self.al_source())
+    }
+
+    /**
+     * Assign the Audio Source to a Bus.
+     *
+     * The volume and pitch of the Audio Source are multiplied by the ones
+     * of the Bus and of its parents, while `get_volume` and `get_pitch` keep
+     * returning the values set on the Audio Source.
+     *
+     * # Argument
+     * * `bus` - The Bus of the Audio Source, or None to remove it from its
+     *   current Bus
+     */
+    fn set_bus(&mut self, bus: Option<&Bus>) {
+        check_openal_context!(());
+
+        bus::set(self.al_source(), bus);
+    }
+
+    /**
+     * Get the Bus of the Audio Source.
+     *
+     * # Return
+     * The Bus of the Audio Source, or None if it isn't assigned to a Bus.
+     */
+    fn get_bus(&self) -> Option<Bus> {
+        bus::bus(self.al_source())
+    }
+
+    /**
+     * Set the resampler of the Audio Source.
+     *
+     * Does nothing if the AL_SOFT_source_resampler extension is not
+     * present.
+     *
+     * The default is `ears::default_resampler`.
+     *
+     * # Argument
+     * * `resampler` - One of the resamplers listed by `ears::resamplers`
+     */
+    fn set_resampler(&mut self, resampler: &Resampler) {
+        check_openal_context!(());
+
+        resampler::set(self.al_source(), resampler);
+    }
+
+    /**
+     * Get the resampler of the Audio Source.
+     *
+     * # Return
+     * The resampler of the Audio Source, or None if the
+     * AL_SOFT_source_resampler extension is not present.
+     */
+    fn get_resampler(&self) -> Option<Resampler> {
+        check_openal_context!(None);
+
+        resampler::source(self.al_source())
+    }
+
+    /**
+     * Spread the Audio Source over an Area.
+     *
+     * The Audio Source is moved to the point of the Area closest to the
+     * listener now and each time `listener::set_position` is called,
+     * overriding `set_position`. Relative Audio Sources use the origin as
+     * the position of the listener.
+     *
+     * # Argument
+     * * `area` - The Area to spread the Audio Source over, or None to keep
+     *   it at its current position
+     */
+    fn set_area(&mut self, area: Option<Area>) {
+        check_openal_context!(());
+
+        area::set(self.al_source(), area);
+    }
+
+    /**
+     * Get the Area the Audio Source is spread over.
+     *
+     * # Return
+     * The Area of the Audio Source, or None if it is a point.
+     */
+    fn get_area(&self) -> Option<Area> {
+        area::area(self.al_source())
+    }
+
+    /**
+     * Get the current position in the Audio Source and the output latency.
+     *
+     * The position is the one of the OpenAL mixer, like `get_offset`: the
+     * samples at this position still take the latency to reach the
+     * speakers, so the position actually heard is the position minus the
+     * latency. Use it to synchronize audio with video or animations.
+     *
+     * The latency requires the AL_SOFT_source_latency extension, without it
+     * the latency is reported as zero.
+     *
+     * # Return
+     * A tuple of the playback position and the latency
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, Music, SoundError};
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     let mut msc = Music::new("path/to/the/dialog.ogg")?;
+     *     msc.play();
+     *     let (offset, latency) = msc.get_offset_with_latency();
+     *     let heard = offset.checked_sub(latency).unwrap_or_default();
+     *     Ok(())
+     * }
+     * ```
+     */
+    fn get_offset_with_latency(&self) -> (Duration, Duration) {
+        check_openal_context!((Duration::from_secs(0), Duration::from_secs(0)));
+
+        self.playback_clock().position_with_latency()
+    }
+
+    /**
+     * Play the Audio Source at a time of the device clock.
+     *
+     * The start is sample accurate when OpenAL provides the
+     * AL_SOFT_source_start_delay extension. Otherwise the Audio Source is
+     * started by a background thread, which is only accurate to the mixing
+     * period of the device, usually 10 to 20 milliseconds. Stopping the Audio
+     * Source before the start time cancels the start.
+     *
+     * # Argument
+     * * `clock_time` - The time of the device clock to start at, as returned
+     *   by `ears::device_clock`. The Audio Source starts right away if it is in
+     *   the past.
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, Sound, SoundError};
+     * use std::time::Duration;
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+     *     let beat = Duration::from_millis(500);
+     *     snd.play_at(ears::device_clock() + beat);
+     *     Ok(())
+     * }
+     * ```
+     */
+    fn play_at(&mut self, clock_time: Duration) {
+        check_openal_context!(());
+
+        self.cue();
+        clock::start(&[self.al_source()], Some(clock_time));
+    }
+
+    /**
+     * Set the gain of the signal sent to the connected ReverbEffect.
+     *
+     * This is the gain of the auxiliary send 0, see `set_send_gain`. The
+     * default reverb send is 1.0.
+     *
+     * # Argument
+     * * `gain` - The gain of the reverb send in the range [0.0, 1.0]
+     */
+    fn set_reverb_send(&mut self, gain: f32) {
+        sends::set_gain(self.al_source(), 0, gain);
+    }
+
+    /**
+     * Get the gain of the signal sent to the connected ReverbEffect.
+     *
+     * # Return
+     * The gain of the reverb send in the range [0.0, 1.0]
+     */
+    fn get_reverb_send(&self) -> f32 {
+        sends::gain(self.al_source(), 0)
+    }
+
+    /**
+     * Connect an auxiliary send of the Audio Source to a ReverbEffect.
+     *
+     * Each send feeds its own effect, letting an Audio Source be heard in a
+     * room reverb and an echo at once. `connect` uses the send 0. Sends out
+     * of the range granted by the device, see `ears::max_auxiliary_sends`,
+     * are ignored.
+     *
+     * # Arguments
+     * * `index` - The index of the send, from 0
+     * * `reverb_effect` - The ReverbEffect to connect, or None to disconnect
+     *   the send
+     * * `filter` - The Filter of the send, or None to leave it unfiltered
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, ReverbEffect, ReverbPreset, Sound, SoundError};
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     let room = ReverbEffect::preset(ReverbPreset::Room.properties()).ok();
+     *     let canyon = ReverbEffect::preset(ReverbPreset::Mountains.properties()).ok();
+     *
+     *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+     *     snd.connect_send(0, room.as_ref(), None);
+     *     snd.connect_send(1, canyon.as_ref(), None);
+     *     snd.set_send_gain(1, 0.3);
+     *     Ok(())
+     * }
+     * ```
+     */
+    fn connect_send(
+        &mut self,
+        index: i32,
+        reverb_effect: Option<&ReverbEffect>,
+        filter: Option<&Filter>,
+    ) {
+        check_openal_context!(());
+
+        if index < 0 || index >= sends::max_auxiliary_sends() {
+            return;
+        }
+        sends::connect(
+            self.al_source(),
+            index,
+            reverb_effect.map(|effect| effect.slot()),
+        );
+        sends::set_filter(self.al_source(), index, filter.map(Filter::id));
+    }
+
+    /**
+     * Get the auxiliary effect slot an auxiliary send is connected to.
+     *
+     * # Argument
+     * * `index` - The index of the send
+     *
+     * # Return
+     * The slot of the connected ReverbEffect (see `ReverbEffect::slot`), or
+     * None if the send isn't connected
+     */
+    fn get_send_slot(&self, index: i32) -> Option<u32> {
+        sends::slot(self.al_source(), index)
+    }
+
+    /**
+     * Set the gain of the signal of an auxiliary send.
+     *
+     * The default gain is 1.0.
+     *
+     * # Arguments
+     * * `index` - The index of the send
+     * * `gain` - The gain of the send in the range [0.0, 1.0]
+     */
+    fn set_send_gain(&mut self, index: i32, gain: f32) {
+        check_openal_context!(());
+
+        if index < 0 || index >= sends::max_auxiliary_sends() {
+            return;
+        }
+        sends::set_gain(self.al_source(), index, gain);
+    }
+
+    /**
+     * Get the gain of the signal of an auxiliary send.
+     *
+     * # Argument
+     * * `index` - The index of the send
+     *
+     * # Return
+     * The gain of the send in the range [0.0, 1.0]
+     */
+    fn get_send_gain(&self, index: i32) -> f32 {
+        sends::gain(self.al_source(), index)
+    }
+
+    /**
+     * Set the Filter of the signal going directly to the listener.
+     *
+     * The Filter is combined with the low-pass of the rolloff curve, if
+     * any. Changing the gains of the Filter updates the Audio Source at once.
+     *
+     * # Argument
+     * * `filter` - The Filter to set, or None to remove the current one
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, Filter, LowpassFilter, Sound, SoundError};
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+     *
+     *     // Underwater
+     *     let water = LowpassFilter::new();
+     *     water.set_gainhf(0.1);
+     *     snd.set_direct_filter(Some(&Filter::from(&water)));
+     *     Ok(())
+     * }
+     * ```
+     */
+    fn set_direct_filter(&mut self, filter: Option<&Filter>) {
+        check_openal_context!(());
+
+        filter::set_direct(self.al_source(), filter);
+    }
+
+    /**
+     * Get the Filter of the signal going directly to the listener.
+     *
+     * # Return
+     * The Filter, or None if the direct signal isn't filtered
+     */
+    fn get_direct_filter(&self) -> Option<Filter> {
+        filter::direct(self.al_source())
+    }
+
+    /**
+     * Set the Filter of the signal of an auxiliary send.
+     *
+     * The Filter is combined with the gain of the send.
+     *
+     * # Arguments
+     * * `index` - The index of the send, 0 for the connected ReverbEffect
+     * * `filter` - The Filter to set, or None to remove the current one
+     */
+    fn set_send_filter(&mut self, index: i32, filter: Option<&Filter>) {
+        check_openal_context!(());
+
+        if index < 0 || index >= sends::max_auxiliary_sends() {
+            return;
+        }
+        sends::set_filter(self.al_source(), index, filter.map(Filter::id));
+    }
+
+    /**
+     * Get the Filter of the signal of an auxiliary send.
+     *
+     * # Argument
+     * * `index` - The index of the send
+     *
+     * # Return
+     * The Filter, or None if the send isn't filtered
+     */
+    fn get_send_filter(&self, index: i32) -> Option<Filter> {
+        sends::filter(self.al_source(), index).and_then(Filter::from_id)
+    }
+
+    /**
+     * Make an auxiliary send of the Audio Source follow the ReverbZones.
+     *
+     * The send is connected to the reverb of the zones while the listener
+     * is in a zone, and disconnected outside all the zones. It replaces the
+     * ReverbEffect connected to the send, if any.
+     *
+     * # Argument
+     * * `index` - The index of the send following the zones, or None to
+     *   disconnect it from the zones
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, ReverbPreset, ReverbZone, Sound, SoundError, ZoneShape};
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     let cave = ReverbZone::new(
+     *         ZoneShape::Sphere { center: [0., 0., 0.], radius: 10. },
+     *         ReverbPreset::Cave.properties(),
+     *     );
+     *
+     *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+     *     snd.set_reverb_zone_send(Some(0));
+     *     Ok(())
+     * }
+     * ```
+     */
+    fn set_reverb_zone_send(&mut self, index: Option<i32>) {
+        check_openal_context!(());
+
+        if let Some(index) = index {
+            if index < 0 || index >= sends::max_auxiliary_sends() {
+                return;
+            }
+        }
+        reverb_zone::set_send(self.al_source(), index);
+    }
+
+    /**
+     * Get the auxiliary send of the Audio Source following the ReverbZones.
+     *
+     * # Return
+     * The index of the send, or None if the Audio Source doesn't follow the
+     * zones
+     */
+    fn get_reverb_zone_send(&self) -> Option<i32> {
+        reverb_zone::send(self.al_source())
+    }
+
+    /**
+     * Get all the properties of the Audio Source at once.
+     *
+     * # Return
+     * The current SourceProperties of the Audio Source.
+     */
+    fn get_properties(&self) -> SourceProperties {
+        SourceProperties {
+            volume: self.get_volume(),
+            min_volume: self.get_min_volume(),
+            max_volume: self.get_max_volume(),
+            pitch: self.get_pitch(),
+            position: self.get_position(),
+            velocity: self.get_velocity(),
+            direction: self.get_direction(),
+            cone: self.get_cone(),
+            max_distance: self.get_max_distance(),
+            reference_distance: self.get_reference_distance(),
+            attenuation: self.get_attenuation(),
+            distance_model: self.get_distance_model(),
+            rolloff_curve: self.get_rolloff_curve(),
+            looping: self.is_looping(),
+            relative: self.is_relative(),
+            direct_channel: self.get_direct_channel(),
+            spatialize: self.get_spatialize(),
+            stereo_angles: self.get_stereo_angles(),
+            radius: self.get_radius(),
+            area: self.get_area(),
+            resampler: self.get_resampler(),
+            bus: self.get_bus(),
+            reverb_slot: match self.get_reverb_zone_send() {
+                // The slot of the zones isn't a ReverbEffect of the user
+                Some(0) => None,
+                _ => self.get_send_slot(0),
+            },
+            reverb_send: self.get_reverb_send(),
+            direct_filter: self.get_direct_filter(),
+            send_filter: self.get_send_filter(0),
+            reverb_zone_send: self.get_reverb_zone_send(),
+        }
+    }
+
+    /**
+     * Set all the properties of the Audio Source at once.
+     *
+     * # Argument
+     * * `properties` - The SourceProperties to apply to the Audio Source
+     */
+    fn apply_properties(&mut self, properties: &SourceProperties) {
+        self.set_volume(properties.volume);
+        self.set_min_volume(properties.min_volume);
+        self.set_max_volume(properties.max_volume);
+        self.set_pitch(properties.pitch);
+        self.set_position(properties.position);
+        self.set_velocity(properties.velocity);
+        self.set_direction(properties.direction);
+        self.set_cone(properties.cone);
+        self.set_max_distance(properties.max_distance);
+        self.set_reference_distance(properties.reference_distance);
+        self.set_attenuation(properties.attenuation);
+        self.set_distance_model(properties.distance_model);
+        self.set_rolloff_curve(properties.rolloff_curve.clone());
+        self.set_looping(properties.looping);
+        self.set_relative(properties.relative);
+        self.set_direct_channel(properties.direct_channel);
+        self.set_spatialize(properties.spatialize);
+        self.set_stereo_angles(properties.stereo_angles);
+        self.set_radius(properties.radius);
+        self.set_area(properties.area);
+        self.set_bus(properties.bus.as_ref());
+        if let Some(resampler) = &properties.resampler {
+            self.set_resampler(resampler);
+        }
+        // The zones release their send before the ReverbEffect connects, and
+        // keep it if they follow the send 0
+        self.set_reverb_zone_send(properties.reverb_zone_send);
+        if properties.reverb_zone_send != Some(0) {
+            // The slot may belong to a ReverbEffect dropped since
+            let slot = properties
+                .reverb_slot
+                .filter(|slot| reverb_effect::is_slot(*slot));
+            sends::connect(self.al_source(), 0, slot);
+        }
+        self.set_reverb_send(properties.reverb_send);
+        self.set_direct_filter(properties.direct_filter.as_ref());
+        self.set_send_filter(0, properties.send_filter.as_ref());
+    }
+
+    /**
+     * Automate the volume of the Audio Source along its playback.
+     *
+     * # Argument
+     * * `automation` - The Automation to attach, or None to remove the
+     *   current one.
+     */
+    fn automate_volume(&mut self, automation: Option<Automation>) {
+        automation::set_volume(self.al_source(), self.playback_clock(), automation);
+    }
+
+    /**
+     * Automate the pitch of the Audio Source along its playback.
+     *
+     * # Argument
+     * * `automation` - The Automation to attach, or None to remove the
+     *   current one.
+     */
+    fn automate_pitch(&mut self, automation: Option<Automation>) {
+        automation::set_pitch(self.al_source(), self.playback_clock(), automation);
+    }
+
+    /**
+     * Automate the position of the Audio Source along its playback.
+     *
+     * # Argument
+     * * `automation` - The Automation of the [x, y, z] position to attach,
+     *   or None to remove the current one.
+     */
+    fn automate_position(&mut self, automation: Option<Automation<[f32; 3]>>) {
+        automation::set_position(self.al_source(), self.playback_clock(), automation);
+    }
+
+    /**
+     * Automate the reverb send of the Audio Source along its playback.
+     *
+     * # Argument
+     * * `automation` - The Automation to attach, or None to remove the
+     *   current one.
+     */
+    fn automate_reverb_send(&mut self, automation: Option<Automation>) {
+        automation::set_reverb_send(self.al_source(), self.playback_clock(), automation);
+    }
+
+    /**
+     * Fade the volume of the Audio Source to a new value.
+     *
+     * Fades progress when `ears::update` is called, or on their own once
+     * `ears::start_ticker` has been called. Starting a new volume fade
+     * cancels the volume fade currently running on the Audio Source.
+     *
+     * # Arguments
+     * * `volume` - The volume to reach at the end of the fade
+     * * `duration` - The duration of the fade
+     * * `curve` - The shape of the fade
+     *
+     * # Return
+     * A FadeHandle to follow or cancel the fade.
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, Curve, Sound, SoundError};
+     * use std::time::Duration;
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     ears::start_ticker(Duration::from_millis(10));
+     *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+     *     snd.play();
+     *     let fade = snd.fade_to(0.2, Duration::from_secs(2), Curve::EaseOut);
+     *     while fade.is_running() {}
+     *     Ok(())
+     * }
+     * ```
+     */
+    fn fade_to(&mut self, volume: f32, duration: Duration, curve: Curve) -> FadeHandle {
+        let target = Target::Volume(self.get_volume(), volume);
+        fade::start(self.al_source(), target, duration, curve, false)
+    }
+
+    /**
+     * Play the Audio Source and fade its volume in.
+     *
+     * If the Audio Source isn't playing yet, its volume is set to 0.0 before
+     * it starts playing.
+     *
+     * # Arguments
+     * * `volume` - The volume to reach at the end of the fade
+     * * `duration` - The duration of the fade
+     * * `curve` - The shape of the fade
+     *
+     * # Return
+     * A FadeHandle to follow or cancel the fade.
+     */
+    fn fade_in(&mut self, volume: f32, duration: Duration, curve: Curve) -> FadeHandle {
+        if !self.is_playing() {
+            self.set_volume(0.);
+            self.play();
+        }
+        self.fade_to(volume, duration, curve)
+    }
+
+    /**
+     * Fade the volume of the Audio Source out, then stop it.
+     *
+     * The volume is left at 0.0 once the Audio Source is stopped.
+     *
+     * # Arguments
+     * * `duration` - The duration of the fade
+     * * `curve` - The shape of the fade
+     *
+     * # Return
+     * A FadeHandle to follow or cancel the fade. The Audio Source isn't
+     * stopped if the fade is cancelled.
+     */
+    fn fade_out_and_stop(&mut self, duration: Duration, curve: Curve) -> FadeHandle {
+        let target = Target::Volume(self.get_volume(), 0.);
+        fade::start(self.al_source(), target, duration, curve, true)
+    }
+
+    /**
+     * Glide the pitch of the Audio Source to a new value.
+     *
+     * Starting a new pitch glide cancels the pitch glide currently running
+     * on the Audio Source.
+     *
+     * # Arguments
+     * * `pitch` - The pitch to reach at the end of the glide
+     * * `duration` - The duration of the glide
+     * * `curve` - The shape of the glide
+     *
+     * # Return
+     * A FadeHandle to follow or cancel the glide.
+     */
+    fn glide_pitch_to(&mut self, pitch: f32, duration: Duration, curve: Curve) -> FadeHandle {
+        let target = Target::Pitch(self.get_pitch(), pitch);
+        fade::start(self.al_source(), target, duration, curve, false)
+    }
+
+    /**
+     * Glide the Audio Source to a new location in three dimensional space.
+     *
+     * Starting a new position glide cancels the position glide currently
+     * running on the Audio Source.
+     *
+     * # Arguments
+     * * `position` - The position [x, y, z] to reach at the end of the glide
+     * * `duration` - The duration of the glide
+     * * `curve` - The shape of the glide
+     *
+     * # Return
+     * A FadeHandle to follow or cancel the glide.
+     */
+    fn glide_position_to(
+        &mut self,
+        position: [f32; 3],
+        duration: Duration,
+        curve: Curve,
+    ) -> FadeHandle {
+        let target = Target::Position(self.get_position(), position);
+        fade::start(self.al_source(), target, duration, curve, false)
+    }
+
+    /**
+     * Cancel all the fades and glides running on the Audio Source.
+     *
+     * The parameters keep the value they had at the last update.
+     */
+    fn cancel_fades(&mut self) {
+        fade::cancel(self.al_source());
+    }
+
+    /**
+     * Set the playback position in the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `offset` - The frame to seek to, should be positive
+     *
+     * # Return
+     * `Ok(())` if the offset was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_offset(&mut self, offset: i32) -> Result<(), SoundError> {
+        check_value("offset", offset, offset >= 0)?;
+        check_al_call(|| self.set_offset(offset))
+    }
+
+    /**
+     * Set the volume of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `volume` - The volume of the Audio Source, should be positive and finite
+     *
+     * # Return
+     * `Ok(())` if the volume was set, `Err(SoundError)` otherwise
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, Sound, SoundError};
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+     *     assert!(snd.try_set_volume(-1.).is_err());
+     *     snd.try_set_volume(0.5)?;
+     *     Ok(())
+     * }
+     * ```
+     */
+    fn try_set_volume(&mut self, volume: f32) -> Result<(), SoundError> {
+        check_value("volume", volume, volume >= 0. && volume.is_finite())?;
+        check_al_call(|| self.set_volume(volume))
+    }
+
+    /**
+     * Set the minimal volume of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `min_volume` - The new minimal volume, should be between 0.0 and 1.0
+     *
+     * # Return
+     * `Ok(())` if the minimal volume was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_min_volume(&mut self, min_volume: f32) -> Result<(), SoundError> {
+        check_value("min_volume", min_volume, in_unit_range(min_volume))?;
+        check_al_call(|| self.set_min_volume(min_volume))
+    }
+
+    /**
+     * Set the maximal volume of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `max_volume` - The new maximal volume, should be between 0.0 and 1.0
+     *
+     * # Return
+     * `Ok(())` if the maximal volume was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_max_volume(&mut self, max_volume: f32) -> Result<(), SoundError> {
+        check_value("max_volume", max_volume, in_unit_range(max_volume))?;
+        check_al_call(|| self.set_max_volume(max_volume))
+    }
+
+    /**
+     * Set the pitch of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `pitch` - The new pitch, should be strictly positive
+     *
+     * # Return
+     * `Ok(())` if the pitch was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_pitch(&mut self, pitch: f32) -> Result<(), SoundError> {
+        check_value("pitch", pitch, pitch > 0. && pitch.is_finite())?;
+        check_al_call(|| self.set_pitch(pitch))
+    }
+
+    /**
+     * Set the Audio Source location in three dimensional space, reporting
+     * errors.
+     *
+     * # Argument
+     * * `position` - The new position [x, y, z], should be finite
+     *
+     * # Return
+     * `Ok(())` if the position was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_position(&mut self, position: [f32; 3]) -> Result<(), SoundError> {
+        check_value("position", position, is_finite_vector(position))?;
+        check_al_call(|| self.set_position(position))
+    }
+
+    /**
+     * Set the velocity of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `velocity` - The new velocity [x, y, z], should be finite
+     *
+     * # Return
+     * `Ok(())` if the velocity was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_velocity(&mut self, velocity: [f32; 3]) -> Result<(), SoundError> {
+        check_value("velocity", velocity, is_finite_vector(velocity))?;
+        check_al_call(|| self.set_velocity(velocity))
+    }
+
+    /**
+     * Set the direction of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `direction` - The new direction [x, y, z], should be finite
+     *
+     * # Return
+     * `Ok(())` if the direction was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_direction(&mut self, direction: [f32; 3]) -> Result<(), SoundError> {
+        check_value("direction", direction, is_finite_vector(direction))?;
+        check_al_call(|| self.set_direction(direction))
+    }
+
+    /**
+     * Set the sound cone of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `cone` - The new cone, with angles in the range [0.0, 360.0] and
+     *   gains in the range [0.0, 1.0]
+     *
+     * # Return
+     * `Ok(())` if the cone was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_cone(&mut self, cone: Cone) -> Result<(), SoundError> {
+        let is_angle = |angle: f32| (0. ..=360.).contains(&angle);
+        check_value(
+            "cone",
+            cone,
+            is_angle(cone.inner_angle)
+                && is_angle(cone.outer_angle)
+                && in_unit_range(cone.outer_gain)
+                && in_unit_range(cone.outer_gainhf),
+        )?;
+        check_al_call(|| self.set_cone(cone))
+    }
+
+    /**
+     * Set the angles of the channels of a stereo Audio Source, reporting
+     * errors.
+     *
+     * # Argument
+     * * `angles` - The angles of the [left, right] channels in radians,
+     *   should be finite
+     *
+     * # Return
+     * `Ok(())` if the angles were set, `Err(SoundError)` otherwise
+     */
+    fn try_set_stereo_angles(&mut self, angles: [f32; 2]) -> Result<(), SoundError> {
+        check_value(
+            "stereo_angles",
+            angles,
+            angles.iter().all(|angle| angle.is_finite()),
+        )?;
+        check_al_call(|| self.set_stereo_angles(angles))
+    }
+
+    /**
+     * Set the radius of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `radius` - The new radius in the range [0.0, +inf]
+     *
+     * # Return
+     * `Ok(())` if the radius was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_radius(&mut self, radius: f32) -> Result<(), SoundError> {
+        check_value("radius", radius, radius >= 0.)?;
+        check_al_call(|| self.set_radius(radius))
+    }
+
+    /**
+     * Set the maximum distance of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `max_distance` - The new maximum distance in the range [0.0, +inf]
+     *
+     * # Return
+     * `Ok(())` if the maximum distance was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_max_distance(&mut self, max_distance: f32) -> Result<(), SoundError> {
+        check_value("max_distance", max_distance, max_distance >= 0.)?;
+        check_al_call(|| self.set_max_distance(max_distance))
+    }
+
+    /**
+     * Set the reference distance of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `ref_distance` - The new reference distance in the range [0.0, +inf]
+     *
+     * # Return
+     * `Ok(())` if the reference distance was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_reference_distance(&mut self, ref_distance: f32) -> Result<(), SoundError> {
+        check_value("reference_distance", ref_distance, ref_distance >= 0.)?;
+        check_al_call(|| self.set_reference_distance(ref_distance))
+    }
+
+    /**
+     * Set the attenuation of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `attenuation` - The new attenuation in the range [0.0, +inf]
+     *
+     * # Return
+     * `Ok(())` if the attenuation was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_attenuation(&mut self, attenuation: f32) -> Result<(), SoundError> {
+        check_value("attenuation", attenuation, attenuation >= 0.)?;
+        check_al_call(|| self.set_attenuation(attenuation))
+    }
+
+    /**
+     * Set the gain of the reverb send of the Audio Source, reporting errors.
+     *
+     * # Argument
+     * * `gain` - The gain of the reverb send in the range [0.0, 1.0]
+     *
+     * # Return
+     * `Ok(())` if the reverb send was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_reverb_send(&mut self, gain: f32) -> Result<(), SoundError> {
+        check_value("reverb_send", gain, in_unit_range(gain))?;
+        check_al_call(|| self.set_reverb_send(gain))
+    }
+
+    /**
+     * Connect an auxiliary send of the Audio Source to a ReverbEffect,
+     * reporting errors.
+     *
+     * # Arguments
+     * * `index` - The index of the send, in the range [0, max_auxiliary_sends[
+     * * `reverb_effect` - The ReverbEffect to connect, or None to disconnect
+     *   the send
+     * * `filter` - The Filter of the send, or None to leave it unfiltered
+     *
+     * # Return
+     * `Ok(())` if the send was connected, `Err(SoundError)` otherwise
+     */
+    fn try_connect_send(
+        &mut self,
+        index: i32,
+        reverb_effect: Option<&ReverbEffect>,
+        filter: Option<&Filter>,
+    ) -> Result<(), SoundError> {
+        try_openal_context!();
+
+        let sends = sends::max_auxiliary_sends();
+        check_value("send index", index, index >= 0 && index < sends)?;
+        check_al_call(|| self.connect_send(index, reverb_effect, filter))
+    }
+
+    /**
+     * Set the gain of the signal of an auxiliary send, reporting errors.
+     *
+     * # Arguments
+     * * `index` - The index of the send, in the range [0, max_auxiliary_sends[
+     * * `gain` - The gain of the send in the range [0.0, 1.0]
+     *
+     * # Return
+     * `Ok(())` if the gain was set, `Err(SoundError)` otherwise
+     */
+    fn try_set_send_gain(&mut self, index: i32, gain: f32) -> Result<(), SoundError> {
+        try_openal_context!();
+
+        let sends = sends::max_auxiliary_sends();
+        check_value("send index", index, index >= 0 && index < sends)?;
+        check_value("send gain", gain, in_unit_range(gain))?;
+        check_al_call(|| self.set_send_gain(index, gain))
+    }
+
+    /**
+     * Make an auxiliary send of the Audio Source follow the ReverbZones,
+     * reporting errors.
+     *
+     * # Argument
+     * * `index` - The index of the send, in the range
+     *   [0, max_auxiliary_sends[, or None to disconnect it from the zones
+     *
+     * # Return
+     * `Ok(())` if the send follows the zones, `Err(SoundError)` otherwise
+     */
+    fn try_set_reverb_zone_send(&mut self, index: Option<i32>) -> Result<(), SoundError> {
+        try_openal_context!();
+
+        if let Some(index) = index {
+            let sends = sends::max_auxiliary_sends();
+            check_value("send index", index, index >= 0 && index < sends)?;
+        }
+        check_al_call(|| self.set_reverb_zone_send(index))
+    }
+}
+
+// Build the error of a rejected value
+fn check_value<T: fmt::Debug>(name: &'static str, value: T, valid: bool) -> Result<(), SoundError> {
+    match valid {
+        true => Ok(()),
+        false => Err(SoundError::InvalidValue(name, format!("{:?}", value))),
+    }
+}
+
+fn in_unit_range(value: f32) -> bool {
+    (0. ..=1.).contains(&value)
+}
+
+fn is_finite_vector(vector: [f32; 3]) -> bool {
+    vector.iter().all(|v| v.is_finite())
+}
+
+// Run an OpenAL call, reporting the error it raised if any. The update is
+// held off meanwhile, the error read can't come from the ticker.
+fn check_al_call<F: FnOnce()>(call: F) -> Result<(), SoundError> {
+    try_openal_context!();
+    let _update = ticker::UPDATE.lock();
+
+    // Clear the errors raised by previous calls
+    al::openal_has_error();
+
+    call();
+
+    match al::openal_has_error() {
+        Some(err) => Err(SoundError::InternalOpenALError(err)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::audio_controller::{check_value, in_unit_range, is_finite_vector};
+    use crate::error::SoundError;
+
+    #[test]
+    fn check_value_OK() {
+        assert!(check_value("volume", 0.5, in_unit_range(0.5)).is_ok());
+        assert!(check_value("position", [1., 2., 3.], is_finite_vector([1., 2., 3.])).is_ok());
+    }
+
+    #[test]
+    fn check_value_FAIL() {
+        match check_value("min_volume", 1.5, in_unit_range(1.5)) {
+            Err(SoundError::InvalidValue(name, value)) => {
+                assert_eq!(name, "min_volume");
+                assert_eq!(value, "1.5");
+            }
+            _ => panic!("1.5 accepted as a min_volume"),
+        }
+        assert!(!in_unit_range(f32::NAN));
+        assert!(!is_finite_vector([0., f32::INFINITY, 0.]));
+    }
 }
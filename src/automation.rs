@@ -0,0 +1,419 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Keyframed automation of the Audio Sources parameters.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicI64;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::clock;
+use crate::fade::{lerp, Curve};
+use crate::internal::OpenAlData;
+use crate::mixer;
+use crate::music;
+use crate::openal::{al, ffi};
+use crate::sends;
+use crate::sndfile::SndInfo;
+
+/// How the value of an automation moves from a keyframe to the next one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    /// Keep the value of the keyframe until the next keyframe.
+    Step,
+    /// Move linearly to the value of the next keyframe.
+    Linear,
+    /// Move to the value of the next keyframe following a curve.
+    Curve(Curve),
+}
+
+/// A value that can be automated.
+pub trait Interpolate: Copy {
+    /// Get the value at `progress` between `from` and `to`.
+    fn interpolate(from: Self, to: Self, progress: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(from: f32, to: f32, progress: f32) -> f32 {
+        lerp(from, to, progress)
+    }
+}
+
+impl Interpolate for [f32; 3] {
+    fn interpolate(from: [f32; 3], to: [f32; 3], progress: f32) -> [f32; 3] {
+        [
+            lerp(from[0], to[0], progress),
+            lerp(from[1], to[1], progress),
+            lerp(from[2], to[2], progress),
+        ]
+    }
+}
+
+/// A value of an automation at a point of the playback.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T> {
+    /// The playback position of the keyframe
+    pub time: Duration,
+    /// The value of the parameter at this position
+    pub value: T,
+    /// How to move to the value of the next keyframe
+    pub interpolation: Interpolation,
+}
+
+/**
+ * Automate a parameter of an Audio Source along keyframes.
+ *
+ * An Automation is evaluated against the playback position of the Audio
+ * Source it's attached to, so it stays in sync when the Audio Source is
+ * paused or seeked. Before the first keyframe the value of the first
+ * keyframe is used, after the last keyframe the value of the last keyframe.
+ *
+ * Like fades, automations are applied when `ears::update` is called or by
+ * the background ticker.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Automation, Interpolation, Sound, SoundError};
+ * use std::time::Duration;
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let mut volume = Automation::new();
+ *     volume.add_keyframe(Duration::from_secs(0), 0.0, Interpolation::Linear);
+ *     volume.add_keyframe(Duration::from_secs(2), 1.0, Interpolation::Step);
+ *     volume.add_keyframe(Duration::from_secs(5), 0.3, Interpolation::Step);
+ *
+ *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+ *     snd.automate_volume(Some(volume));
+ *     snd.play();
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Automation<T = f32> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate> Automation<T> {
+    /// Create an Automation without keyframes.
+    pub fn new() -> Automation<T> {
+        Automation {
+            keyframes: Vec::new(),
+        }
+    }
+
+    /**
+     * Add a keyframe to the Automation.
+     *
+     * Keyframes are kept sorted by time, a keyframe added at the time of an
+     * existing keyframe replaces it.
+     *
+     * # Arguments
+     * * `time` - The playback position of the keyframe
+     * * `value` - The value of the parameter at this position
+     * * `interpolation` - How to move to the value of the next keyframe
+     */
+    pub fn add_keyframe(&mut self, time: Duration, value: T, interpolation: Interpolation) {
+        let keyframe = Keyframe {
+            time,
+            value,
+            interpolation,
+        };
+
+        match self.keyframes.binary_search_by(|k| k.time.cmp(&time)) {
+            Ok(index) => self.keyframes[index] = keyframe,
+            Err(index) => self.keyframes.insert(index, keyframe),
+        }
+    }
+
+    /// Get the keyframes of the Automation, sorted by time.
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /**
+     * Evaluate the Automation.
+     *
+     * # Argument
+     * * `time` - The playback position
+     *
+     * # Return
+     * The value of the parameter at `time`, or None if the Automation has
+     * no keyframes.
+     */
+    pub fn value_at(&self, time: Duration) -> Option<T> {
+        let next = self.keyframes.iter().position(|k| k.time > time);
+
+        match next {
+            None => self.keyframes.last().map(|k| k.value),
+            Some(0) => Some(self.keyframes[0].value),
+            Some(index) => {
+                let from = &self.keyframes[index - 1];
+                let to = &self.keyframes[index];
+                let span = (to.time - from.time).as_secs_f32();
+                let progress = (time - from.time).as_secs_f32() / span;
+
+                Some(match from.interpolation {
+                    Interpolation::Step => from.value,
+                    Interpolation::Linear => T::interpolate(from.value, to.value, progress),
+                    Interpolation::Curve(curve) => {
+                        T::interpolate(from.value, to.value, curve.apply(progress))
+                    }
+                })
+            }
+        }
+    }
+}
+
+impl<T: Interpolate> Default for Automation<T> {
+    fn default() -> Automation<T> {
+        Automation::new()
+    }
+}
+
+/// Where the playback position of an Audio Source is read from.
+#[doc(hidden)]
+#[derive(Clone)]
+pub enum PlaybackClock {
+    /// A source playing a single buffer
+    Source(u32),
+    /// A source streaming a file, see Music
+    Stream {
+        al_source: u32,
+        cursor: Arc<AtomicI64>,
+        buffer_size: i64,
+        file_infos: SndInfo,
+    },
+}
+
+impl PlaybackClock {
+    /// The playback position of the source, and the time it takes to reach
+    /// the speakers.
+    pub(crate) fn position_with_latency(&self) -> (Duration, Duration) {
+        match self {
+            PlaybackClock::Source(al_source) => {
+                let (offset, latency) = clock::source_offset_with_latency(*al_source);
+                (
+                    Duration::from_secs_f64(offset),
+                    Duration::from_secs_f64(latency),
+                )
+            }
+            PlaybackClock::Stream {
+                al_source,
+                cursor,
+                buffer_size,
+                file_infos,
+            } => {
+                let (offset, latency) = clock::source_offset_with_latency(*al_source);
+                let sample_rate = file_infos.samplerate as f64;
+                let sample_offset = (offset * sample_rate).round() as i32;
+                let frame = music::stream_offset_at(
+                    *al_source,
+                    cursor,
+                    *buffer_size,
+                    file_infos,
+                    sample_offset,
+                );
+                (
+                    Duration::from_secs_f64(frame as f64 / sample_rate),
+                    Duration::from_secs_f64(latency),
+                )
+            }
+        }
+    }
+
+    /// The playback position of the source.
+    pub(crate) fn position(&self) -> Duration {
+        match self {
+            PlaybackClock::Source(al_source) => {
+                let mut offset = 0.;
+                al::alGetSourcef(*al_source, ffi::AL_SEC_OFFSET, &mut offset);
+                Duration::from_secs_f32(offset.max(0.))
+            }
+            PlaybackClock::Stream {
+                al_source,
+                cursor,
+                buffer_size,
+                file_infos,
+            } => {
+                let frame = music::stream_offset(*al_source, cursor, *buffer_size, file_infos);
+                Duration::from_secs_f64(frame as f64 / file_infos.samplerate as f64)
+            }
+        }
+    }
+}
+
+struct Automations {
+    clock: PlaybackClock,
+    volume: Option<Automation<f32>>,
+    pitch: Option<Automation<f32>>,
+    position: Option<Automation<[f32; 3]>>,
+    reverb_send: Option<Automation<f32>>,
+}
+
+impl Automations {
+    fn is_empty(&self) -> bool {
+        self.volume.is_none()
+            && self.pitch.is_none()
+            && self.position.is_none()
+            && self.reverb_send.is_none()
+    }
+}
+
+lazy_static! {
+    static ref AUTOMATIONS: Mutex<HashMap<u32, Automations>> = Mutex::new(HashMap::new());
+}
+
+/// Change the automations attached to a source.
+fn attach<F: FnOnce(&mut Automations)>(al_source: u32, clock: PlaybackClock, f: F) {
+    if let Ok(mut automations) = AUTOMATIONS.lock() {
+        let entry = automations.entry(al_source).or_insert(Automations {
+            clock: clock.clone(),
+            volume: None,
+            pitch: None,
+            position: None,
+            reverb_send: None,
+        });
+        entry.clock = clock;
+        f(entry);
+        if entry.is_empty() {
+            automations.remove(&al_source);
+        }
+    }
+}
+
+pub(crate) fn set_volume(al_source: u32, clock: PlaybackClock, automation: Option<Automation>) {
+    attach(al_source, clock, |a| a.volume = automation);
+}
+
+pub(crate) fn set_pitch(al_source: u32, clock: PlaybackClock, automation: Option<Automation>) {
+    attach(al_source, clock, |a| a.pitch = automation);
+}
+
+pub(crate) fn set_position(
+    al_source: u32,
+    clock: PlaybackClock,
+    automation: Option<Automation<[f32; 3]>>,
+) {
+    attach(al_source, clock, |a| a.position = automation);
+}
+
+pub(crate) fn set_reverb_send(
+    al_source: u32,
+    clock: PlaybackClock,
+    automation: Option<Automation>,
+) {
+    attach(al_source, clock, |a| a.reverb_send = automation);
+}
+
+/// Drop the automations of a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut automations) = AUTOMATIONS.lock() {
+        automations.remove(&al_source);
+    }
+}
+
+/// Apply the automations of all the sources at their current playback
+/// position.
+pub(crate) fn update() {
+    check_openal_context!(());
+
+    let automations = match AUTOMATIONS.lock() {
+        Ok(automations) => automations,
+        Err(_) => return,
+    };
+
+    for (al_source, automation) in automations.iter() {
+        let time = automation.clock.position();
+
+        if let Some(volume) = automation.volume.as_ref().and_then(|a| a.value_at(time)) {
+            mixer::set_volume(*al_source, volume);
+        }
+        if let Some(pitch) = automation.pitch.as_ref().and_then(|a| a.value_at(time)) {
+            mixer::set_pitch(*al_source, pitch);
+        }
+        if let Some(position) = automation.position.as_ref().and_then(|a| a.value_at(time)) {
+            al::alSourcefv(*al_source, ffi::AL_POSITION, &position[0]);
+        }
+        if let Some(gain) = automation
+            .reverb_send
+            .as_ref()
+            .and_then(|a| a.value_at(time))
+        {
+            sends::set_gain(*al_source, 0, gain);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::automation::{Automation, Interpolation};
+    use crate::fade::Curve;
+    use std::time::Duration;
+
+    fn secs(secs: f32) -> Duration {
+        Duration::from_secs_f32(secs)
+    }
+
+    #[test]
+    fn automation_empty_OK() {
+        let automation: Automation = Automation::new();
+
+        assert_eq!(automation.value_at(secs(1.)), None);
+    }
+
+    #[test]
+    fn automation_bounds_OK() {
+        let mut automation = Automation::new();
+        automation.add_keyframe(secs(1.), 0.2, Interpolation::Linear);
+        automation.add_keyframe(secs(2.), 0.8, Interpolation::Linear);
+
+        assert_eq!(automation.value_at(secs(0.)), Some(0.2));
+        assert_eq!(automation.value_at(secs(3.)), Some(0.8));
+    }
+
+    #[test]
+    fn automation_interpolation_OK() {
+        let mut automation = Automation::new();
+        automation.add_keyframe(secs(0.), 0., Interpolation::Linear);
+        automation.add_keyframe(secs(2.), 1., Interpolation::Step);
+        automation.add_keyframe(secs(4.), 0., Interpolation::Curve(Curve::EaseIn));
+        automation.add_keyframe(secs(6.), 1., Interpolation::Step);
+
+        assert_eq!(automation.value_at(secs(1.)), Some(0.5));
+        assert_eq!(automation.value_at(secs(3.)), Some(1.));
+        assert_eq!(automation.value_at(secs(5.)), Some(0.25));
+    }
+
+    #[test]
+    fn automation_keyframes_sorted_OK() {
+        let mut automation = Automation::new();
+        automation.add_keyframe(secs(2.), [1., 1., 1.], Interpolation::Linear);
+        automation.add_keyframe(secs(0.), [0., 0., 0.], Interpolation::Linear);
+        automation.add_keyframe(secs(2.), [2., 2., 2.], Interpolation::Linear);
+
+        assert_eq!(automation.keyframes().len(), 2);
+        assert_eq!(automation.value_at(secs(1.)), Some([1., 1., 1.]));
+    }
+}
@@ -0,0 +1,559 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Groups of Audio Sources sharing a volume, a pitch and a pause state.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::clock;
+use crate::internal::OpenAlData;
+use crate::mixer;
+use crate::mixer::Stage;
+use crate::openal::{al, ffi};
+use crate::sends;
+
+/**
+ * A group of Audio Sources, like "SFX", "Music" or "Voice".
+ *
+ * The volume, mute state and pitch of a Bus apply to all the Audio Sources
+ * assigned to it with `AudioController::set_bus`, on top of their own
+ * volume and pitch, and to all the Buses created with it as parent. A Bus is
+ * a handle: clones refer to the same Bus, which lives as long as one of its
+ * handles or one of its child Buses exists. When the last handle is
+ * dropped, its Audio Sources go back to their own volume and pitch.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Bus, Music, Sound, SoundError};
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let master = Bus::new();
+ *     let sfx = Bus::with_parent(&master);
+ *     let music = Bus::with_parent(&master);
+ *
+ *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+ *     snd.set_bus(Some(&sfx));
+ *     let mut msc = Music::new("path/to/the/music.ogg")?;
+ *     msc.set_bus(Some(&music));
+ *
+ *     // The options menu
+ *     master.set_volume(0.8);
+ *     music.set_volume(0.5);
+ *
+ *     // The pause menu
+ *     sfx.pause();
+ *     sfx.resume();
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Clone)]
+pub struct Bus {
+    handle: Arc<BusHandle>,
+}
+
+struct BusHandle {
+    id: u64,
+    /// Keeps the parent alive as long as its children
+    parent: Option<Bus>,
+}
+
+struct BusState {
+    parent: Option<u64>,
+    handle: Weak<BusHandle>,
+    volume: f32,
+    muted: bool,
+    pitch: f32,
+    paused: bool,
+    /// The sources paused by `Bus::pause`, to resume
+    paused_sources: Vec<u32>,
+    /// The volume, pitch and reverb send factors of the mix snapshots
+    snapshot: (f32, f32, f32),
+}
+
+struct Buses {
+    buses: HashMap<u64, BusState>,
+    /// The Bus of each source
+    members: HashMap<u32, u64>,
+}
+
+impl Buses {
+    /// Whether a bus is the given ancestor or one of its descendants.
+    fn descends_from(&self, mut id: u64, ancestor: u64) -> bool {
+        loop {
+            if id == ancestor {
+                return true;
+            }
+            match self.buses.get(&id).and_then(|bus| bus.parent) {
+                Some(parent) => id = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// The bus itself or the closest of its ancestors paused by
+    /// `Bus::pause`.
+    fn paused_ancestor(&self, mut id: u64) -> Option<u64> {
+        while let Some(bus) = self.buses.get(&id) {
+            if bus.paused {
+                return Some(id);
+            }
+            id = bus.parent?;
+        }
+        None
+    }
+
+    /// The gain, pitch and reverb send gain of a bus, multiplied down the
+    /// hierarchy.
+    fn factors(&self, mut id: u64) -> (f32, f32, f32) {
+        let mut gain = 1.;
+        let mut pitch = 1.;
+        let mut send = 1.;
+        while let Some(bus) = self.buses.get(&id) {
+            let (snapshot_volume, snapshot_pitch, snapshot_send) = bus.snapshot;
+            gain *= if bus.muted { 0. } else { bus.volume } * snapshot_volume;
+            pitch *= bus.pitch * snapshot_pitch;
+            send *= snapshot_send;
+            match bus.parent {
+                Some(parent) => id = parent,
+                None => break,
+            }
+        }
+        (gain, pitch, send)
+    }
+
+    /// Apply the factors of a bus to the sources of the bus and of its
+    /// descendants.
+    fn apply(&self, id: u64) {
+        for (al_source, bus) in self.members.iter() {
+            if self.descends_from(*bus, id) {
+                let (gain, pitch, send) = self.factors(*bus);
+                mixer::set_gain(*al_source, Stage::Bus, Some(gain));
+                mixer::set_pitch_factor(*al_source, Stage::Bus, Some(pitch));
+                sends::set_factor(*al_source, send);
+            }
+        }
+    }
+}
+
+lazy_static! {
+    static ref BUSES: Mutex<Buses> = Mutex::new(Buses {
+        buses: HashMap::new(),
+        members: HashMap::new(),
+    });
+    static ref NEXT_BUS: AtomicU64 = AtomicU64::new(0);
+}
+
+impl Bus {
+    /// Create a Bus without parent, like a master Bus.
+    pub fn new() -> Bus {
+        Bus::create(None)
+    }
+
+    /**
+     * Create a Bus inside another one.
+     *
+     * # Argument
+     * * `parent` - The Bus scaling the volume and pitch of the new Bus
+     */
+    pub fn with_parent(parent: &Bus) -> Bus {
+        Bus::create(Some(parent.clone()))
+    }
+
+    fn create(parent: Option<Bus>) -> Bus {
+        let handle = Arc::new(BusHandle {
+            id: NEXT_BUS.fetch_add(1, Ordering::Relaxed),
+            parent,
+        });
+
+        if let Ok(mut buses) = BUSES.lock() {
+            buses.buses.insert(
+                handle.id,
+                BusState {
+                    parent: handle.parent.as_ref().map(|parent| parent.id()),
+                    handle: Arc::downgrade(&handle),
+                    volume: 1.,
+                    muted: false,
+                    pitch: 1.,
+                    paused: false,
+                    paused_sources: Vec::new(),
+                    snapshot: (1., 1., 1.),
+                },
+            );
+        }
+
+        Bus { handle }
+    }
+
+    fn id(&self) -> u64 {
+        self.handle.id
+    }
+
+    pub(crate) fn from_id(id: u64) -> Option<Bus> {
+        match BUSES.lock() {
+            Ok(buses) => buses
+                .buses
+                .get(&id)
+                .and_then(|bus| bus.handle.upgrade())
+                .map(|handle| Bus { handle }),
+            Err(_) => None,
+        }
+    }
+
+    /// Get the parent of the Bus, None for a Bus created with `Bus::new`.
+    pub fn parent(&self) -> Option<Bus> {
+        self.handle.parent.clone()
+    }
+
+    fn update<F: FnOnce(&mut BusState)>(&self, change: F) {
+        check_openal_context!(());
+
+        if let Ok(mut buses) = BUSES.lock() {
+            if let Some(bus) = buses.buses.get_mut(&self.id()) {
+                change(bus);
+            }
+            buses.apply(self.id());
+        }
+    }
+
+    fn read<T, F: FnOnce(&BusState) -> T>(&self, read: F, default: T) -> T {
+        match BUSES.lock() {
+            Ok(buses) => buses.buses.get(&self.id()).map(read).unwrap_or(default),
+            Err(_) => default,
+        }
+    }
+
+    /**
+     * Set the volume of the Bus.
+     *
+     * The volume of the Audio Sources of the Bus is multiplied by the volume
+     * of the Bus and of all its parents.
+     *
+     * Default volume is 1.0.
+     *
+     * # Argument
+     * * `volume` - The new volume of the Bus, in the range [0.0, 1.0]
+     */
+    pub fn set_volume(&self, volume: f32) {
+        self.update(|bus| bus.volume = volume);
+    }
+
+    /// Get the volume of the Bus, without its parents.
+    pub fn get_volume(&self) -> f32 {
+        self.read(|bus| bus.volume, 1.)
+    }
+
+    /**
+     * Mute or unmute the Bus.
+     *
+     * A muted Bus silences its Audio Sources and its children without
+     * changing their volume.
+     *
+     * # Argument
+     * * `muted` - true to mute the Bus, false to unmute it
+     */
+    pub fn set_muted(&self, muted: bool) {
+        self.update(|bus| bus.muted = muted);
+    }
+
+    /// Check if the Bus is muted, without its parents.
+    pub fn is_muted(&self) -> bool {
+        self.read(|bus| bus.muted, false)
+    }
+
+    /**
+     * Set the pitch of the Bus.
+     *
+     * The pitch of the Audio Sources of the Bus is multiplied by the pitch
+     * of the Bus and of all its parents, slowing down or speeding up a whole
+     * group at once.
+     *
+     * Default pitch is 1.0.
+     *
+     * # Argument
+     * * `pitch` - The new pitch multiplier of the Bus
+     */
+    pub fn set_pitch(&self, pitch: f32) {
+        self.update(|bus| bus.pitch = pitch);
+    }
+
+    /// Get the pitch of the Bus, without its parents.
+    pub fn get_pitch(&self) -> f32 {
+        self.read(|bus| bus.pitch, 1.)
+    }
+
+    /**
+     * Pause the playing Audio Sources of the Bus and of its children.
+     *
+     * Only the Audio Sources paused here are played again by `resume`, the
+     * ones which were already paused or stopped stay so. The playing Audio
+     * Sources assigned to the Bus while it is paused are paused too.
+     */
+    pub fn pause(&self) {
+        check_openal_context!(());
+
+        let mut buses = match BUSES.lock() {
+            Ok(buses) => buses,
+            Err(_) => return,
+        };
+
+        let playing: Vec<u32> = buses
+            .members
+            .iter()
+            .filter(|(_, bus)| buses.descends_from(**bus, self.id()))
+            .map(|(al_source, _)| *al_source)
+            .filter(|al_source| {
+                let mut state = 0;
+                al::alGetSourcei(*al_source, ffi::AL_SOURCE_STATE, &mut state);
+                state == ffi::AL_PLAYING
+            })
+            .collect();
+
+        for al_source in playing.iter() {
+            al::alSourcePause(*al_source);
+        }
+
+        if let Some(bus) = buses.buses.get_mut(&self.id()) {
+            bus.paused = true;
+            bus.paused_sources.extend(playing);
+        }
+    }
+
+    /// Play again the Audio Sources paused by `pause`, all at once.
+    pub fn resume(&self) {
+        check_openal_context!(());
+
+        let mut buses = match BUSES.lock() {
+            Ok(buses) => buses,
+            Err(_) => return,
+        };
+
+        if let Some(bus) = buses.buses.get_mut(&self.id()) {
+            let paused: Vec<u32> = bus
+                .paused_sources
+                .drain(..)
+                .filter(|al_source| {
+                    let mut state = 0;
+                    al::alGetSourcei(*al_source, ffi::AL_SOURCE_STATE, &mut state);
+                    state == ffi::AL_PAUSED
+                })
+                .collect();
+            bus.paused = false;
+            clock::start(&paused, None);
+        }
+    }
+
+    /// Check if the Bus is paused by `pause`.
+    pub fn is_paused(&self) -> bool {
+        self.read(|bus| bus.paused, false)
+    }
+
+    /// Set the volume, pitch and reverb send factors of the mix snapshots.
+    pub(crate) fn set_snapshot(&self, volume: f32, pitch: f32, send: f32) {
+        self.update(|bus| bus.snapshot = (volume, pitch, send));
+    }
+}
+
+impl Default for Bus {
+    fn default() -> Bus {
+        Bus::new()
+    }
+}
+
+impl PartialEq for Bus {
+    /// Two handles are equal if they refer to the same Bus.
+    fn eq(&self, other: &Bus) -> bool {
+        Arc::ptr_eq(&self.handle, &other.handle)
+    }
+}
+
+impl fmt::Debug for Bus {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Bus")
+            .field("volume", &self.get_volume())
+            .field("muted", &self.is_muted())
+            .field("pitch", &self.get_pitch())
+            .field("parent", &self.parent())
+            .finish()
+    }
+}
+
+impl Drop for BusHandle {
+    fn drop(&mut self) {
+        if let Ok(mut buses) = BUSES.lock() {
+            buses.buses.remove(&self.id);
+
+            let id = self.id;
+            let members: Vec<u32> = buses
+                .members
+                .iter()
+                .filter(|(_, bus)| **bus == id)
+                .map(|(al_source, _)| *al_source)
+                .collect();
+            for al_source in members {
+                buses.members.remove(&al_source);
+                mixer::set_gain(al_source, Stage::Bus, None);
+                mixer::set_pitch_factor(al_source, Stage::Bus, None);
+                sends::set_factor(al_source, 1.);
+            }
+        }
+    }
+}
+
+/// Assign a source to a Bus, or remove it from its Bus with None.
+///
+/// A playing source assigned to a paused Bus is paused with it.
+pub(crate) fn set(al_source: u32, bus: Option<&Bus>) {
+    if let Ok(mut buses) = BUSES.lock() {
+        match bus {
+            Some(bus) => {
+                buses.members.insert(al_source, bus.id());
+                let (gain, pitch, send) = buses.factors(bus.id());
+                mixer::set_gain(al_source, Stage::Bus, Some(gain));
+                mixer::set_pitch_factor(al_source, Stage::Bus, Some(pitch));
+                sends::set_factor(al_source, send);
+
+                if let Some(paused) = buses.paused_ancestor(bus.id()) {
+                    let mut state = 0;
+                    al::alGetSourcei(al_source, ffi::AL_SOURCE_STATE, &mut state);
+                    if state == ffi::AL_PLAYING {
+                        al::alSourcePause(al_source);
+                        if let Some(paused) = buses.buses.get_mut(&paused) {
+                            paused.paused_sources.push(al_source);
+                        }
+                    }
+                }
+            }
+            None => {
+                if buses.members.remove(&al_source).is_some() {
+                    mixer::set_gain(al_source, Stage::Bus, None);
+                    mixer::set_pitch_factor(al_source, Stage::Bus, None);
+                    sends::set_factor(al_source, 1.);
+                }
+            }
+        }
+    }
+}
+
+/// The Bus of a source.
+pub(crate) fn bus(al_source: u32) -> Option<Bus> {
+    let id = match BUSES.lock() {
+        Ok(buses) => buses.members.get(&al_source).cloned(),
+        Err(_) => None,
+    };
+    id.and_then(Bus::from_id)
+}
+
+/// Remove a source about to be deleted from its Bus.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut buses) = BUSES.lock() {
+        buses.members.remove(&al_source);
+        for bus in buses.buses.values_mut() {
+            bus.paused_sources.retain(|source| *source != al_source);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use std::collections::HashMap;
+    use std::sync::Weak;
+
+    use crate::bus::{Bus, BusState, Buses};
+
+    fn state(parent: Option<u64>, volume: f32, pitch: f32) -> BusState {
+        BusState {
+            parent,
+            handle: Weak::new(),
+            volume,
+            muted: false,
+            pitch,
+            paused: false,
+            paused_sources: Vec::new(),
+            snapshot: (1., 1., 1.),
+        }
+    }
+
+    /// A master bus 0, with a child 1 and a grandchild 2.
+    fn hierarchy() -> Buses {
+        let mut buses = Buses {
+            buses: HashMap::new(),
+            members: HashMap::new(),
+        };
+        buses.buses.insert(0, state(None, 0.5, 2.));
+        buses.buses.insert(1, state(Some(0), 0.5, 0.5));
+        buses.buses.insert(2, state(Some(1), 0.8, 1.5));
+        buses
+    }
+
+    #[test]
+    fn bus_hierarchy_OK() {
+        let master = Bus::new();
+        let sfx = Bus::with_parent(&master);
+
+        assert_eq!(sfx.parent(), Some(master.clone()));
+        assert_eq!(master.parent(), None);
+        assert!(sfx != master);
+    }
+
+    #[test]
+    fn bus_factors_OK() {
+        let mut buses = hierarchy();
+
+        assert_eq!(buses.factors(0), (0.5, 2., 1.));
+        assert_eq!(buses.factors(1), (0.25, 1., 1.));
+        assert_eq!(buses.factors(2), (0.2, 1.5, 1.));
+
+        buses.buses.get_mut(&1).unwrap().snapshot = (0.5, 1., 0.25);
+        assert_eq!(buses.factors(2), (0.1, 1.5, 0.25));
+
+        buses.buses.get_mut(&0).unwrap().muted = true;
+        assert_eq!(buses.factors(2).0, 0.);
+        assert_eq!(buses.factors(2).1, 1.5);
+    }
+
+    #[test]
+    fn bus_descends_from_OK() {
+        let buses = hierarchy();
+
+        assert!(buses.descends_from(2, 0));
+        assert!(buses.descends_from(1, 1));
+        assert!(!buses.descends_from(0, 2));
+    }
+
+    #[test]
+    fn bus_paused_ancestor_OK() {
+        let mut buses = hierarchy();
+        assert_eq!(buses.paused_ancestor(2), None);
+
+        buses.buses.get_mut(&0).unwrap().paused = true;
+        assert_eq!(buses.paused_ancestor(2), Some(0));
+
+        buses.buses.get_mut(&1).unwrap().paused = true;
+        assert_eq!(buses.paused_ancestor(2), Some(1));
+        assert_eq!(buses.paused_ancestor(0), Some(0));
+    }
+}
@@ -0,0 +1,356 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! The device clock and the scheduled start of the Audio Sources.
+//!
+//! When OpenAL provides the AL_SOFT_source_start_delay extension, scheduled
+//! Audio Sources are started by OpenAL itself, on the exact sample matching
+//! the requested time of the device clock. Otherwise a background thread
+//! starts them once the clock reaches the requested time: the start is then
+//! only accurate to the mixing period of the device, usually 10 to 20
+//! milliseconds, plus a millisecond or so of thread scheduling latency.
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::audio_controller::AudioController;
+use crate::internal::OpenAlData;
+use crate::openal::{al, ffi};
+
+/// A software start of several sources together.
+struct Start {
+    id: u64,
+    clock_time: Duration,
+    al_sources: Vec<u32>,
+}
+
+struct Scheduler {
+    /// The pending start of each source
+    pending: HashMap<u32, u64>,
+    /// The starts waiting for their time of the device clock
+    starts: Vec<Start>,
+    /// Whether the scheduler thread is running
+    running: bool,
+}
+
+impl Scheduler {
+    /// Start the sources still pending for a start.
+    fn play(&mut self, start: Start) {
+        let al_sources: Vec<u32> = start
+            .al_sources
+            .into_iter()
+            .filter(|al_source| self.pending.get(al_source) == Some(&start.id))
+            .collect();
+        for al_source in al_sources.iter() {
+            self.pending.remove(al_source);
+        }
+        if !al_sources.is_empty() {
+            al::alSourcePlayv(al_sources.len() as i32, &al_sources[0]);
+        }
+    }
+
+    /// Forget the starts of which every source was cancelled.
+    fn purge(&mut self) {
+        let pending = &self.pending;
+        self.starts.retain(|start| {
+            start
+                .al_sources
+                .iter()
+                .any(|al_source| pending.get(al_source) == Some(&start.id))
+        });
+    }
+}
+
+lazy_static! {
+    static ref EPOCH: Instant = Instant::now();
+    static ref SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler {
+        pending: HashMap::new(),
+        starts: Vec::new(),
+        running: false,
+    });
+    /// Wakes the scheduler thread up when the starts change
+    static ref WAKE: Condvar = Condvar::new();
+}
+
+static NEXT_SCHEDULE: AtomicU64 = AtomicU64::new(0);
+
+/**
+ * Get the current time of the audio device clock.
+ *
+ * The clock starts when the device is opened and advances with the samples
+ * mixed by the device. It is the reference for `play_at` and
+ * `play_group_at`. If OpenAL doesn't provide the ALC_SOFT_device_clock
+ * extension, a monotonic system clock is used instead.
+ *
+ * # Return
+ * The time of the device clock
+ *
+ * # Example
+ * ```no_run
+ * use std::time::Duration;
+ *
+ * let in_half_a_second = ears::device_clock() + Duration::from_millis(500);
+ * ```
+ */
+pub fn device_clock() -> Duration {
+    check_openal_context!(Duration::from_secs(0));
+
+    match al::alcGetInteger64vSOFT(ffi::ALC_DEVICE_CLOCK_SOFT) {
+        Some(nanoseconds) => Duration::from_nanos(nanoseconds.max(0) as u64),
+        None => EPOCH.elapsed(),
+    }
+}
+
+/**
+ * Get the output latency of the audio device.
+ *
+ * This is the time a sample mixed by the device takes to reach the
+ * speakers. It requires the ALC_SOFT_device_clock extension, without it the
+ * latency is reported as zero.
+ *
+ * # Return
+ * The latency of the device
+ */
+pub fn device_latency() -> Duration {
+    check_openal_context!(Duration::from_secs(0));
+
+    match al::alcGetInteger64vSOFT(ffi::ALC_DEVICE_LATENCY_SOFT) {
+        Some(nanoseconds) => Duration::from_nanos(nanoseconds.max(0) as u64),
+        None => Duration::from_secs(0),
+    }
+}
+
+/**
+ * Start several Audio Sources together.
+ *
+ * All the Audio Sources start on the same sample, which calling `play` on
+ * each of them can't guarantee.
+ *
+ * # Argument
+ * * `sources` - The Audio Sources to start
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Music, SoundError};
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let mut drums = Music::new("path/to/the/drums.ogg")?;
+ *     let mut bass = Music::new("path/to/the/bass.ogg")?;
+ *     ears::play_group(&mut [&mut drums, &mut bass]);
+ *     Ok(())
+ * }
+ * ```
+ */
+pub fn play_group(sources: &mut [&mut dyn AudioController]) {
+    check_openal_context!(());
+
+    let al_sources = cue_all(sources);
+    start(&al_sources, None);
+}
+
+/**
+ * Start several Audio Sources together at a time of the device clock.
+ *
+ * The start is sample accurate when OpenAL provides the
+ * AL_SOFT_source_start_delay extension. Otherwise the Audio Sources are
+ * started by a background thread, which is only accurate to the mixing
+ * period of the device, usually 10 to 20 milliseconds.
+ *
+ * # Arguments
+ * * `sources` - The Audio Sources to start
+ * * `clock_time` - The time of the device clock to start at, as returned
+ *   by `device_clock`. The Audio Sources start right away if it is in the
+ *   past.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Music, SoundError};
+ * use std::time::Duration;
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let mut drums = Music::new("path/to/the/drums.ogg")?;
+ *     let mut bass = Music::new("path/to/the/bass.ogg")?;
+ *     let start = ears::device_clock() + Duration::from_millis(200);
+ *     ears::play_group_at(&mut [&mut drums, &mut bass], start);
+ *     Ok(())
+ * }
+ * ```
+ */
+pub fn play_group_at(sources: &mut [&mut dyn AudioController], clock_time: Duration) {
+    check_openal_context!(());
+
+    let al_sources = cue_all(sources);
+    start(&al_sources, Some(clock_time));
+}
+
+fn cue_all(sources: &mut [&mut dyn AudioController]) -> Vec<u32> {
+    sources
+        .iter_mut()
+        .map(|source| {
+            source.cue();
+            source.al_source()
+        })
+        .collect()
+}
+
+/// Start sources now, or at a time of the device clock. Replaces the
+/// pending scheduled starts of the sources.
+pub(crate) fn start(al_sources: &[u32], clock_time: Option<Duration>) {
+    for al_source in al_sources.iter() {
+        cancel(*al_source);
+    }
+
+    if al_sources.is_empty() {
+        return;
+    }
+
+    let clock_time = match clock_time {
+        Some(clock_time) if clock_time > device_clock() => clock_time,
+        _ => {
+            al::alSourcePlayv(al_sources.len() as i32, &al_sources[0]);
+            return;
+        }
+    };
+
+    // The start delay extension counts on the device clock, make sure it is
+    // the one `device_clock` reported.
+    if al::alcGetInteger64vSOFT(ffi::ALC_DEVICE_CLOCK_SOFT).is_some()
+        && al::alSourcePlayAtTimevSOFT(
+            al_sources.len() as i32,
+            &al_sources[0],
+            clock_time.as_nanos() as i64,
+        )
+    {
+        return;
+    }
+
+    schedule(al_sources.to_vec(), clock_time);
+}
+
+fn schedule(al_sources: Vec<u32>, clock_time: Duration) {
+    let id = NEXT_SCHEDULE.fetch_add(1, Ordering::Relaxed);
+
+    let mut scheduler = match SCHEDULER.lock() {
+        Ok(scheduler) => scheduler,
+        Err(_) => return,
+    };
+    for al_source in al_sources.iter() {
+        scheduler.pending.insert(*al_source, id);
+    }
+    scheduler.starts.push(Start {
+        id,
+        clock_time,
+        al_sources,
+    });
+
+    if scheduler.running {
+        WAKE.notify_one();
+        return;
+    }
+
+    let thread = thread::Builder::new().name(String::from("ears-scheduler"));
+    match thread.spawn(run_scheduler) {
+        Ok(_) => scheduler.running = true,
+        // Better late than never, start right away
+        Err(_) => {
+            for start in mem::take(&mut scheduler.starts) {
+                scheduler.play(start);
+            }
+        }
+    }
+}
+
+/// Start the scheduled sources once the device clock reaches their time.
+/// The thread exits when no start is left, `schedule` spawns a new one.
+fn run_scheduler() {
+    loop {
+        let now = device_clock();
+
+        // Keep the lock while starting, so a source can't be deleted in
+        // between
+        let mut scheduler = match SCHEDULER.lock() {
+            Ok(scheduler) => scheduler,
+            Err(_) => return,
+        };
+        scheduler.purge();
+        let next = match scheduler.starts.iter().map(|start| start.clock_time).min() {
+            Some(next) => next,
+            None => {
+                scheduler.running = false;
+                return;
+            }
+        };
+
+        if now >= next {
+            let (due, waiting): (Vec<Start>, Vec<Start>) = mem::take(&mut scheduler.starts)
+                .into_iter()
+                .partition(|start| start.clock_time <= now);
+            scheduler.starts = waiting;
+            for start in due {
+                scheduler.play(start);
+            }
+            continue;
+        }
+
+        // Sleep through most of the wait, then poll closely
+        let remaining = next - now;
+        let timeout = if remaining > Duration::from_millis(2) {
+            remaining - Duration::from_millis(1)
+        } else {
+            Duration::from_micros(100)
+        };
+        let _ = WAKE.wait_timeout(scheduler, timeout);
+    }
+}
+
+/// The playback position of a source in its buffer queue and the latency
+/// of its output, in seconds.
+///
+/// Without the AL_SOFT_source_latency extension the latency is zero.
+pub(crate) fn source_offset_with_latency(al_source: u32) -> (f64, f64) {
+    let mut values = [0.; 2];
+    if al::alGetSourcedvSOFT(al_source, ffi::AL_SEC_OFFSET_LATENCY_SOFT, &mut values[0]) {
+        (values[0].max(0.), values[1].max(0.))
+    } else {
+        let mut offset = 0.;
+        al::alGetSourcef(al_source, ffi::AL_SEC_OFFSET, &mut offset);
+        (offset.max(0.) as f64, 0.)
+    }
+}
+
+/// Cancel the pending software start of a source.
+pub(crate) fn cancel(al_source: u32) {
+    if let Ok(mut scheduler) = SCHEDULER.lock() {
+        if scheduler.pending.remove(&al_source).is_some() {
+            // Let the thread forget the start, instead of sleeping until it
+            WAKE.notify_one();
+        }
+    }
+}
+
+/// Drop the pending start of a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    cancel(al_source);
+}
@@ -0,0 +1,79 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! The directional cone of an Audio Source.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * The sound cone of a directional Audio Source.
+ *
+ * The cone is oriented along the direction of the Audio Source, see
+ * `AudioController::set_direction`. Inside the inner angle the Audio Source
+ * is heard at its full volume, outside the outer angle it is attenuated by
+ * the outer gains, and in between the gain is interpolated. The cone has no
+ * effect as long as the direction is [0.0, 0.0, 0.0].
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Cone, Sound, SoundError};
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     // A loudspeaker facing forward, muffled from behind
+ *     let mut snd = Sound::new("path/to/the/announcement.ogg")?;
+ *     snd.set_direction([0., 0., -1.]);
+ *     snd.set_cone(Cone {
+ *         inner_angle: 60.,
+ *         outer_angle: 180.,
+ *         outer_gain: 0.3,
+ *         outer_gainhf: 0.5,
+ *     });
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Cone {
+    /// The angle in degrees of the inner cone, in the range [0.0, 360.0]
+    pub inner_angle: f32,
+    /// The angle in degrees of the outer cone, in the range [0.0, 360.0]
+    pub outer_angle: f32,
+    /// The gain outside the outer cone, in the range [0.0, 1.0]
+    pub outer_gain: f32,
+    /// The gain of the high frequencies outside the outer cone, in the range
+    /// [0.0, 1.0]
+    pub outer_gainhf: f32,
+}
+
+impl Default for Cone {
+    /// The default cone of an OpenAL source, which is omnidirectional.
+    fn default() -> Cone {
+        Cone {
+            inner_angle: 360.,
+            outer_angle: 360.,
+            outer_gain: 0.,
+            outer_gainhf: 1.,
+        }
+    }
+}
@@ -0,0 +1,502 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Containers choosing a variation of a sound each time they are played.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::audio_controller::AudioController;
+use crate::error::SoundError;
+use crate::oneshot::{OneShotHandle, OneShotParams};
+use crate::sound::Sound;
+use crate::sound_data::SoundData;
+use crate::voice::{Voice, VoiceManager, VoiceParams};
+
+/// How a SoundContainer chooses the next variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerMode {
+    /// A random variant, following the weights of the variants, never one
+    /// of the last `no_repeat` played variants while others are available.
+    Random { no_repeat: usize },
+    /// All the variants in a random order, then all of them again in
+    /// another order, never playing the same variant twice in a row.
+    Shuffle,
+    /// All the variants in the order they were added, round-robin.
+    Sequential,
+}
+
+/**
+ * A variant chosen by a SoundContainer, with its randomized pitch and
+ * volume.
+ */
+#[derive(Clone)]
+pub struct Variant {
+    /// The SoundData of the variant
+    pub data: Rc<RefCell<SoundData>>,
+    /// The pitch to play it with
+    pub pitch: f32,
+    /// The volume to play it with
+    pub volume: f32,
+}
+
+/// A small xorshift generator, good enough to pick variants.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Rng {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(0);
+        Rng::with_seed(time ^ COUNTER.fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed))
+    }
+
+    fn with_seed(seed: u64) -> Rng {
+        // The state of a xorshift generator must not be 0
+        Rng(seed | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in the range [0.0, 1.0).
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A number in the range [min, max].
+    fn range(&mut self, (min, max): (f32, f32)) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// An index in the range [0, len).
+    fn index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+/// Chooses the index of the next variant.
+struct Selector {
+    mode: ContainerMode,
+    weights: Vec<f32>,
+    /// The last played indices, the most recent last
+    history: VecDeque<usize>,
+    /// The indices left to play in Shuffle mode, the next one last
+    bag: Vec<usize>,
+    cursor: usize,
+    rng: Rng,
+}
+
+impl Selector {
+    fn new(mode: ContainerMode) -> Selector {
+        Selector {
+            mode,
+            weights: Vec::new(),
+            history: VecDeque::new(),
+            bag: Vec::new(),
+            cursor: 0,
+            rng: Rng::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.history.clear();
+        self.bag.clear();
+        self.cursor = 0;
+    }
+
+    fn pick(&mut self) -> Option<usize> {
+        let len = self.weights.len();
+        if len == 0 {
+            return None;
+        }
+
+        let index = match self.mode {
+            ContainerMode::Random { no_repeat } => {
+                let mut candidates: Vec<usize> = (0..len)
+                    .filter(|index| self.weights[*index] > 0. && !self.history.contains(index))
+                    .collect();
+                if candidates.is_empty() {
+                    candidates = (0..len).filter(|index| self.weights[*index] > 0.).collect();
+                }
+                if candidates.is_empty() {
+                    return None;
+                }
+
+                let total: f32 = candidates.iter().map(|index| self.weights[*index]).sum();
+                let mut pick = self.rng.next_f32() * total;
+                let mut chosen = candidates[candidates.len() - 1];
+                for index in candidates {
+                    if pick < self.weights[index] {
+                        chosen = index;
+                        break;
+                    }
+                    pick -= self.weights[index];
+                }
+
+                self.history.push_back(chosen);
+                while self.history.len() > no_repeat {
+                    self.history.pop_front();
+                }
+                chosen
+            }
+            ContainerMode::Shuffle => {
+                if self.bag.is_empty() {
+                    self.bag = (0..len).collect();
+                    for i in (1..len).rev() {
+                        let j = self.rng.index(i + 1);
+                        self.bag.swap(i, j);
+                    }
+                    // Don't play the last variant of the previous round again
+                    if len > 1 && self.history.back() == self.bag.last() {
+                        self.bag.swap(0, len - 1);
+                    }
+                }
+                let chosen = self.bag.pop().unwrap_or(0);
+                self.history.clear();
+                self.history.push_back(chosen);
+                chosen
+            }
+            ContainerMode::Sequential => {
+                let chosen = self.cursor % len;
+                self.cursor = chosen + 1;
+                chosen
+            }
+        };
+        Some(index)
+    }
+}
+
+/**
+ * A group of variations of a sound, like several footsteps or gunshots,
+ * choosing one each time it is played.
+ *
+ * Each time the container is triggered, it chooses a variant following its
+ * ContainerMode, and a pitch and a volume in its ranges, to avoid the
+ * repetition of a single sample.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{ContainerMode, OneShotParams, SoundContainer, SoundData, SoundError};
+ * use std::cell::RefCell;
+ * use std::rc::Rc;
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let mut footsteps = SoundContainer::new(ContainerMode::Random { no_repeat: 2 });
+ *     for path in &["step1.ogg", "step2.ogg", "step3.ogg", "step4.ogg"] {
+ *         footsteps.add_variant(Rc::new(RefCell::new(SoundData::new(path)?)));
+ *     }
+ *     footsteps.set_pitch_range(0.95, 1.05);
+ *     footsteps.set_volume_range(0.8, 1.);
+ *
+ *     // At each step
+ *     footsteps.play_oneshot(OneShotParams {
+ *         position: [1., 0., 3.],
+ *         ..Default::default()
+ *     })?;
+ *     Ok(())
+ * }
+ * ```
+ */
+pub struct SoundContainer {
+    variants: Vec<Rc<RefCell<SoundData>>>,
+    selector: Selector,
+    pitch_range: (f32, f32),
+    volume_range: (f32, f32),
+}
+
+impl SoundContainer {
+    /**
+     * Create an empty SoundContainer.
+     *
+     * # Argument
+     * * `mode` - How the variants are chosen
+     */
+    pub fn new(mode: ContainerMode) -> SoundContainer {
+        SoundContainer {
+            variants: Vec::new(),
+            selector: Selector::new(mode),
+            pitch_range: (1., 1.),
+            volume_range: (1., 1.),
+        }
+    }
+
+    /**
+     * Add a variant with a weight of 1.0.
+     *
+     * # Argument
+     * * `data` - The SoundData of the variant
+     */
+    pub fn add_variant(&mut self, data: Rc<RefCell<SoundData>>) {
+        self.add_weighted_variant(data, 1.);
+    }
+
+    /**
+     * Add a variant with a weight.
+     *
+     * The weights are only used by `ContainerMode::Random`: a variant with a
+     * weight of 2.0 is chosen twice as often as a variant with a weight of
+     * 1.0, a variant with a weight of 0.0 is never chosen.
+     *
+     * # Arguments
+     * * `data` - The SoundData of the variant
+     * * `weight` - The weight of the variant, in the range [0.0, +inf]
+     */
+    pub fn add_weighted_variant(&mut self, data: Rc<RefCell<SoundData>>, weight: f32) {
+        self.variants.push(data);
+        self.selector.weights.push(weight.max(0.));
+        self.selector.reset();
+    }
+
+    /// Get the number of variants.
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Check if the container has no variant.
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+
+    /// Get the mode of the container.
+    pub fn get_mode(&self) -> ContainerMode {
+        self.selector.mode
+    }
+
+    /**
+     * Set the mode of the container, starting a new round.
+     *
+     * # Argument
+     * * `mode` - How the variants are chosen
+     */
+    pub fn set_mode(&mut self, mode: ContainerMode) {
+        self.selector.mode = mode;
+        self.selector.reset();
+    }
+
+    /**
+     * Seed the random choices of the container, to replay the same
+     * sequence of variants, pitches and volumes.
+     *
+     * # Argument
+     * * `seed` - The seed of the random generator
+     */
+    pub fn set_seed(&mut self, seed: u64) {
+        self.selector.rng = Rng::with_seed(seed);
+    }
+
+    /**
+     * Set the range of the pitch of each trigger.
+     *
+     * Default range is [1.0, 1.0].
+     *
+     * # Arguments
+     * * `min` - The lowest pitch
+     * * `max` - The highest pitch
+     */
+    pub fn set_pitch_range(&mut self, min: f32, max: f32) {
+        self.pitch_range = (min, max);
+    }
+
+    /// Get the range of the pitch of each trigger, as (min, max).
+    pub fn get_pitch_range(&self) -> (f32, f32) {
+        self.pitch_range
+    }
+
+    /**
+     * Set the range of the volume of each trigger.
+     *
+     * Default range is [1.0, 1.0].
+     *
+     * # Arguments
+     * * `min` - The lowest volume
+     * * `max` - The highest volume
+     */
+    pub fn set_volume_range(&mut self, min: f32, max: f32) {
+        self.volume_range = (min, max);
+    }
+
+    /// Get the range of the volume of each trigger, as (min, max).
+    pub fn get_volume_range(&self) -> (f32, f32) {
+        self.volume_range
+    }
+
+    /**
+     * Choose the next variant, with a random pitch and volume.
+     *
+     * # Return
+     * The chosen Variant, or None if the container has no variant to play.
+     */
+    pub fn choose(&mut self) -> Option<Variant> {
+        let index = self.selector.pick()?;
+        let pitch = self.selector.rng.range(self.pitch_range);
+        let volume = self.selector.rng.range(self.volume_range);
+
+        Some(Variant {
+            data: self.variants[index].clone(),
+            pitch,
+            volume,
+        })
+    }
+
+    /**
+     * Play the next variant on a Sound.
+     *
+     * The Sound is stopped, then plays the chosen variant with its pitch
+     * and volume. Does nothing if the container has no variant to play.
+     *
+     * # Argument
+     * * `sound` - The Sound to play the variant on
+     */
+    pub fn play_on(&mut self, sound: &mut Sound) {
+        if let Some(variant) = self.choose() {
+            sound.stop();
+            sound.set_datas(variant.data);
+            sound.set_pitch(variant.pitch);
+            sound.set_volume(variant.volume);
+            sound.play();
+        }
+    }
+
+    /**
+     * Play the next variant as a one-shot, see `SoundData::play_oneshot`.
+     *
+     * The pitch and volume of `params` are multiplied by the ones of the
+     * variant.
+     *
+     * # Argument
+     * * `params` - The parameters of the one-shot
+     *
+     * # Return
+     * A weak handle to the one-shot, None if the container has no variant to
+     * play, or `Err(SoundError)` if no OpenAL source is available
+     */
+    pub fn play_oneshot(
+        &mut self,
+        params: OneShotParams,
+    ) -> Result<Option<OneShotHandle>, SoundError> {
+        match self.choose() {
+            Some(variant) => {
+                let params = OneShotParams {
+                    pitch: params.pitch * variant.pitch,
+                    volume: params.volume * variant.volume,
+                    ..params
+                };
+                variant.data.borrow().play_oneshot(params).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /**
+     * Play the next variant with a VoiceManager, see `VoiceManager::play`.
+     *
+     * The pitch and volume of `params` are multiplied by the ones of the
+     * variant.
+     *
+     * # Arguments
+     * * `voices` - The VoiceManager to play the variant with
+     * * `params` - The parameters of the Voice
+     *
+     * # Return
+     * The new Voice, or None if the container has no variant to play or if
+     * the VoiceManager rejected it.
+     */
+    pub fn play_voice(&mut self, voices: &mut VoiceManager, params: VoiceParams) -> Option<Voice> {
+        let variant = self.choose()?;
+        let params = VoiceParams {
+            pitch: params.pitch * variant.pitch,
+            volume: params.volume * variant.volume,
+            ..params
+        };
+        voices.play(&variant.data, params)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::container::{ContainerMode, Rng, Selector};
+
+    fn selector(mode: ContainerMode, weights: &[f32]) -> Selector {
+        let mut selector = Selector::new(mode);
+        selector.weights = weights.to_vec();
+        selector.rng = Rng::with_seed(42);
+        selector
+    }
+
+    #[test]
+    fn container_sequential_OK() {
+        let mut selector = selector(ContainerMode::Sequential, &[1., 1., 1.]);
+
+        let played: Vec<usize> = (0..5).filter_map(|_| selector.pick()).collect();
+        assert_eq!(played, vec![0, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn container_random_no_repeat_OK() {
+        let mut selector = selector(ContainerMode::Random { no_repeat: 2 }, &[1., 1., 1., 1.]);
+
+        let played: Vec<usize> = (0..100).filter_map(|_| selector.pick()).collect();
+        for window in played.windows(3) {
+            assert!(window[0] != window[1] && window[0] != window[2] && window[1] != window[2]);
+        }
+    }
+
+    #[test]
+    fn container_random_weights_OK() {
+        let mut selector = selector(ContainerMode::Random { no_repeat: 0 }, &[0., 1.]);
+
+        assert!((0..20).all(|_| selector.pick() == Some(1)));
+    }
+
+    #[test]
+    fn container_shuffle_OK() {
+        let mut selector = selector(ContainerMode::Shuffle, &[1., 1., 1.]);
+
+        let played: Vec<usize> = (0..30).filter_map(|_| selector.pick()).collect();
+        for round in played.chunks(3) {
+            let mut round = round.to_vec();
+            round.sort();
+            assert_eq!(round, vec![0, 1, 2]);
+        }
+        for pair in played.windows(2) {
+            assert!(pair[0] != pair[1]);
+        }
+    }
+
+    #[test]
+    fn container_empty_FAIL() {
+        let mut selector = selector(ContainerMode::Shuffle, &[]);
+
+        assert_eq!(selector.pick(), None);
+    }
+}
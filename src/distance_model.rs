@@ -0,0 +1,205 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! The distance models attenuating the Audio Sources with distance.
+//!
+//! OpenAL has a single global distance model. With the
+//! AL_EXT_source_distance_model extension each source can use its own model
+//! instead, but then the global model is ignored by every source: the
+//! sources following the global model are kept here to apply it to them
+//! manually.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::internal::OpenAlData;
+use crate::openal::{al, ffi};
+
+/**
+ * How the volume of the Audio Sources decreases with their distance to the
+ * listener.
+ *
+ * In the formulas below `distance` is the distance between the Audio Source
+ * and the listener, `reference` its reference distance, `max` its maximum
+ * distance and `rolloff` its attenuation (see `AudioController`). The
+ * clamped variants first clamp `distance` in the range [`reference`, `max`].
+ *
+ * The default model is `InverseClamped`.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DistanceModel {
+    /// No distance attenuation.
+    None,
+    /// `reference / (reference + rolloff * (distance - reference))`
+    Inverse,
+    /// Like `Inverse`, with the distance clamped.
+    #[default]
+    InverseClamped,
+    /// `1 - rolloff * (distance - reference) / (max - reference)`
+    Linear,
+    /// Like `Linear`, with the distance clamped.
+    LinearClamped,
+    /// `(distance / reference) ^ -rolloff`
+    Exponent,
+    /// Like `Exponent`, with the distance clamped.
+    ExponentClamped,
+}
+
+impl DistanceModel {
+    pub(crate) fn to_al(self) -> i32 {
+        match self {
+            DistanceModel::None => ffi::AL_NONE,
+            DistanceModel::Inverse => ffi::AL_INVERSE_DISTANCE,
+            DistanceModel::InverseClamped => ffi::AL_INVERSE_DISTANCE_CLAMPED,
+            DistanceModel::Linear => ffi::AL_LINEAR_DISTANCE,
+            DistanceModel::LinearClamped => ffi::AL_LINEAR_DISTANCE_CLAMPED,
+            DistanceModel::Exponent => ffi::AL_EXPONENT_DISTANCE,
+            DistanceModel::ExponentClamped => ffi::AL_EXPONENT_DISTANCE_CLAMPED,
+        }
+    }
+
+    pub(crate) fn from_al(model: i32) -> DistanceModel {
+        match model {
+            ffi::AL_NONE => DistanceModel::None,
+            ffi::AL_INVERSE_DISTANCE => DistanceModel::Inverse,
+            ffi::AL_LINEAR_DISTANCE => DistanceModel::Linear,
+            ffi::AL_LINEAR_DISTANCE_CLAMPED => DistanceModel::LinearClamped,
+            ffi::AL_EXPONENT_DISTANCE => DistanceModel::Exponent,
+            ffi::AL_EXPONENT_DISTANCE_CLAMPED => DistanceModel::ExponentClamped,
+            _ => DistanceModel::InverseClamped,
+        }
+    }
+}
+
+struct Models {
+    /// Whether AL_SOURCE_DISTANCE_MODEL has been enabled
+    per_source: bool,
+    /// The model of each source, None for the sources following the global
+    /// model
+    sources: HashMap<u32, Option<DistanceModel>>,
+}
+
+lazy_static! {
+    static ref MODELS: Mutex<Models> = Mutex::new(Models {
+        per_source: false,
+        sources: HashMap::new(),
+    });
+}
+
+/// The global distance model.
+pub(crate) fn global() -> DistanceModel {
+    DistanceModel::from_al(al::alGetInteger(ffi::AL_DISTANCE_MODEL))
+}
+
+/// Set the global distance model, and apply it to the sources following
+/// it.
+pub(crate) fn set_global(model: DistanceModel) {
+    al::alDistanceModel(model.to_al());
+
+    if let Ok(models) = MODELS.lock() {
+        if models.per_source {
+            for (al_source, _) in models.sources.iter().filter(|(_, m)| m.is_none()) {
+                al::alSourcei(*al_source, ffi::AL_DISTANCE_MODEL, model.to_al());
+            }
+        }
+    }
+}
+
+/// Keep track of a new source, following the global model.
+pub(crate) fn register(al_source: u32) {
+    if let Ok(mut models) = MODELS.lock() {
+        if models.per_source {
+            al::alSourcei(al_source, ffi::AL_DISTANCE_MODEL, global().to_al());
+        }
+        models.sources.insert(al_source, None);
+    }
+}
+
+/// Set the distance model of a source, or make it follow the global model
+/// with None.
+///
+/// Does nothing if AL_EXT_source_distance_model isn't available.
+pub(crate) fn set_source(al_source: u32, model: Option<DistanceModel>) {
+    if !OpenAlData::source_distance_model_capable() {
+        return;
+    }
+
+    if let Ok(mut models) = MODELS.lock() {
+        let global = global();
+
+        if model.is_some() && !models.per_source {
+            for (other, _) in models.sources.iter() {
+                al::alSourcei(*other, ffi::AL_DISTANCE_MODEL, global.to_al());
+            }
+            al::alEnable(ffi::AL_SOURCE_DISTANCE_MODEL);
+            models.per_source = true;
+        }
+
+        if models.per_source {
+            let applied = model.unwrap_or(global);
+            al::alSourcei(al_source, ffi::AL_DISTANCE_MODEL, applied.to_al());
+        }
+        models.sources.insert(al_source, model);
+    }
+}
+
+/// The distance model of a source, None if it follows the global model.
+pub(crate) fn source(al_source: u32) -> Option<DistanceModel> {
+    match MODELS.lock() {
+        Ok(models) => models.sources.get(&al_source).cloned().flatten(),
+        Err(_) => None,
+    }
+}
+
+/// Stop tracking a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut models) = MODELS.lock() {
+        models.sources.remove(&al_source);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::distance_model::DistanceModel;
+
+    #[test]
+    fn distance_model_al_OK() {
+        for model in [
+            DistanceModel::None,
+            DistanceModel::Inverse,
+            DistanceModel::InverseClamped,
+            DistanceModel::Linear,
+            DistanceModel::LinearClamped,
+            DistanceModel::Exponent,
+            DistanceModel::ExponentClamped,
+        ]
+        .iter()
+        {
+            assert_eq!(DistanceModel::from_al(model.to_al()), *model);
+        }
+    }
+}
@@ -0,0 +1,438 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Automatic ducking of Audio Sources while others are playing.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::audio_controller::AudioController;
+use crate::fade::step;
+use crate::internal::OpenAlData;
+use crate::mixer;
+use crate::mixer::Stage;
+use crate::openal::{al, ffi};
+
+struct Rule {
+    attenuation: f32,
+    attack: Duration,
+    hold: Duration,
+    release: Duration,
+    gain_threshold: Option<f32>,
+    triggers: HashSet<u32>,
+    targets: HashSet<u32>,
+    /// How much of the attenuation is applied, in the range [0.0, 1.0]
+    amount: f32,
+    /// The time left before the release starts
+    hold_left: Duration,
+}
+
+impl Rule {
+    fn new(attenuation: f32) -> Rule {
+        Rule {
+            attenuation,
+            attack: Duration::from_millis(100),
+            hold: Duration::from_secs(0),
+            release: Duration::from_millis(100),
+            gain_threshold: None,
+            triggers: HashSet::new(),
+            targets: HashSet::new(),
+            amount: 0.,
+            hold_left: Duration::from_secs(0),
+        }
+    }
+
+    fn is_triggered(&self) -> bool {
+        self.triggers.iter().any(|al_source| {
+            let mut state = 0;
+            al::alGetSourcei(*al_source, ffi::AL_SOURCE_STATE, &mut state);
+            if state != ffi::AL_PLAYING {
+                return false;
+            }
+
+            // Without the ducking of the trigger itself, so a trigger ducked
+            // by another Ducker doesn't feed back into its own level
+            match self.gain_threshold {
+                Some(threshold) => mixer::gain_without(*al_source, Stage::Ducking) >= threshold,
+                None => true,
+            }
+        })
+    }
+
+    /// Advance the envelope by `dt`, `triggered` telling if a trigger plays.
+    fn advance(&mut self, dt: Duration, triggered: bool) {
+        if triggered {
+            self.amount = step(self.amount, dt, self.attack, 1.);
+            self.hold_left = self.hold;
+        } else if self.hold_left > Duration::from_secs(0) {
+            self.hold_left = self.hold_left.checked_sub(dt).unwrap_or_default();
+        } else {
+            self.amount = step(self.amount, dt, self.release, -1.);
+        }
+    }
+
+    /// The gain applied to the targets.
+    fn gain(&self) -> f32 {
+        10f32.powf(-self.attenuation * self.amount / 20.)
+    }
+}
+
+struct Rules {
+    rules: HashMap<u64, Rule>,
+    /// The targets with a ducking gain in the mixer
+    ducked: HashSet<u32>,
+}
+
+lazy_static! {
+    static ref RULES: Mutex<Rules> = Mutex::new(Rules {
+        rules: HashMap::new(),
+        ducked: HashSet::new(),
+    });
+    static ref NEXT_RULE: AtomicU64 = AtomicU64::new(0);
+}
+
+/**
+ * Lower the volume of some Audio Sources while others are playing.
+ *
+ * While one of the triggers plays, the targets are attenuated, reaching the
+ * full attenuation after the attack time. Once no trigger plays anymore the
+ * attenuation is kept during the hold time, then goes back to 0 dB over
+ * the release time. The Ducker is evaluated when `ears::update` is called,
+ * or by the background ticker.
+ *
+ * Dropping the Ducker removes the attenuation from its targets.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Ducker, Music, Sound, SoundError};
+ * use std::time::Duration;
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let dialog = Sound::new("path/to/the/dialog.ogg")?;
+ *     let music = Music::new("path/to/the/music.ogg")?;
+ *
+ *     // Lower the music by 12dB while the dialog plays
+ *     let ducker = Ducker::new(12.);
+ *     ducker.add_trigger(&dialog);
+ *     ducker.add_target(&music);
+ *     ducker.set_attack(Duration::from_millis(150));
+ *     ducker.set_release(Duration::from_millis(800));
+ *
+ *     ears::start_ticker(Duration::from_millis(10));
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Debug)]
+pub struct Ducker {
+    id: u64,
+}
+
+impl Ducker {
+    /**
+     * Create a Ducker without triggers nor targets.
+     *
+     * The attack and release times default to 100ms, the hold time to 0.
+     *
+     * # Argument
+     * * `attenuation` - The attenuation of the targets in dB, 12.0 lowers
+     *   their volume to a quarter
+     */
+    pub fn new(attenuation: f32) -> Ducker {
+        let id = NEXT_RULE.fetch_add(1, Ordering::Relaxed);
+
+        if let Ok(mut rules) = RULES.lock() {
+            rules.rules.insert(id, Rule::new(attenuation));
+        }
+
+        Ducker { id }
+    }
+
+    fn with_rule<T, F: FnOnce(&mut Rule) -> T>(&self, f: F, default: T) -> T {
+        match RULES.lock() {
+            Ok(mut rules) => rules.rules.get_mut(&self.id).map(f).unwrap_or(default),
+            Err(_) => default,
+        }
+    }
+
+    /**
+     * Add an Audio Source triggering the ducking when it plays.
+     *
+     * # Argument
+     * * `source` - The triggering Audio Source
+     */
+    pub fn add_trigger(&self, source: &dyn AudioController) {
+        let al_source = source.al_source();
+        self.with_rule(|rule| rule.triggers.insert(al_source), false);
+    }
+
+    /**
+     * Remove a triggering Audio Source.
+     *
+     * # Argument
+     * * `source` - The Audio Source to remove from the triggers
+     */
+    pub fn remove_trigger(&self, source: &dyn AudioController) {
+        let al_source = source.al_source();
+        self.with_rule(|rule| rule.triggers.remove(&al_source), false);
+    }
+
+    /**
+     * Add an Audio Source to attenuate.
+     *
+     * # Argument
+     * * `source` - The Audio Source to attenuate
+     */
+    pub fn add_target(&self, source: &dyn AudioController) {
+        let al_source = source.al_source();
+        self.with_rule(|rule| rule.targets.insert(al_source), false);
+    }
+
+    /**
+     * Remove an attenuated Audio Source.
+     *
+     * Its attenuation is removed at the next update.
+     *
+     * # Argument
+     * * `source` - The Audio Source to remove from the targets
+     */
+    pub fn remove_target(&self, source: &dyn AudioController) {
+        let al_source = source.al_source();
+        self.with_rule(|rule| rule.targets.remove(&al_source), false);
+    }
+
+    /**
+     * Set the attenuation of the targets.
+     *
+     * # Argument
+     * * `attenuation` - The attenuation in dB
+     */
+    pub fn set_attenuation(&self, attenuation: f32) {
+        self.with_rule(|rule| rule.attenuation = attenuation, ());
+    }
+
+    /// Get the attenuation of the targets, in dB.
+    pub fn get_attenuation(&self) -> f32 {
+        self.with_rule(|rule| rule.attenuation, 0.)
+    }
+
+    /**
+     * Set the time to reach the full attenuation once a trigger plays.
+     *
+     * # Argument
+     * * `attack` - The attack time
+     */
+    pub fn set_attack(&self, attack: Duration) {
+        self.with_rule(|rule| rule.attack = attack, ());
+    }
+
+    /**
+     * Set the time the attenuation is kept once no trigger plays anymore.
+     *
+     * # Argument
+     * * `hold` - The hold time
+     */
+    pub fn set_hold(&self, hold: Duration) {
+        self.with_rule(|rule| rule.hold = hold, ());
+    }
+
+    /**
+     * Set the time to go back to 0 dB after the hold time.
+     *
+     * # Argument
+     * * `release` - The release time
+     */
+    pub fn set_release(&self, release: Duration) {
+        self.with_rule(|rule| rule.release = release, ());
+    }
+
+    /**
+     * Only trigger the ducking when the gain of a trigger is high enough.
+     *
+     * The gain of a trigger is its volume multiplied by its Bus and its
+     * rolloff curve, without the ducking applied to the trigger itself nor
+     * the distance attenuation of OpenAL. It isn't the level of the signal.
+     *
+     * # Argument
+     * * `threshold` - The minimal gain of a playing trigger, or None to
+     *   trigger on any playing trigger
+     */
+    pub fn set_gain_threshold(&self, threshold: Option<f32>) {
+        self.with_rule(|rule| rule.gain_threshold = threshold, ());
+    }
+
+    /**
+     * Get the minimal gain of a playing trigger.
+     *
+     * # Return
+     * The threshold, or None if any playing trigger triggers the ducking
+     */
+    pub fn get_gain_threshold(&self) -> Option<f32> {
+        self.with_rule(|rule| rule.gain_threshold, None)
+    }
+
+    /**
+     * Get the gain currently applied to the targets.
+     *
+     * # Return
+     * The gain, 1.0 when the targets aren't attenuated
+     */
+    pub fn get_gain(&self) -> f32 {
+        self.with_rule(|rule| rule.gain(), 1.)
+    }
+}
+
+impl Drop for Ducker {
+    fn drop(&mut self) {
+        if let Ok(mut rules) = RULES.lock() {
+            rules.rules.remove(&self.id);
+            apply(&mut rules);
+        }
+    }
+}
+
+/// The product of the gains of all the rules, for each target.
+fn gains(rules: &HashMap<u64, Rule>) -> HashMap<u32, f32> {
+    let mut gains: HashMap<u32, f32> = HashMap::new();
+    for rule in rules.values() {
+        let gain = rule.gain();
+        for target in rule.targets.iter() {
+            *gains.entry(*target).or_insert(1.) *= gain;
+        }
+    }
+    gains
+}
+
+/// Apply the product of the gains of all the rules to their targets.
+fn apply(rules: &mut Rules) {
+    let gains = gains(&rules.rules);
+
+    for al_source in rules.ducked.iter() {
+        if !gains.contains_key(al_source) {
+            mixer::set_gain(*al_source, Stage::Ducking, None);
+        }
+    }
+    for (al_source, gain) in gains.iter() {
+        mixer::set_gain(*al_source, Stage::Ducking, Some(*gain));
+    }
+    rules.ducked = gains.keys().cloned().collect();
+}
+
+/// Stop ducking and triggering with a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut rules) = RULES.lock() {
+        for rule in rules.rules.values_mut() {
+            rule.triggers.remove(&al_source);
+            rule.targets.remove(&al_source);
+        }
+        rules.ducked.remove(&al_source);
+    }
+}
+
+/// Advance the ducking envelopes by `dt`.
+pub(crate) fn update(dt: Duration) {
+    check_openal_context!(());
+
+    if let Ok(mut rules) = RULES.lock() {
+        for rule in rules.rules.values_mut() {
+            let triggered = rule.is_triggered();
+            rule.advance(dt, triggered);
+        }
+        apply(&mut rules);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use crate::ducking::{gains, Rule};
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-6, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn ducking_envelope_OK() {
+        let mut rule = Rule::new(12.);
+        rule.attack = Duration::from_millis(500);
+        rule.hold = Duration::from_millis(500);
+        rule.release = Duration::from_secs(1);
+        let dt = Duration::from_millis(250);
+
+        // Attack
+        rule.advance(dt, true);
+        assert_eq!(rule.amount, 0.5);
+        rule.advance(dt, true);
+        rule.advance(dt, true);
+        assert_eq!(rule.amount, 1.);
+
+        // Hold
+        rule.advance(dt, false);
+        rule.advance(dt, false);
+        assert_eq!(rule.amount, 1.);
+
+        // Release
+        rule.advance(dt, false);
+        assert_eq!(rule.amount, 0.75);
+        for _ in 0..4 {
+            rule.advance(dt, false);
+        }
+        assert_eq!(rule.amount, 0.);
+    }
+
+    #[test]
+    fn ducking_gain_OK() {
+        let mut rule = Rule::new(20.);
+        assert_eq!(rule.gain(), 1.);
+
+        rule.amount = 1.;
+        assert_near(rule.gain(), 0.1);
+
+        rule.amount = 0.5;
+        assert_near(rule.gain(), 0.316_227_77);
+    }
+
+    #[test]
+    fn ducking_gains_OK() {
+        let mut first = Rule::new(20.);
+        first.amount = 1.;
+        first.targets.extend([1, 2]);
+        let mut second = Rule::new(40.);
+        second.amount = 0.5;
+        second.targets.extend([2, 3]);
+
+        let mut rules = HashMap::new();
+        rules.insert(0, first);
+        rules.insert(1, second);
+
+        let gains = gains(&rules);
+        assert_eq!(gains.len(), 3);
+        assert_near(gains[&1], 0.1);
+        assert_near(gains[&2], 0.01);
+        assert_near(gains[&3], 0.1);
+    }
+}
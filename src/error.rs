@@ -16,6 +16,10 @@ pub enum SoundError {
 
     /// Internal OpenAL error.
     InternalOpenALError(al::AlError),
+
+    /// A value out of the range accepted by a parameter, with the name of
+    /// the parameter and the rejected value.
+    InvalidValue(&'static str, String),
 }
 
 impl fmt::Display for SoundError {
@@ -28,6 +32,8 @@ impl fmt::Display for SoundError {
                 SoundError::LoadError(err) => format!("error while loading music file: {}", err),
                 SoundError::InvalidFormat => "unrecognized music format".to_string(),
                 SoundError::InternalOpenALError(err) => format!("internal OpenAL error: {}", err),
+                SoundError::InvalidValue(name, value) =>
+                    format!("invalid value for {}: {}", name, value),
             }
         )
     }
@@ -46,6 +52,7 @@ impl Error for SoundError {
             SoundError::LoadError(err) => Some(err),
             SoundError::InvalidFormat => None,
             SoundError::InternalOpenALError(err) => Some(err),
+            SoundError::InvalidValue(..) => None,
         }
     }
 }
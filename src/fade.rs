@@ -0,0 +1,443 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Fades and glides of the Audio Sources parameters.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::clock;
+use crate::internal::OpenAlData;
+use crate::mixer;
+use crate::openal::{al, ffi};
+
+const RUNNING: u8 = 0;
+const COMPLETED: u8 = 1;
+const CANCELLED: u8 = 2;
+
+/// The shape of a fade, mapping the progress of the fade to the progress of
+/// the value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Curve {
+    /// Constant rate of change.
+    Linear,
+    /// Starts slowly and accelerates.
+    EaseIn,
+    /// Starts quickly and decelerates.
+    EaseOut,
+    /// Starts and ends slowly.
+    EaseInOut,
+}
+
+impl Curve {
+    /**
+     * Apply the curve to a progress value.
+     *
+     * # Argument
+     * * `t` - The progress of the fade, clamped in the range [0.0, 1.0]
+     *
+     * # Return
+     * The progress of the value in the range [0.0, 1.0]
+     */
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+
+        match self {
+            Curve::Linear => t,
+            Curve::EaseIn => t * t,
+            Curve::EaseOut => 1. - (1. - t) * (1. - t),
+            Curve::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+/// The differents states in which a fade can be.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum FadeStatus {
+    /// The fade is still running
+    Running,
+    /// The fade reached its target value
+    Completed,
+    /// The fade was cancelled before reaching its target value
+    Cancelled,
+}
+
+/// The status of a fade, shared by the fade and its handles.
+#[derive(Debug)]
+struct Shared {
+    status: AtomicU8,
+    /// Notified once when the fade completes or is cancelled
+    listeners: Mutex<Vec<Sender<FadeStatus>>>,
+}
+
+impl Shared {
+    fn new() -> Shared {
+        Shared {
+            status: AtomicU8::new(RUNNING),
+            listeners: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.status.load(Ordering::Relaxed) == RUNNING
+    }
+
+    /// End the fade with `status` and notify the listeners, if the fade
+    /// is still running.
+    fn finish(&self, status: u8) {
+        if self
+            .status
+            .compare_exchange(RUNNING, status, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+
+        let status = if status == COMPLETED {
+            FadeStatus::Completed
+        } else {
+            FadeStatus::Cancelled
+        };
+        if let Ok(mut listeners) = self.listeners.lock() {
+            for listener in listeners.drain(..) {
+                let _ = listener.send(status);
+            }
+        }
+    }
+}
+
+/**
+ * Follow or cancel a running fade.
+ *
+ * Returned by the fade methods of an AudioController. Dropping the handle
+ * doesn't stop the fade.
+ */
+#[derive(Clone, Debug)]
+pub struct FadeHandle {
+    shared: Arc<Shared>,
+}
+
+impl FadeHandle {
+    /**
+     * Get the current status of the fade.
+     *
+     * # Return
+     * The status of the fade as a variant of the enum FadeStatus
+     */
+    pub fn status(&self) -> FadeStatus {
+        match self.shared.status.load(Ordering::Relaxed) {
+            RUNNING => FadeStatus::Running,
+            COMPLETED => FadeStatus::Completed,
+            _ => FadeStatus::Cancelled,
+        }
+    }
+
+    /// Check if the fade is still running.
+    pub fn is_running(&self) -> bool {
+        self.status() == FadeStatus::Running
+    }
+
+    /// Check if the fade reached its target value.
+    pub fn is_completed(&self) -> bool {
+        self.status() == FadeStatus::Completed
+    }
+
+    /**
+     * Cancel the fade.
+     *
+     * The parameter keeps the value it had at the last update. Does nothing
+     * if the fade is already completed.
+     */
+    pub fn cancel(&self) {
+        self.shared.finish(CANCELLED);
+    }
+
+    /**
+     * Get notified when the fade ends.
+     *
+     * The status is sent once, when the fade completes or is cancelled, or
+     * at once if the fade already ended.
+     *
+     * # Return
+     * A receiver of the final status of the fade, `Completed` or `Cancelled`
+     *
+     * # Example
+     * ```no_run
+     * use ears::{AudioController, Curve, FadeStatus, Sound};
+     * use std::time::Duration;
+     *
+     * let mut snd = Sound::new("path/to/my/sound.ogg").unwrap();
+     * snd.play();
+     * ears::start_ticker(Duration::from_millis(10));
+     *
+     * let fade = snd.fade_out_and_stop(Duration::from_secs(2), Curve::EaseIn);
+     * if fade.completion().recv() == Ok(FadeStatus::Completed) {
+     *     println!("Faded out");
+     * }
+     * ```
+     */
+    pub fn completion(&self) -> Receiver<FadeStatus> {
+        let (sender, receiver) = channel();
+
+        if let Ok(mut listeners) = self.shared.listeners.lock() {
+            match self.status() {
+                FadeStatus::Running => listeners.push(sender),
+                status => {
+                    let _ = sender.send(status);
+                }
+            }
+        }
+
+        receiver
+    }
+}
+
+/// A parameter of a source being faded, with its start and end values.
+#[derive(Clone, Copy)]
+pub(crate) enum Target {
+    Volume(f32, f32),
+    Pitch(f32, f32),
+    Position([f32; 3], [f32; 3]),
+}
+
+impl Target {
+    fn same_parameter(&self, other: &Target) -> bool {
+        matches!(
+            (self, other),
+            (Target::Volume(..), Target::Volume(..))
+                | (Target::Pitch(..), Target::Pitch(..))
+                | (Target::Position(..), Target::Position(..))
+        )
+    }
+
+    fn apply(&self, al_source: u32, progress: f32) {
+        match *self {
+            Target::Volume(from, to) => {
+                mixer::set_volume(al_source, lerp(from, to, progress));
+            }
+            Target::Pitch(from, to) => {
+                mixer::set_pitch(al_source, lerp(from, to, progress));
+            }
+            Target::Position(from, to) => {
+                let position = [
+                    lerp(from[0], to[0], progress),
+                    lerp(from[1], to[1], progress),
+                    lerp(from[2], to[2], progress),
+                ];
+                al::alSourcefv(al_source, ffi::AL_POSITION, &position[0]);
+            }
+        }
+    }
+}
+
+struct Fade {
+    al_source: u32,
+    target: Target,
+    curve: Curve,
+    duration: Duration,
+    elapsed: Duration,
+    stop_at_end: bool,
+    shared: Arc<Shared>,
+}
+
+lazy_static! {
+    static ref FADES: Mutex<Vec<Fade>> = Mutex::new(Vec::new());
+}
+
+pub(crate) fn lerp(from: f32, to: f32, progress: f32) -> f32 {
+    from + (to - from) * progress
+}
+
+/// Move `amount` towards 0.0 or 1.0, covering the whole range in
+/// `duration`.
+pub(crate) fn step(amount: f32, dt: Duration, duration: Duration, direction: f32) -> f32 {
+    let delta = if duration.as_secs_f32() > 0. {
+        dt.as_secs_f32() / duration.as_secs_f32()
+    } else {
+        1.
+    };
+    (amount + direction * delta).clamp(0., 1.)
+}
+
+/// Start a new fade, cancelling the running fade of the same parameter on
+/// the same source.
+pub(crate) fn start(
+    al_source: u32,
+    target: Target,
+    duration: Duration,
+    curve: Curve,
+    stop_at_end: bool,
+) -> FadeHandle {
+    let shared = Arc::new(Shared::new());
+
+    if let Ok(mut fades) = FADES.lock() {
+        for fade in fades.iter() {
+            if fade.al_source == al_source && fade.target.same_parameter(&target) {
+                fade.shared.finish(CANCELLED);
+            }
+        }
+        fades.retain(|fade| fade.shared.is_running());
+        fades.push(Fade {
+            al_source,
+            target,
+            curve,
+            duration,
+            elapsed: Duration::from_secs(0),
+            stop_at_end,
+            shared: shared.clone(),
+        });
+    }
+
+    FadeHandle { shared }
+}
+
+/// Cancel every fade running on a source.
+pub(crate) fn cancel(al_source: u32) {
+    if let Ok(mut fades) = FADES.lock() {
+        fades.retain(|fade| {
+            if fade.al_source == al_source {
+                fade.shared.finish(CANCELLED);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+/// Drop every fade of a source without applying them, used when the source
+/// is deleted.
+pub(crate) fn forget(al_source: u32) {
+    cancel(al_source);
+}
+
+/// Advance all the running fades.
+pub(crate) fn update(dt: Duration) {
+    check_openal_context!(());
+
+    let mut fades = match FADES.lock() {
+        Ok(fades) => fades,
+        Err(_) => return,
+    };
+    let mut stopped = Vec::new();
+
+    fades.retain(|fade| fade.shared.is_running());
+
+    for fade in fades.iter_mut() {
+        fade.elapsed += dt;
+
+        let progress = if fade.duration.as_secs_f32() > 0. {
+            fade.elapsed.as_secs_f32() / fade.duration.as_secs_f32()
+        } else {
+            1.
+        };
+
+        fade.target
+            .apply(fade.al_source, fade.curve.apply(progress));
+
+        if progress >= 1. {
+            if fade.stop_at_end {
+                stopped.push(fade.al_source);
+            }
+            fade.shared.finish(COMPLETED);
+        }
+    }
+
+    fades.retain(|fade| fade.shared.is_running());
+    drop(fades);
+
+    // Stop like AudioController::stop, the streaming thread of a Music
+    // ending with its source
+    for al_source in stopped {
+        clock::cancel(al_source);
+        al::alSourceStop(al_source);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::fade::{step, Curve, FadeHandle, FadeStatus, Shared, COMPLETED};
+
+    #[test]
+    fn curve_bounds_OK() {
+        for curve in [
+            Curve::Linear,
+            Curve::EaseIn,
+            Curve::EaseOut,
+            Curve::EaseInOut,
+        ]
+        .iter()
+        {
+            assert_eq!(curve.apply(0.), 0.);
+            assert_eq!(curve.apply(1.), 1.);
+        }
+    }
+
+    #[test]
+    fn curve_clamp_OK() {
+        assert_eq!(Curve::Linear.apply(-1.), 0.);
+        assert_eq!(Curve::EaseIn.apply(2.), 1.);
+    }
+
+    #[test]
+    fn curve_shape_OK() {
+        assert_eq!(Curve::Linear.apply(0.5), 0.5);
+        assert!(Curve::EaseIn.apply(0.5) < 0.5);
+        assert!(Curve::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(Curve::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn step_OK() {
+        let attack = Duration::from_millis(100);
+
+        assert_eq!(step(0., Duration::from_millis(50), attack, 1.), 0.5);
+        assert_eq!(step(0.5, Duration::from_millis(100), attack, 1.), 1.);
+        assert_eq!(
+            step(0.5, Duration::from_millis(10), Duration::from_secs(0), -1.),
+            0.
+        );
+    }
+
+    #[test]
+    fn fade_handle_completion_OK() {
+        let handle = FadeHandle {
+            shared: Arc::new(Shared::new()),
+        };
+        let completion = handle.completion();
+        assert!(completion.try_recv().is_err());
+
+        handle.shared.finish(COMPLETED);
+        assert_eq!(completion.try_recv(), Ok(FadeStatus::Completed));
+
+        // Already ended, cancelling doesn't change the status
+        handle.cancel();
+        assert_eq!(handle.status(), FadeStatus::Completed);
+        assert_eq!(handle.completion().try_recv(), Ok(FadeStatus::Completed));
+    }
+}
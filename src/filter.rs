@@ -0,0 +1,620 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! EFX filters attenuating the low or high frequencies of Audio Sources.
+//!
+//! OpenAL only allows one filter on the direct path of a source and one on
+//! each send, and copies the filter parameters when it is attached. The
+//! filters set here are combined with the ones __ears__ uses internally
+//! (the low-pass of the rolloff curves, the gain of the reverb send) into
+//! one OpenAL filter per source, updated whenever a parameter changes.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::internal::OpenAlData;
+use crate::openal::{al, ffi};
+use crate::sends;
+
+/// The gains of a filter, the identity leaving the sound unchanged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FilterParams {
+    pub gain: f32,
+    pub gainlf: f32,
+    pub gainhf: f32,
+}
+
+impl FilterParams {
+    pub(crate) const IDENTITY: FilterParams = FilterParams {
+        gain: 1.,
+        gainlf: 1.,
+        gainhf: 1.,
+    };
+}
+
+/**
+ * Configure an OpenAL filter with the given gains.
+ *
+ * The filter is created on demand, and released when the gains don't
+ * change the sound.
+ *
+ * # Return
+ * The filter to attach to the source, AL_FILTER_NULL if none is needed
+ */
+pub(crate) fn configure(al_filter: &mut u32, params: &FilterParams) -> i32 {
+    if *params == FilterParams::IDENTITY {
+        release(al_filter);
+        return ffi::AL_FILTER_NULL;
+    }
+
+    if *al_filter == 0 {
+        al::alGenFilters(1, al_filter);
+    }
+
+    if params.gainlf == 1. {
+        al::alFilteri(*al_filter, ffi::AL_FILTER_TYPE, ffi::AL_FILTER_LOWPASS);
+        al::alFilterf(*al_filter, ffi::AL_LOWPASS_GAIN, params.gain);
+        al::alFilterf(*al_filter, ffi::AL_LOWPASS_GAINHF, params.gainhf);
+    } else if params.gainhf == 1. {
+        al::alFilteri(*al_filter, ffi::AL_FILTER_TYPE, ffi::AL_FILTER_HIGHPASS);
+        al::alFilterf(*al_filter, ffi::AL_HIGHPASS_GAIN, params.gain);
+        al::alFilterf(*al_filter, ffi::AL_HIGHPASS_GAINLF, params.gainlf);
+    } else {
+        al::alFilteri(*al_filter, ffi::AL_FILTER_TYPE, ffi::AL_FILTER_BANDPASS);
+        al::alFilterf(*al_filter, ffi::AL_BANDPASS_GAIN, params.gain);
+        al::alFilterf(*al_filter, ffi::AL_BANDPASS_GAINLF, params.gainlf);
+        al::alFilterf(*al_filter, ffi::AL_BANDPASS_GAINHF, params.gainhf);
+    }
+    *al_filter as i32
+}
+
+/// Delete an OpenAL filter created by `configure`.
+pub(crate) fn release(al_filter: &mut u32) {
+    if *al_filter != 0 {
+        unsafe {
+            ffi::alDeleteFilters(1, al_filter);
+        }
+        *al_filter = 0;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Lowpass,
+    Highpass,
+    Bandpass,
+}
+
+struct FilterHandle {
+    id: u64,
+}
+
+struct FilterState {
+    kind: Kind,
+    handle: Weak<FilterHandle>,
+    params: FilterParams,
+}
+
+struct DirectState {
+    /// The Filter set on the direct path
+    filter: Option<u64>,
+    /// The high frequencies gain of the rolloff curve
+    rolloff: Option<f32>,
+    /// The OpenAL filter combining them, created on demand
+    al_filter: u32,
+}
+
+lazy_static! {
+    static ref FILTERS: Mutex<HashMap<u64, FilterState>> = Mutex::new(HashMap::new());
+    static ref NEXT_FILTER: AtomicU64 = AtomicU64::new(0);
+    static ref DIRECT: Mutex<HashMap<u32, DirectState>> = Mutex::new(HashMap::new());
+}
+
+/// The gains of a Filter, None once it was dropped.
+pub(crate) fn params(id: u64) -> Option<FilterParams> {
+    match FILTERS.lock() {
+        Ok(filters) => filters.get(&id).map(|filter| filter.params),
+        Err(_) => None,
+    }
+}
+
+impl FilterHandle {
+    fn create(kind: Kind) -> Arc<FilterHandle> {
+        let handle = Arc::new(FilterHandle {
+            id: NEXT_FILTER.fetch_add(1, Ordering::Relaxed),
+        });
+
+        if let Ok(mut filters) = FILTERS.lock() {
+            filters.insert(
+                handle.id,
+                FilterState {
+                    kind,
+                    handle: Arc::downgrade(&handle),
+                    params: FilterParams::IDENTITY,
+                },
+            );
+        }
+
+        handle
+    }
+
+    fn read(&self) -> FilterParams {
+        params(self.id).unwrap_or(FilterParams::IDENTITY)
+    }
+
+    fn update<F: FnOnce(&mut FilterParams)>(&self, change: F) {
+        if let Ok(mut filters) = FILTERS.lock() {
+            if let Some(filter) = filters.get_mut(&self.id) {
+                change(&mut filter.params);
+            }
+        }
+        refresh(self.id);
+    }
+}
+
+impl Drop for FilterHandle {
+    fn drop(&mut self) {
+        if let Ok(mut filters) = FILTERS.lock() {
+            filters.remove(&self.id);
+        }
+        refresh(self.id);
+    }
+}
+
+/// Apply the new gains of a Filter to the sources using it.
+fn refresh(id: u64) {
+    check_openal_context!(());
+
+    if let Ok(mut direct) = DIRECT.lock() {
+        for (al_source, state) in direct.iter_mut() {
+            if state.filter == Some(id) {
+                apply(*al_source, state);
+            }
+        }
+        direct.retain(|_, state| state.filter.is_some() || state.rolloff.is_some());
+    }
+    sends::refresh(id);
+}
+
+fn apply(al_source: u32, state: &mut DirectState) {
+    let mut params = match state.filter.and_then(params) {
+        Some(params) => params,
+        None => {
+            state.filter = None;
+            FilterParams::IDENTITY
+        }
+    };
+    if let Some(gainhf) = state.rolloff {
+        params.gainhf *= gainhf;
+    }
+
+    let al_filter = configure(&mut state.al_filter, &params);
+    al::alSourcei(al_source, ffi::AL_DIRECT_FILTER, al_filter);
+}
+
+fn with_direct<F: FnOnce(&mut DirectState)>(al_source: u32, f: F) {
+    if let Ok(mut direct) = DIRECT.lock() {
+        let state = direct.entry(al_source).or_insert(DirectState {
+            filter: None,
+            rolloff: None,
+            al_filter: 0,
+        });
+        f(state);
+        apply(al_source, state);
+        if state.filter.is_none() && state.rolloff.is_none() {
+            direct.remove(&al_source);
+        }
+    }
+}
+
+/// Set the Filter on the direct path of a source, or remove it with None.
+pub(crate) fn set_direct(al_source: u32, filter: Option<&Filter>) {
+    with_direct(al_source, |state| state.filter = filter.map(Filter::id));
+}
+
+/// The Filter on the direct path of a source.
+pub(crate) fn direct(al_source: u32) -> Option<Filter> {
+    let id = match DIRECT.lock() {
+        Ok(direct) => direct.get(&al_source).and_then(|state| state.filter),
+        Err(_) => None,
+    };
+    id.and_then(Filter::from_id)
+}
+
+/// Set the high frequencies gain of the rolloff curve of a source, or
+/// remove it with None.
+pub(crate) fn set_rolloff_gainhf(al_source: u32, gainhf: Option<f32>) {
+    with_direct(al_source, |state| state.rolloff = gainhf);
+}
+
+/// Release the direct filter of a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut direct) = DIRECT.lock() {
+        if let Some(mut state) = direct.remove(&al_source) {
+            if state.al_filter != 0 {
+                al::alSourcei(al_source, ffi::AL_DIRECT_FILTER, ffi::AL_FILTER_NULL);
+                release(&mut state.al_filter);
+            }
+        }
+    }
+}
+
+/**
+ * A low-pass filter, attenuating the high frequencies.
+ *
+ * Use it to muffle the Audio Sources behind a wall or underwater. A filter
+ * is a handle: clones refer to the same filter, and changing its gains
+ * updates all the Audio Sources using it at once. When the last handle is
+ * dropped, the Audio Sources are no longer filtered.
+ *
+ * OpenAL filters don't have a cutoff frequency, the high frequencies are
+ * attenuated above 5kHz.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Filter, LowpassFilter, Sound, SoundError};
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+ *
+ *     let wall = LowpassFilter::new();
+ *     wall.set_gainhf(0.2);
+ *     snd.set_direct_filter(Some(&Filter::from(&wall)));
+ *
+ *     // The door opens
+ *     wall.set_gainhf(0.8);
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Clone)]
+pub struct LowpassFilter {
+    handle: Arc<FilterHandle>,
+}
+
+impl LowpassFilter {
+    /// Create a LowpassFilter leaving the sound unchanged.
+    pub fn new() -> LowpassFilter {
+        LowpassFilter {
+            handle: FilterHandle::create(Kind::Lowpass),
+        }
+    }
+
+    /**
+     * Set the gain of the whole signal.
+     *
+     * Default gain is 1.0.
+     *
+     * # Argument
+     * * `gain` - The new gain, in the range [0.0, 1.0]
+     */
+    pub fn set_gain(&self, gain: f32) {
+        self.handle.update(|params| params.gain = gain);
+    }
+
+    /// Get the gain of the whole signal.
+    pub fn get_gain(&self) -> f32 {
+        self.handle.read().gain
+    }
+
+    /**
+     * Set the gain of the high frequencies.
+     *
+     * Default gain is 1.0.
+     *
+     * # Argument
+     * * `gainhf` - The new gain, in the range [0.0, 1.0]
+     */
+    pub fn set_gainhf(&self, gainhf: f32) {
+        self.handle.update(|params| params.gainhf = gainhf);
+    }
+
+    /// Get the gain of the high frequencies.
+    pub fn get_gainhf(&self) -> f32 {
+        self.handle.read().gainhf
+    }
+}
+
+/**
+ * A high-pass filter, attenuating the low frequencies.
+ *
+ * Use it to make an Audio Source sound thin, like through a phone or a
+ * small radio. Like the LowpassFilter, a filter is a handle updating the
+ * Audio Sources using it.
+ *
+ * OpenAL filters don't have a cutoff frequency, the low frequencies are
+ * attenuated below 250Hz.
+ */
+#[derive(Clone)]
+pub struct HighpassFilter {
+    handle: Arc<FilterHandle>,
+}
+
+impl HighpassFilter {
+    /// Create a HighpassFilter leaving the sound unchanged.
+    pub fn new() -> HighpassFilter {
+        HighpassFilter {
+            handle: FilterHandle::create(Kind::Highpass),
+        }
+    }
+
+    /**
+     * Set the gain of the whole signal.
+     *
+     * Default gain is 1.0.
+     *
+     * # Argument
+     * * `gain` - The new gain, in the range [0.0, 1.0]
+     */
+    pub fn set_gain(&self, gain: f32) {
+        self.handle.update(|params| params.gain = gain);
+    }
+
+    /// Get the gain of the whole signal.
+    pub fn get_gain(&self) -> f32 {
+        self.handle.read().gain
+    }
+
+    /**
+     * Set the gain of the low frequencies.
+     *
+     * Default gain is 1.0.
+     *
+     * # Argument
+     * * `gainlf` - The new gain, in the range [0.0, 1.0]
+     */
+    pub fn set_gainlf(&self, gainlf: f32) {
+        self.handle.update(|params| params.gainlf = gainlf);
+    }
+
+    /// Get the gain of the low frequencies.
+    pub fn get_gainlf(&self) -> f32 {
+        self.handle.read().gainlf
+    }
+}
+
+/**
+ * A band-pass filter, attenuating both the low and the high frequencies.
+ *
+ * Like the LowpassFilter, a filter is a handle updating the Audio Sources
+ * using it.
+ */
+#[derive(Clone)]
+pub struct BandpassFilter {
+    handle: Arc<FilterHandle>,
+}
+
+impl BandpassFilter {
+    /// Create a BandpassFilter leaving the sound unchanged.
+    pub fn new() -> BandpassFilter {
+        BandpassFilter {
+            handle: FilterHandle::create(Kind::Bandpass),
+        }
+    }
+
+    /**
+     * Set the gain of the whole signal.
+     *
+     * Default gain is 1.0.
+     *
+     * # Argument
+     * * `gain` - The new gain, in the range [0.0, 1.0]
+     */
+    pub fn set_gain(&self, gain: f32) {
+        self.handle.update(|params| params.gain = gain);
+    }
+
+    /// Get the gain of the whole signal.
+    pub fn get_gain(&self) -> f32 {
+        self.handle.read().gain
+    }
+
+    /**
+     * Set the gain of the low frequencies.
+     *
+     * Default gain is 1.0.
+     *
+     * # Argument
+     * * `gainlf` - The new gain, in the range [0.0, 1.0]
+     */
+    pub fn set_gainlf(&self, gainlf: f32) {
+        self.handle.update(|params| params.gainlf = gainlf);
+    }
+
+    /// Get the gain of the low frequencies.
+    pub fn get_gainlf(&self) -> f32 {
+        self.handle.read().gainlf
+    }
+
+    /**
+     * Set the gain of the high frequencies.
+     *
+     * Default gain is 1.0.
+     *
+     * # Argument
+     * * `gainhf` - The new gain, in the range [0.0, 1.0]
+     */
+    pub fn set_gainhf(&self, gainhf: f32) {
+        self.handle.update(|params| params.gainhf = gainhf);
+    }
+
+    /// Get the gain of the high frequencies.
+    pub fn get_gainhf(&self) -> f32 {
+        self.handle.read().gainhf
+    }
+}
+
+/**
+ * One of the filters, as set on an Audio Source.
+ *
+ * See `AudioController::set_direct_filter` and
+ * `AudioController::set_send_filter`.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    Lowpass(LowpassFilter),
+    Highpass(HighpassFilter),
+    Bandpass(BandpassFilter),
+}
+
+impl Filter {
+    pub(crate) fn id(&self) -> u64 {
+        match self {
+            Filter::Lowpass(filter) => filter.handle.id,
+            Filter::Highpass(filter) => filter.handle.id,
+            Filter::Bandpass(filter) => filter.handle.id,
+        }
+    }
+
+    pub(crate) fn from_id(id: u64) -> Option<Filter> {
+        let (kind, handle) = match FILTERS.lock() {
+            Ok(filters) => {
+                let filter = filters.get(&id)?;
+                (filter.kind, filter.handle.upgrade()?)
+            }
+            Err(_) => return None,
+        };
+
+        Some(match kind {
+            Kind::Lowpass => Filter::Lowpass(LowpassFilter { handle }),
+            Kind::Highpass => Filter::Highpass(HighpassFilter { handle }),
+            Kind::Bandpass => Filter::Bandpass(BandpassFilter { handle }),
+        })
+    }
+}
+
+impl<'a> From<&'a LowpassFilter> for Filter {
+    fn from(filter: &'a LowpassFilter) -> Filter {
+        Filter::Lowpass(filter.clone())
+    }
+}
+
+impl<'a> From<&'a HighpassFilter> for Filter {
+    fn from(filter: &'a HighpassFilter) -> Filter {
+        Filter::Highpass(filter.clone())
+    }
+}
+
+impl<'a> From<&'a BandpassFilter> for Filter {
+    fn from(filter: &'a BandpassFilter) -> Filter {
+        Filter::Bandpass(filter.clone())
+    }
+}
+
+impl Default for LowpassFilter {
+    fn default() -> LowpassFilter {
+        LowpassFilter::new()
+    }
+}
+
+impl Default for HighpassFilter {
+    fn default() -> HighpassFilter {
+        HighpassFilter::new()
+    }
+}
+
+impl Default for BandpassFilter {
+    fn default() -> BandpassFilter {
+        BandpassFilter::new()
+    }
+}
+
+impl PartialEq for LowpassFilter {
+    /// Two handles are equal if they refer to the same filter.
+    fn eq(&self, other: &LowpassFilter) -> bool {
+        Arc::ptr_eq(&self.handle, &other.handle)
+    }
+}
+
+impl PartialEq for HighpassFilter {
+    /// Two handles are equal if they refer to the same filter.
+    fn eq(&self, other: &HighpassFilter) -> bool {
+        Arc::ptr_eq(&self.handle, &other.handle)
+    }
+}
+
+impl PartialEq for BandpassFilter {
+    /// Two handles are equal if they refer to the same filter.
+    fn eq(&self, other: &BandpassFilter) -> bool {
+        Arc::ptr_eq(&self.handle, &other.handle)
+    }
+}
+
+impl fmt::Debug for LowpassFilter {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("LowpassFilter")
+            .field("gain", &self.get_gain())
+            .field("gainhf", &self.get_gainhf())
+            .finish()
+    }
+}
+
+impl fmt::Debug for HighpassFilter {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("HighpassFilter")
+            .field("gain", &self.get_gain())
+            .field("gainlf", &self.get_gainlf())
+            .finish()
+    }
+}
+
+impl fmt::Debug for BandpassFilter {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("BandpassFilter")
+            .field("gain", &self.get_gain())
+            .field("gainlf", &self.get_gainlf())
+            .field("gainhf", &self.get_gainhf())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::filter::{BandpassFilter, Filter, LowpassFilter};
+
+    #[test]
+    fn filter_params_OK() {
+        let lowpass = LowpassFilter::new();
+        lowpass.set_gainhf(0.25);
+        assert_eq!(lowpass.get_gain(), 1.);
+        assert_eq!(lowpass.get_gainhf(), 0.25);
+
+        let bandpass = BandpassFilter::new();
+        bandpass.set_gainlf(0.5);
+        assert_eq!(bandpass.get_gainlf(), 0.5);
+        assert_eq!(bandpass.get_gainhf(), 1.);
+    }
+
+    #[test]
+    fn filter_handle_OK() {
+        let lowpass = LowpassFilter::new();
+        let filter = Filter::from(&lowpass);
+
+        assert_eq!(Filter::from_id(filter.id()), Some(filter.clone()));
+        assert_eq!(filter, Filter::Lowpass(lowpass.clone()));
+        drop(filter);
+        assert!(lowpass != LowpassFilter::new());
+    }
+}
@@ -49,6 +49,34 @@ pub fn init() -> Result<(), OpenAlContextError> {
     return OpenAlData::check_al_context();
 }
 
+/**
+ * Initialize the internal context with a number of auxiliary sends per
+ * source.
+ *
+ * Each auxiliary send can be connected to a ReverbEffect, see
+ * `AudioController::connect_send`. The device may grant less sends than
+ * requested, `ears::max_auxiliary_sends` gives the granted count. Has no
+ * effect on the number of sends if the context already exists.
+ *
+ * # Argument
+ * * `sends` - The number of sends to request, 4 by default
+ *
+ * # Return
+ * `Ok(())` if initialization is successful, `Err(OpenAlContextError)` otherwise
+ *
+ * # Example
+ * ```no_run
+ * fn main() -> Result<(), ears::OpenAlContextError> {
+ *     ears::init_with_sends(2)?;
+ *     Ok(())
+ * }
+ * ```
+ */
+pub fn init_with_sends(sends: i32) -> Result<(), OpenAlContextError> {
+    OpenAlData::request_auxiliary_sends(sends);
+    OpenAlData::check_al_context()
+}
+
 /**
  * Initialize the input device context
  *
@@ -71,8 +99,7 @@ pub fn init_in() -> Result<RecordContext, OpenAlContextError> {
 mod test {
     #![allow(non_snake_case)]
 
-    use init;
-    use init_in;
+    use crate::{init, init_in};
     use std::thread;
 
     #[test]
@@ -35,6 +35,7 @@ use std::error::Error;
 use std::ffi::CString;
 use std::fmt;
 use std::ptr;
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Mutex;
 
 #[derive(Clone)]
@@ -81,6 +82,9 @@ impl fmt::Debug for OpenAlContextError {
 
 impl Error for OpenAlContextError {}
 
+/// The number of auxiliary sends per source requested at context creation
+static REQUESTED_SENDS: AtomicI32 = AtomicI32::new(4);
+
 lazy_static! {
     static ref AL_CONTEXT: Mutex<Result<OpenAlData, OpenAlContextError>> =
         Mutex::new(OpenAlData::new());
@@ -102,7 +106,12 @@ impl OpenAlData {
         if device == 0 {
             return Err(OpenAlContextError::DefaultDeviceError);
         }
-        let context = unsafe { ffi::alcCreateContext(device, ptr::null_mut()) };
+        let mut attributes = [
+            ffi::ALC_MAX_AUXILIARY_SENDS,
+            REQUESTED_SENDS.load(Ordering::Relaxed),
+            0,
+        ];
+        let context = unsafe { ffi::alcCreateContext(device, attributes.as_mut_ptr()) };
         if context == 0 {
             return Err(OpenAlContextError::CreationError);
         }
@@ -181,6 +190,14 @@ impl OpenAlData {
         }
     }
 
+    /// Set the number of auxiliary sends per source to request when the
+    /// context is created.
+    ///
+    /// Has no effect once the context exists.
+    pub fn request_auxiliary_sends(count: i32) {
+        REQUESTED_SENDS.store(count, Ordering::Relaxed);
+    }
+
     /// Check if AL_SOFT_direct_channels extension is present
     ///
     /// # Return
@@ -190,6 +207,51 @@ impl OpenAlData {
         unsafe { ffi::alIsExtensionPresent(c_str.as_ptr()) == ffi::AL_TRUE }
     }
 
+    /// Check if AL_EXT_source_distance_model extension is present
+    ///
+    /// # Return
+    /// true if the extension is present, otherwise false.
+    pub fn source_distance_model_capable() -> bool {
+        let c_str = CString::new("AL_EXT_source_distance_model").unwrap();
+        unsafe { ffi::alIsExtensionPresent(c_str.as_ptr()) == ffi::AL_TRUE }
+    }
+
+    /// Check if AL_SOFT_source_spatialize extension is present
+    ///
+    /// # Return
+    /// true if the extension is present, otherwise false.
+    pub fn source_spatialize_capable() -> bool {
+        let c_str = CString::new("AL_SOFT_source_spatialize").unwrap();
+        unsafe { ffi::alIsExtensionPresent(c_str.as_ptr()) == ffi::AL_TRUE }
+    }
+
+    /// Check if AL_EXT_STEREO_ANGLES extension is present
+    ///
+    /// # Return
+    /// true if the extension is present, otherwise false.
+    pub fn stereo_angles_capable() -> bool {
+        let c_str = CString::new("AL_EXT_STEREO_ANGLES").unwrap();
+        unsafe { ffi::alIsExtensionPresent(c_str.as_ptr()) == ffi::AL_TRUE }
+    }
+
+    /// Check if AL_EXT_SOURCE_RADIUS extension is present
+    ///
+    /// # Return
+    /// true if the extension is present, otherwise false.
+    pub fn source_radius_capable() -> bool {
+        let c_str = CString::new("AL_EXT_SOURCE_RADIUS").unwrap();
+        unsafe { ffi::alIsExtensionPresent(c_str.as_ptr()) == ffi::AL_TRUE }
+    }
+
+    /// Check if AL_SOFT_source_resampler extension is present
+    ///
+    /// # Return
+    /// true if the extension is present, otherwise false.
+    pub fn source_resampler_capable() -> bool {
+        let c_str = CString::new("AL_SOFT_source_resampler").unwrap();
+        unsafe { ffi::alIsExtensionPresent(c_str.as_ptr()) == ffi::AL_TRUE }
+    }
+
     /// Check if the input context is created.
     ///
     /// This function check if the input OpenAl context is already created.
@@ -229,7 +291,20 @@ macro_rules! check_openal_context(
     ($def_ret:expr) => (
             match OpenAlData::check_al_context() {
                 Ok(_)    => {},
-                Err(err) => { println!("{}", err); return $def_ret; }
+                Err(err) => {
+                    println!("{}", err);
+                    #[allow(clippy::unused_unit)]
+                    return $def_ret;
+                }
+            }
+        );
+);
+
+macro_rules! try_openal_context(
+    () => (
+            match OpenAlData::check_al_context() {
+                Ok(_)  => {},
+                Err(_) => { return Err(SoundError::InvalidOpenALContext); }
             }
         );
 );
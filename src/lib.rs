@@ -70,19 +70,50 @@ extern crate libc;
 extern crate lazy_static;
 
 // Reexport public API
+pub use area::Area;
 pub use audio_controller::AudioController;
 pub use audio_tags::{AudioTags, Tags};
-pub use einit::{init, init_in};
+pub use automation::{Automation, Interpolate, Interpolation, Keyframe};
+pub use bus::Bus;
+pub use clock::{device_clock, device_latency, play_group, play_group_at};
+pub use cone::Cone;
+pub use container::{ContainerMode, SoundContainer, Variant};
+pub use distance_model::DistanceModel;
+pub use ducking::Ducker;
+pub use einit::{init, init_in, init_with_sends};
 pub use error::SoundError;
+pub use fade::{Curve, FadeHandle, FadeStatus};
+pub use filter::{BandpassFilter, Filter, HighpassFilter, LowpassFilter};
 pub use internal::OpenAlContextError;
 pub use music::Music;
-pub use presets::ReverbPreset;
+pub use oneshot::{
+    max_oneshot_sources, set_max_oneshot_sources, OneShotHandle, OneShotParams,
+};
+pub use preset_registry::{
+    builtin_reverb_presets, register_reverb_preset, reverb_preset, reverb_preset_names,
+    unregister_reverb_preset,
+};
+#[cfg(feature = "serde")]
+pub use preset_registry::{
+    load_reverb_presets, parse_reverb_presets, save_reverb_presets, write_reverb_presets,
+    PresetError, PresetFormat,
+};
+pub use presets::{ReverbPreset, ReverbProperties};
 pub use record_context::RecordContext;
 pub use recorder::Recorder;
-pub use reverb_effect::ReverbEffect;
+pub use resampler::{default_resampler, resamplers, set_default_resampler, Resampler};
+pub use reverb_effect::{ReverbEffect, ReverbType};
+pub use reverb_zone::{ReverbZone, ZoneShape};
+pub use rolloff::RolloffCurve;
+pub use sends::max_auxiliary_sends;
+pub use snapshot::{BusSnapshot, MixSnapshot, SnapshotBlend};
 pub use sound::Sound;
 pub use sound_data::SoundData;
+pub use source_properties::SourceProperties;
+pub use spatialize::Spatialize;
 pub use states::State;
+pub use ticker::{start_ticker, stop_ticker, update};
+pub use voice::{Voice, VoiceManager, VoiceParams};
 
 // Hidden internal bindings
 mod internal;
@@ -91,17 +122,39 @@ mod sndfile;
 
 // The public ears API
 
+mod area;
 mod audio_controller;
 mod audio_tags;
+mod automation;
+mod bus;
+mod clock;
+mod cone;
+mod container;
+mod distance_model;
+mod ducking;
 #[path = "init.rs"]
 mod einit;
 mod error;
+mod fade;
+mod filter;
 pub mod listener;
+mod mixer;
 mod music;
+mod oneshot;
+mod preset_registry;
 mod presets;
 mod record_context;
 mod recorder;
+mod resampler;
 mod reverb_effect;
+mod reverb_zone;
+mod rolloff;
+mod sends;
+mod snapshot;
 mod sound;
 mod sound_data;
+mod source_properties;
+mod spatialize;
 mod states;
+mod ticker;
+mod voice;
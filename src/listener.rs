@@ -21,6 +21,9 @@
 
 //! Module for manage the listener in the scene.
 
+use crate::area;
+use crate::distance_model;
+use crate::distance_model::DistanceModel;
 use crate::internal::OpenAlData;
 use crate::openal::{al, ffi};
 
@@ -75,6 +78,9 @@ pub fn get_volume() -> f32 {
  * To switch from a left handed coordinate system, flip the sign on the Z
  * coordinate.
  *
+ * The Audio Sources spread over an Area are moved to the point of their Area
+ * closest to the new position, see `AudioController::set_area`.
+ *
  * Default is [0., 0., 0.].
  *
  * # Argument
@@ -89,6 +95,7 @@ pub fn get_volume() -> f32 {
 pub fn set_position(position: [f32; 3]) -> () {
     check_openal_context!(());
     al::alListenerfv(ffi::AL_POSITION, &position[0]);
+    area::update();
 }
 
 /**
@@ -195,10 +202,123 @@ pub fn get_velocity() -> [f32; 3] {
     velocity
 }
 
+/**
+ * Set the Doppler factor of the scene.
+ *
+ * Scales the pitch shift caused by the velocities of the listener and the
+ * Audio Sources. At 0.0 the Doppler effect is disabled, values above 1.0
+ * exaggerate it.
+ *
+ * Default is 1.0.
+ *
+ * # Argument
+ * * `factor` - The new Doppler factor, should be positive
+ *
+ * # Example
+ * ```
+ * # use ears::listener;
+ * listener::set_doppler_factor(0.5);
+ * ```
+ */
+pub fn set_doppler_factor(factor: f32) {
+    check_openal_context!(());
+
+    al::alDopplerFactor(factor);
+}
+
+/**
+ * Get the Doppler factor of the scene.
+ *
+ * # Return
+ * The Doppler factor of the scene
+ */
+pub fn get_doppler_factor() -> f32 {
+    check_openal_context!(1.);
+
+    al::alGetFloat(ffi::AL_DOPPLER_FACTOR)
+}
+
+/**
+ * Set the speed of sound of the scene.
+ *
+ * The speed of sound is expressed in world units per second, like the
+ * velocities. The default of 343.3 matches the speed of sound in the air
+ * when a world unit is a meter.
+ *
+ * # Argument
+ * * `speed` - The new speed of sound, should be greater than 0.0
+ *
+ * # Example
+ * ```
+ * # use ears::listener;
+ * // The world units are centimeters
+ * listener::set_speed_of_sound(34330.);
+ * ```
+ */
+pub fn set_speed_of_sound(speed: f32) {
+    check_openal_context!(());
+
+    al::alSpeedOfSound(speed);
+}
+
+/**
+ * Get the speed of sound of the scene.
+ *
+ * # Return
+ * The speed of sound, in world units per second
+ */
+pub fn get_speed_of_sound() -> f32 {
+    check_openal_context!(343.3);
+
+    al::alGetFloat(ffi::AL_SPEED_OF_SOUND)
+}
+
+/**
+ * Set the distance model of the scene.
+ *
+ * The distance model sets how the volume of the Audio Sources decreases
+ * with their distance to the listener, according to their reference
+ * distance, maximum distance and attenuation. It applies to all the Audio
+ * Sources, except the ones using their own model, see
+ * `AudioController::set_distance_model`.
+ *
+ * Default is DistanceModel::InverseClamped.
+ *
+ * # Argument
+ * * `model` - The new distance model of the scene
+ *
+ * # Example
+ * ```
+ * # use ears::listener;
+ * use ears::DistanceModel;
+ * listener::set_distance_model(DistanceModel::LinearClamped);
+ * ```
+ */
+pub fn set_distance_model(model: DistanceModel) {
+    check_openal_context!(());
+
+    distance_model::set_global(model);
+}
+
+/**
+ * Get the distance model of the scene.
+ *
+ * # Return
+ * The distance model of the scene
+ */
+pub fn get_distance_model() -> DistanceModel {
+    check_openal_context!(DistanceModel::default());
+
+    distance_model::global()
+}
+
 #[cfg(test)]
 mod test {
-    use listener::{
-        get_orientation, get_position, get_volume, set_orientation, set_position, set_volume,
+    use crate::distance_model::DistanceModel;
+    use crate::listener::{
+        get_distance_model, get_doppler_factor, get_orientation, get_position, get_speed_of_sound,
+        get_volume, set_distance_model, set_doppler_factor, set_orientation, set_position,
+        set_speed_of_sound, set_volume,
     };
 
     #[test]
@@ -226,4 +346,20 @@ mod test {
         assert_eq!(s1, [50f32, 150f32, 234f32]);
         assert_eq!(s2, [277f32, 125f32, 71f32])
     }
+
+    #[test]
+    #[ignore]
+    pub fn listener_set_distance_model() {
+        set_distance_model(DistanceModel::ExponentClamped);
+        assert_eq!(get_distance_model(), DistanceModel::ExponentClamped);
+    }
+
+    #[test]
+    #[ignore]
+    pub fn listener_set_doppler() {
+        set_doppler_factor(0.5);
+        set_speed_of_sound(1000.);
+        assert_eq!(get_doppler_factor(), 0.5);
+        assert_eq!(get_speed_of_sound(), 1000.);
+    }
 }
@@ -0,0 +1,203 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Internal mixing of the gain and pitch of the sources.
+//!
+//! Several features of __ears__ scale the volume or the pitch of a source on
+//! top of the value set by the user. The user values and the factors of each
+//! feature are kept here, and OpenAL receives their product, so the features
+//! never overwrite each other nor the user values.
+//!
+//! Sources without any factor have no entry: their volume and pitch live in
+//! OpenAL only.
+//!
+//! `AudioController::set_volume` and `set_pitch`, the fades and the
+//! automations write the user values with `set_volume` and `set_pitch`. The
+//! rolloff curves, the Buses and the Duckers write their factor with
+//! `set_gain` and `set_pitch_factor`. The sources are forgotten when they
+//! are deleted.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::openal::{al, ffi};
+
+/// The features scaling the volume or the pitch of a source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Stage {
+    /// The custom rolloff curve
+    Rolloff,
+    /// The volume, mute and pitch of the Bus hierarchy
+    Bus,
+    /// The attenuation of the Duckers
+    Ducking,
+}
+
+struct Channel {
+    volume: f32,
+    gains: HashMap<Stage, f32>,
+    pitch: f32,
+    pitches: HashMap<Stage, f32>,
+}
+
+impl Channel {
+    fn new(al_source: u32) -> Channel {
+        let mut volume = 1.;
+        al::alGetSourcef(al_source, ffi::AL_GAIN, &mut volume);
+        let mut pitch = 1.;
+        al::alGetSourcef(al_source, ffi::AL_PITCH, &mut pitch);
+
+        Channel {
+            volume,
+            gains: HashMap::new(),
+            pitch,
+            pitches: HashMap::new(),
+        }
+    }
+
+    fn apply_volume(&self, al_source: u32) {
+        let gain = self.gains.values().fold(self.volume, |gain, g| gain * g);
+        al::alSourcef(al_source, ffi::AL_GAIN, gain);
+    }
+
+    fn apply_pitch(&self, al_source: u32) {
+        let pitch = self.pitches.values().fold(self.pitch, |pitch, p| pitch * p);
+        al::alSourcef(al_source, ffi::AL_PITCH, pitch);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.gains.is_empty() && self.pitches.is_empty()
+    }
+}
+
+lazy_static! {
+    static ref CHANNELS: Mutex<HashMap<u32, Channel>> = Mutex::new(HashMap::new());
+}
+
+/// Set the volume of a source, as set by the user.
+pub(crate) fn set_volume(al_source: u32, volume: f32) {
+    if let Ok(mut channels) = CHANNELS.lock() {
+        match channels.get_mut(&al_source) {
+            Some(channel) => {
+                channel.volume = volume;
+                channel.apply_volume(al_source);
+            }
+            None => al::alSourcef(al_source, ffi::AL_GAIN, volume),
+        }
+    }
+}
+
+/// The volume of a source, as set by the user.
+pub(crate) fn volume(al_source: u32) -> f32 {
+    if let Ok(channels) = CHANNELS.lock() {
+        if let Some(channel) = channels.get(&al_source) {
+            return channel.volume;
+        }
+    }
+
+    let mut volume = 0.;
+    al::alGetSourcef(al_source, ffi::AL_GAIN, &mut volume);
+    volume
+}
+
+/// The gain of a source with all the stages but one.
+pub(crate) fn gain_without(al_source: u32, stage: Stage) -> f32 {
+    if let Ok(channels) = CHANNELS.lock() {
+        if let Some(channel) = channels.get(&al_source) {
+            return channel
+                .gains
+                .iter()
+                .filter(|(other, _)| **other != stage)
+                .fold(channel.volume, |gain, (_, g)| gain * g);
+        }
+    }
+
+    volume(al_source)
+}
+
+/// Scale the volume of a source for a stage, or remove the factor of the
+/// stage with None.
+pub(crate) fn set_gain(al_source: u32, stage: Stage, gain: Option<f32>) {
+    if let Ok(mut channels) = CHANNELS.lock() {
+        let channel = channels
+            .entry(al_source)
+            .or_insert_with(|| Channel::new(al_source));
+        match gain {
+            Some(gain) => channel.gains.insert(stage, gain),
+            None => channel.gains.remove(&stage),
+        };
+        channel.apply_volume(al_source);
+        if channel.is_empty() {
+            channels.remove(&al_source);
+        }
+    }
+}
+
+/// Set the pitch of a source, as set by the user.
+pub(crate) fn set_pitch(al_source: u32, pitch: f32) {
+    if let Ok(mut channels) = CHANNELS.lock() {
+        match channels.get_mut(&al_source) {
+            Some(channel) => {
+                channel.pitch = pitch;
+                channel.apply_pitch(al_source);
+            }
+            None => al::alSourcef(al_source, ffi::AL_PITCH, pitch),
+        }
+    }
+}
+
+/// The pitch of a source, as set by the user.
+pub(crate) fn pitch(al_source: u32) -> f32 {
+    if let Ok(channels) = CHANNELS.lock() {
+        if let Some(channel) = channels.get(&al_source) {
+            return channel.pitch;
+        }
+    }
+
+    let mut pitch = 0.;
+    al::alGetSourcef(al_source, ffi::AL_PITCH, &mut pitch);
+    pitch
+}
+
+/// Scale the pitch of a source for a stage, or remove the factor of the
+/// stage with None.
+pub(crate) fn set_pitch_factor(al_source: u32, stage: Stage, factor: Option<f32>) {
+    if let Ok(mut channels) = CHANNELS.lock() {
+        let channel = channels
+            .entry(al_source)
+            .or_insert_with(|| Channel::new(al_source));
+        match factor {
+            Some(factor) => channel.pitches.insert(stage, factor),
+            None => channel.pitches.remove(&stage),
+        };
+        channel.apply_pitch(al_source);
+        if channel.is_empty() {
+            channels.remove(&al_source);
+        }
+    }
+}
+
+/// Drop the factors of a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut channels) = CHANNELS.lock() {
+        channels.remove(&al_source);
+    }
+}
@@ -34,15 +34,25 @@ use std::vec::Vec;
 
 use crate::audio_controller::AudioController;
 use crate::audio_tags::{get_sound_tags, AudioTags, Tags};
+use crate::automation::PlaybackClock;
+use crate::clock;
+use crate::cone::Cone;
+use crate::distance_model;
 use crate::error::SoundError;
 use crate::internal::OpenAlData;
+use crate::mixer;
 use crate::openal::{al, ffi};
+use crate::resampler;
 use crate::reverb_effect::ReverbEffect;
+use crate::rolloff;
+use crate::sends;
 use crate::sndfile::OpenMode::Read;
 use crate::sndfile::SeekMode::SeekSet;
 use crate::sndfile::{SndFile, SndInfo};
+use crate::spatialize::{Spatialize, DEFAULT_STEREO_ANGLES};
 use crate::states::State;
 use crate::states::State::{Initial, Paused, Playing, Stopped};
+use crate::ticker;
 
 const BUFFER_COUNT: i32 = 2;
 
@@ -183,6 +193,41 @@ fn calculate_true_offset(
     offset.try_into().unwrap_or(0)
 }
 
+// The offset into the full file of the frame being played by a streaming
+// source
+pub(crate) fn stream_offset(
+    al_source: u32,
+    cursor: &Arc<AtomicI64>,
+    buffer_size: i64,
+    info: &SndInfo,
+) -> i32 {
+    let mut sample_offset: i32 = 0;
+    al::alGetSourcei(al_source, ffi::AL_SAMPLE_OFFSET, &mut sample_offset);
+
+    stream_offset_at(al_source, cursor, buffer_size, info, sample_offset)
+}
+
+// The offset into the full file of a frame of the buffers queued on a
+// streaming source
+pub(crate) fn stream_offset_at(
+    al_source: u32,
+    cursor: &Arc<AtomicI64>,
+    buffer_size: i64,
+    info: &SndInfo,
+    sample_offset: i32,
+) -> i32 {
+    let mut buffers_queued: i32 = 0;
+    al::alGetSourcei(al_source, ffi::AL_BUFFERS_QUEUED, &mut buffers_queued);
+
+    calculate_true_offset(
+        info,
+        cursor.load(Ordering::Relaxed),
+        buffer_size,
+        buffers_queued,
+        sample_offset,
+    )
+}
+
 // Sets the new cursor from offset in seconds with reasonable accuracy
 fn set_cursor_from_offset(info: &SndInfo, cursor: Arc<AtomicI64>, offset: f32) {
     let frames = info.frames as f32;
@@ -224,6 +269,8 @@ impl Music {
         let mut buffer_ids = [0; BUFFER_COUNT as usize];
         // create the source
         al::alGenSources(1, &mut source_id);
+        distance_model::register(source_id);
+        resampler::register(source_id);
         // create the buffers
         al::alGenBuffers(BUFFER_COUNT, &mut buffer_ids[0]);
 
@@ -305,8 +352,9 @@ impl Music {
         // Queue the buffers
         al::alSourceQueueBuffers(al_source, 2, &al_buffers[0]);
 
-        // Start playing
-        al::alSourcePlay(al_source);
+        // Leave the source ready to be started, the streaming thread waits
+        // for it as long as it isn't stopped
+        al::alSourceRewind(al_source);
 
         let (looping_sender, looping_receiver): (Sender<bool>, Receiver<bool>) = channel();
         let (offset_sender, offset_receiver): (Sender<i32>, Receiver<i32>) = channel();
@@ -417,21 +465,8 @@ impl AudioController for Music {
     fn play(&mut self) -> () {
         check_openal_context!(());
 
-        match self.get_state() {
-            Paused => {
-                al::alSourcePlay(self.al_source);
-                return;
-            }
-            _ => {
-                if self.is_playing() {
-                    al::alSourceStop(self.al_source);
-                    // wait a bit for openal terminate
-                    sleep(Duration::from_millis(50));
-                }
-                self.file.as_mut().unwrap().seek(0, SeekSet);
-                self.process_music();
-            }
-        }
+        self.cue();
+        clock::start(&[self.al_source], None);
     }
 
     /**
@@ -449,6 +484,7 @@ impl AudioController for Music {
     fn stop(&mut self) -> () {
         check_openal_context!(());
 
+        clock::cancel(self.al_source);
         al::alSourceStop(self.al_source);
     }
 
@@ -458,26 +494,11 @@ impl AudioController for Music {
     fn connect(&mut self, reverb_effect: &Option<ReverbEffect>) {
         check_openal_context!(());
 
-        match reverb_effect {
-            Some(reverb_effect) => {
-                al::alSource3i(
-                    self.al_source,
-                    ffi::AL_AUXILIARY_SEND_FILTER,
-                    reverb_effect.slot() as i32,
-                    0,
-                    ffi::AL_FILTER_NULL,
-                );
-            }
-            None => {
-                al::alSource3i(
-                    self.al_source,
-                    ffi::AL_AUXILIARY_SEND_FILTER,
-                    ffi::AL_EFFECTSLOT_NULL,
-                    0,
-                    ffi::AL_FILTER_NULL,
-                );
-            }
-        }
+        sends::connect(
+            self.al_source,
+            0,
+            reverb_effect.as_ref().map(|effect| effect.slot()),
+        );
     }
 
     /**
@@ -537,21 +558,11 @@ impl AudioController for Music {
     fn get_offset(&self) -> i32 {
         check_openal_context!(0);
 
-        let mut sample_offset: i32 = 0;
-        al::alGetSourcei(self.al_source, ffi::AL_SAMPLE_OFFSET, &mut sample_offset);
-
-        let mut buffers_queued: i32 = 0;
-        al::alGetSourcei(self.al_source, ffi::AL_BUFFERS_QUEUED, &mut buffers_queued);
-
-        let cursor = self.cursor.load(Ordering::Relaxed);
-        let buffer_size = self.sample_to_read;
-
-        calculate_true_offset(
+        stream_offset(
+            self.al_source,
+            &self.cursor,
+            self.sample_to_read,
             &self.file_infos,
-            cursor,
-            buffer_size,
-            buffers_queued,
-            sample_offset,
         )
     }
 
@@ -568,7 +579,7 @@ impl AudioController for Music {
     fn set_volume(&mut self, volume: f32) -> () {
         check_openal_context!(());
 
-        al::alSourcef(self.al_source, ffi::AL_GAIN, volume);
+        mixer::set_volume(self.al_source, volume);
     }
 
     /**
@@ -580,9 +591,7 @@ impl AudioController for Music {
     fn get_volume(&self) -> f32 {
         check_openal_context!(0.);
 
-        let mut volume: f32 = 0.;
-        al::alGetSourcef(self.al_source, ffi::AL_GAIN, &mut volume);
-        volume
+        mixer::volume(self.al_source)
     }
 
     /**
@@ -683,7 +692,7 @@ impl AudioController for Music {
     fn set_pitch(&mut self, pitch: f32) -> () {
         check_openal_context!(());
 
-        al::alSourcef(self.al_source, ffi::AL_PITCH, pitch)
+        mixer::set_pitch(self.al_source, pitch)
     }
 
     /**
@@ -695,9 +704,7 @@ impl AudioController for Music {
     fn get_pitch(&self) -> f32 {
         check_openal_context!(0.);
 
-        let mut pitch = 0.;
-        al::alGetSourcef(self.al_source, ffi::AL_PITCH, &mut pitch);
-        pitch
+        mixer::pitch(self.al_source)
     }
 
     /**
@@ -732,7 +739,7 @@ impl AudioController for Music {
      * # Return
      * True if the Music is relative to the listener false otherwise
      */
-    fn is_relative(&mut self) -> bool {
+    fn is_relative(&self) -> bool {
         check_openal_context!(false);
 
         let mut boolean = 0;
@@ -780,6 +787,39 @@ impl AudioController for Music {
         position
     }
 
+    /**
+     * Set the velocity of the Music.
+     *
+     * The velocity is only used for the Doppler effect, it doesn't move the
+     * Music. See `listener::set_doppler_factor`.
+     *
+     * Default velocity is [0.0, 0.0, 0.0].
+     *
+     * # Argument
+     * * `velocity` - A three dimensional vector of f32 containing the velocity
+     *   of the music [x, y, z].
+     */
+    fn set_velocity(&mut self, velocity: [f32; 3]) {
+        check_openal_context!(());
+
+        al::alSourcefv(self.al_source, ffi::AL_VELOCITY, &velocity[0]);
+    }
+
+    /**
+     * Get the velocity of the Music.
+     *
+     * # Return
+     * A three dimensional vector of f32 containing the velocity
+     * of the music [x, y, z].
+     */
+    fn get_velocity(&self) -> [f32; 3] {
+        check_openal_context!([0.0; 3]);
+
+        let mut velocity: [f32; 3] = [0.0; 3];
+        al::alGetSourcefv(self.al_source, ffi::AL_VELOCITY, &mut velocity[0]);
+        velocity
+    }
+
     /**
      * Set the direction of the Music.
      *
@@ -810,6 +850,58 @@ impl AudioController for Music {
         direction
     }
 
+    /**
+     * Set the sound cone of the Music.
+     *
+     * The cone only has an effect once a direction is set.
+     *
+     * The default cone is omnidirectional, see `Cone::default`.
+     *
+     * # Argument
+     * `cone` - The new cone of the Music.
+     */
+    fn set_cone(&mut self, cone: Cone) {
+        check_openal_context!(());
+
+        al::alSourcef(self.al_source, ffi::AL_CONE_INNER_ANGLE, cone.inner_angle);
+        al::alSourcef(self.al_source, ffi::AL_CONE_OUTER_ANGLE, cone.outer_angle);
+        al::alSourcef(self.al_source, ffi::AL_CONE_OUTER_GAIN, cone.outer_gain);
+        al::alSourcef(self.al_source, ffi::AL_CONE_OUTER_GAINHF, cone.outer_gainhf);
+    }
+
+    /**
+     * Get the sound cone of the Music.
+     *
+     * # Return
+     * The current cone of the Music.
+     */
+    fn get_cone(&self) -> Cone {
+        check_openal_context!(Cone::default());
+
+        let mut cone = Cone::default();
+        al::alGetSourcef(
+            self.al_source,
+            ffi::AL_CONE_INNER_ANGLE,
+            &mut cone.inner_angle,
+        );
+        al::alGetSourcef(
+            self.al_source,
+            ffi::AL_CONE_OUTER_ANGLE,
+            &mut cone.outer_angle,
+        );
+        al::alGetSourcef(
+            self.al_source,
+            ffi::AL_CONE_OUTER_GAIN,
+            &mut cone.outer_gain,
+        );
+        al::alGetSourcef(
+            self.al_source,
+            ffi::AL_CONE_OUTER_GAINHF,
+            &mut cone.outer_gainhf,
+        );
+        cone
+    }
+
     /**
      * Set the maximum distance of the Music.
      *
@@ -817,6 +909,11 @@ impl AudioController for Music {
      * clamped distance model, or where attenuation reaches 0.0 gain for linear
      * distance models with a default rolloff factor.
      *
+     * The attenuation depends on the distance model of the Music, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     * While a rolloff curve is set, the attenuation is kept aside and only
+     * applies once the curve is removed, see `set_rolloff_curve`.
+     *
      * The default maximum distance is +inf.
      *
      * # Argument
@@ -849,6 +946,11 @@ impl AudioController for Music {
      * At 0.0, no distance attenuation ever occurs on non-linear
      * attenuation models.
      *
+     * The attenuation depends on the distance model of the Music, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     * While a rolloff curve is set, the attenuation is kept aside and only
+     * applies once the curve is removed, see `set_rolloff_curve`.
+     *
      * The default distance reference is 1.
      *
      * # Argument
@@ -884,6 +986,11 @@ impl AudioController for Music {
      * Multiplier to exaggerate or diminish distance attenuation.
      * At 0.0, no distance attenuation ever occurs.
      *
+     * The attenuation depends on the distance model of the Music, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     * While a rolloff curve is set, the attenuation is kept aside and only
+     * applies once the curve is removed, see `set_rolloff_curve`.
+     *
      * The default attenuation is 1.
      *
      * # Arguments
@@ -892,7 +999,7 @@ impl AudioController for Music {
     fn set_attenuation(&mut self, attenuation: f32) -> () {
         check_openal_context!(());
 
-        al::alSourcef(self.al_source, ffi::AL_ROLLOFF_FACTOR, attenuation);
+        rolloff::set_attenuation(self.al_source, attenuation);
     }
 
     /**
@@ -904,9 +1011,7 @@ impl AudioController for Music {
     fn get_attenuation(&self) -> f32 {
         check_openal_context!(1.);
 
-        let mut attenuation = 0.;
-        al::alGetSourcef(self.al_source, ffi::AL_ROLLOFF_FACTOR, &mut attenuation);
-        attenuation
+        rolloff::attenuation(self.al_source)
     }
 
     /**
@@ -969,6 +1074,142 @@ impl AudioController for Music {
         }
     }
 
+    /**
+     * Set whether the Music is positioned in the scene.
+     *
+     * With `Spatialize::On` a stereo or multichannel Music is positioned
+     * like a mono one, its channels placed around its position, instead of
+     * playing on the matching speakers. Applies only when the
+     * AL_SOFT_source_spatialize extension exists.
+     *
+     * [https://openal-soft.org/openal-extensions/SOFT_source_spatialize.txt]()
+     *
+     * The default is `Spatialize::Auto`.
+     *
+     * # Argument
+     * * `spatialize` - The new spatialization mode
+     */
+    fn set_spatialize(&mut self, spatialize: Spatialize) {
+        check_openal_context!(());
+
+        if OpenAlData::source_spatialize_capable() {
+            al::alSourcei(
+                self.al_source,
+                ffi::AL_SOURCE_SPATIALIZE_SOFT,
+                spatialize.to_al(),
+            );
+        }
+    }
+
+    /**
+     * Get whether the Music is positioned in the scene.
+     *
+     * Will always return `Spatialize::Auto` if the AL_SOFT_source_spatialize
+     * extension is not present.
+     *
+     * # Return
+     * The current spatialization mode
+     */
+    fn get_spatialize(&self) -> Spatialize {
+        check_openal_context!(Spatialize::Auto);
+
+        if !OpenAlData::source_spatialize_capable() {
+            return Spatialize::Auto;
+        }
+
+        let mut spatialize = 0;
+        al::alGetSourcei(
+            self.al_source,
+            ffi::AL_SOURCE_SPATIALIZE_SOFT,
+            &mut spatialize,
+        );
+        Spatialize::from_al(spatialize)
+    }
+
+    /**
+     * Set the angles of the channels of a stereo Music.
+     *
+     * The angles are in radians, counter-clockwise from the front of the
+     * listener: positive angles are on the left. Applies only when the
+     * AL_EXT_STEREO_ANGLES extension exists and when playing stereo
+     * buffers which aren't spatialized (see `set_spatialize`).
+     *
+     * The default is [PI / 6, -PI / 6], 30 degrees on each side.
+     *
+     * # Argument
+     * * `angles` - The angles of the [left, right] channels
+     */
+    fn set_stereo_angles(&mut self, angles: [f32; 2]) {
+        check_openal_context!(());
+
+        if OpenAlData::stereo_angles_capable() {
+            al::alSourcefv(self.al_source, ffi::AL_STEREO_ANGLES, &angles[0]);
+        }
+    }
+
+    /**
+     * Get the angles of the channels of a stereo Music.
+     *
+     * Will always return the default angles if the AL_EXT_STEREO_ANGLES
+     * extension is not present.
+     *
+     * # Return
+     * The angles of the [left, right] channels, in radians
+     */
+    fn get_stereo_angles(&self) -> [f32; 2] {
+        check_openal_context!(DEFAULT_STEREO_ANGLES);
+
+        if !OpenAlData::stereo_angles_capable() {
+            return DEFAULT_STEREO_ANGLES;
+        }
+
+        let mut angles = [0.; 2];
+        al::alGetSourcefv(self.al_source, ffi::AL_STEREO_ANGLES, &mut angles[0]);
+        angles
+    }
+
+    /**
+     * Set the radius of the Music.
+     *
+     * A Music with a radius is a sphere instead of a point: as the listener
+     * gets inside the sphere the sound spreads around them instead of coming
+     * from a single direction. Applies only when the AL_EXT_SOURCE_RADIUS
+     * extension exists.
+     *
+     * The default radius is 0.0.
+     *
+     * # Argument
+     * * `radius` - The new radius, in the range [0.0, +inf]
+     */
+    fn set_radius(&mut self, radius: f32) {
+        check_openal_context!(());
+
+        if OpenAlData::source_radius_capable() {
+            al::alSourcef(self.al_source, ffi::AL_SOURCE_RADIUS, radius);
+        }
+    }
+
+    /**
+     * Get the radius of the Music.
+     *
+     * Will always return 0.0 if the AL_EXT_SOURCE_RADIUS extension is not
+     * present.
+     *
+     * # Return
+     * The current radius of the Music
+     */
+    fn get_radius(&self) -> f32 {
+        check_openal_context!(0.);
+
+        if !OpenAlData::source_radius_capable() {
+            return 0.;
+        }
+
+        let mut radius = 0.;
+        al::alGetSourcef(self.al_source, ffi::AL_SOURCE_RADIUS, &mut radius);
+        radius
+    }
+
     /**
      * Returns the duration of the Music.
      */
@@ -981,11 +1222,46 @@ impl AudioController for Music {
 
         Duration::new(seconds, nanoseconds as u32)
     }
+
+    #[doc(hidden)]
+    fn al_source(&self) -> u32 {
+        self.al_source
+    }
+
+    #[doc(hidden)]
+    fn playback_clock(&self) -> PlaybackClock {
+        PlaybackClock::Stream {
+            al_source: self.al_source,
+            cursor: self.cursor.clone(),
+            buffer_size: self.sample_to_read,
+            file_infos: self.file_infos.clone(),
+        }
+    }
+
+    #[doc(hidden)]
+    fn cue(&mut self) {
+        check_openal_context!(());
+
+        // A paused Music resumes where it was
+        if self.get_state() == Paused {
+            return;
+        }
+
+        // Wait for the previous streaming thread to release the buffers
+        al::alSourceStop(self.al_source);
+        if let Some(handle) = self.thread_handle.take() {
+            handle.join();
+        }
+
+        self.file.as_mut().unwrap().seek(0, SeekSet);
+        self.process_music();
+    }
 }
 
 impl Drop for Music {
     /// Destroy all the resources of the Music.
     fn drop(&mut self) -> () {
+        ticker::forget_source(self.al_source);
         self.stop();
         if let Some(handle) = self.thread_handle.take() {
             handle.join();
@@ -1002,9 +1278,11 @@ impl Drop for Music {
 mod test {
     #![allow(non_snake_case)]
 
-    use audio_controller::AudioController;
-    use music::Music;
-    use states::State::{Paused, Playing, Stopped};
+    use crate::audio_controller::AudioController;
+    use crate::cone::Cone;
+    use crate::music::Music;
+    use crate::spatialize::Spatialize;
+    use crate::states::State::{Paused, Playing, Stopped};
 
     #[test]
     #[ignore]
@@ -1166,6 +1444,62 @@ mod test {
         assert_eq!([res[0], res[1], res[2]], [50f32, 150f32, 250f32]);
     }
 
+    #[test]
+    #[ignore]
+    fn music_set_cone_OK() {
+        let mut msc = Music::new("res/shot.wav").expect("Cannot create Music");
+
+        let cone = Cone {
+            inner_angle: 45.,
+            outer_angle: 90.,
+            outer_gain: 0.5,
+            outer_gainhf: 0.25,
+        };
+        msc.set_cone(cone);
+        assert_eq!(msc.get_cone(), cone);
+    }
+
+    #[test]
+    #[ignore]
+    fn music_set_velocity_OK() {
+        let mut msc = Music::new("res/shot.wav").expect("Cannot create Music");
+
+        msc.set_velocity([50., 150., 250.]);
+        let res = msc.get_velocity();
+        assert_eq!([res[0], res[1], res[2]], [50f32, 150f32, 250f32]);
+    }
+
+    #[test]
+    #[ignore]
+    fn music_set_spatialize_OK() {
+        let mut msc = Music::new("res/shot.wav").expect("Cannot create Music");
+
+        msc.set_spatialize(Spatialize::On);
+        assert_eq!(msc.get_spatialize(), Spatialize::On);
+        msc.set_stereo_angles([1., -1.]);
+        assert_eq!(msc.get_stereo_angles(), [1., -1.]);
+    }
+
+    #[test]
+    #[ignore]
+    fn music_set_radius_OK() {
+        let mut msc = Music::new("res/shot.wav").expect("Cannot create Music");
+
+        msc.set_radius(2.5);
+        assert_eq!(msc.get_radius(), 2.5);
+    }
+
+    #[test]
+    #[ignore]
+    fn music_set_resampler_OK() {
+        let mut msc = Music::new("res/shot.wav").expect("Cannot create Music");
+
+        for resampler in crate::resamplers() {
+            msc.set_resampler(&resampler);
+            assert_eq!(msc.get_resampler(), Some(resampler));
+        }
+    }
+
     #[test]
     #[ignore]
     fn music_set_max_distance() -> () {
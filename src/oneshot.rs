@@ -0,0 +1,307 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Fire-and-forget playback of SoundDatas with a pool of OpenAL sources.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::distance_model;
+use crate::error::SoundError;
+use crate::internal::OpenAlData;
+use crate::openal::{al, ffi};
+use crate::resampler;
+use crate::reverb_effect::ReverbEffect;
+use crate::sends;
+use crate::ticker;
+
+/**
+ * The parameters of a one-shot, see `SoundData::play_oneshot`.
+ */
+#[derive(Clone, Copy)]
+pub struct OneShotParams<'a> {
+    /// The position [x, y, z], see `AudioController::set_position`
+    pub position: [f32; 3],
+    /// The volume, see `AudioController::set_volume`
+    pub volume: f32,
+    /// The pitch, see `AudioController::set_pitch`
+    pub pitch: f32,
+    /// The ReverbEffect to connect the one-shot to, see
+    /// `AudioController::connect`. Dropping the ReverbEffect disconnects
+    /// the one-shot
+    pub reverb: Option<&'a ReverbEffect>,
+}
+
+impl<'a> Default for OneShotParams<'a> {
+    fn default() -> OneShotParams<'a> {
+        OneShotParams {
+            position: [0.; 3],
+            volume: 1.,
+            pitch: 1.,
+            reverb: None,
+        }
+    }
+}
+
+/**
+ * A weak handle to a playing one-shot.
+ *
+ * The handle doesn't keep the one-shot alive: once the one-shot ended or
+ * was stopped its source goes back to the pool, and the methods of the
+ * handle do nothing.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OneShotHandle {
+    al_source: u32,
+    generation: u64,
+}
+
+struct OneShots {
+    /// The sources ready to play a one-shot
+    free: Vec<u32>,
+    /// The number of sources kept in the pool, the others are deleted
+    max_free: usize,
+    /// The generation of the one-shot playing on each source
+    playing: HashMap<u32, u64>,
+    next_generation: u64,
+}
+
+impl OneShots {
+    /// Give the sources of the ended one-shots back to the pool.
+    fn reap(&mut self) {
+        let ended: Vec<u32> = self
+            .playing
+            .keys()
+            .cloned()
+            .filter(|al_source| {
+                let mut state = 0;
+                al::alGetSourcei(*al_source, ffi::AL_SOURCE_STATE, &mut state);
+                state == ffi::AL_STOPPED
+            })
+            .collect();
+        for al_source in ended {
+            self.release(al_source);
+        }
+    }
+
+    fn release(&mut self, al_source: u32) {
+        if self.playing.remove(&al_source).is_some() {
+            al::alSourceStop(al_source);
+            al::alSourcei(al_source, ffi::AL_BUFFER, 0);
+            ticker::forget_source(al_source);
+            self.free.push(al_source);
+            self.shrink();
+        }
+    }
+
+    /// Delete the sources exceeding the maximal size of the pool.
+    fn shrink(&mut self) {
+        while self.free.len() > self.max_free {
+            if let Some(mut al_source) = self.free.pop() {
+                unsafe {
+                    ffi::alDeleteSources(1, &mut al_source);
+                }
+            }
+        }
+    }
+
+    fn is_current(&self, handle: &OneShotHandle) -> bool {
+        self.playing.get(&handle.al_source) == Some(&handle.generation)
+    }
+}
+
+lazy_static! {
+    static ref ONESHOTS: Mutex<OneShots> = Mutex::new(OneShots {
+        free: Vec::new(),
+        max_free: 16,
+        playing: HashMap::new(),
+        next_generation: 0,
+    });
+}
+
+/// Play a buffer on a source of the pool.
+pub(crate) fn play(al_buffer: u32, params: &OneShotParams) -> Result<OneShotHandle, SoundError> {
+    try_openal_context!();
+
+    let mut oneshots = match ONESHOTS.lock() {
+        Ok(oneshots) => oneshots,
+        Err(_) => return Err(SoundError::InvalidOpenALContext),
+    };
+    oneshots.reap();
+
+    let al_source = match oneshots.free.pop() {
+        Some(al_source) => al_source,
+        None => {
+            let mut al_source = 0;
+            al::alGenSources(1, &mut al_source);
+            if let Some(err) = al::openal_has_error() {
+                return Err(SoundError::InternalOpenALError(err));
+            }
+            al_source
+        }
+    };
+
+    distance_model::register(al_source);
+    resampler::register(al_source);
+    al::alSourcei(al_source, ffi::AL_BUFFER, al_buffer as i32);
+    al::alSourcef(al_source, ffi::AL_GAIN, params.volume);
+    al::alSourcef(al_source, ffi::AL_PITCH, params.pitch);
+    al::alSourcefv(al_source, ffi::AL_POSITION, &params.position[0]);
+    if let Some(reverb) = params.reverb {
+        sends::connect(al_source, 0, Some(reverb.slot()));
+    }
+    al::alSourcePlay(al_source);
+
+    let generation = oneshots.next_generation;
+    oneshots.next_generation += 1;
+    oneshots.playing.insert(al_source, generation);
+
+    Ok(OneShotHandle {
+        al_source,
+        generation,
+    })
+}
+
+/// Stop the one-shots playing a buffer about to be deleted.
+pub(crate) fn release_buffer(al_buffer: u32) {
+    if let Ok(mut oneshots) = ONESHOTS.lock() {
+        let using: Vec<u32> = oneshots
+            .playing
+            .keys()
+            .cloned()
+            .filter(|al_source| {
+                let mut buffer = 0;
+                al::alGetSourcei(*al_source, ffi::AL_BUFFER, &mut buffer);
+                buffer as u32 == al_buffer
+            })
+            .collect();
+        for al_source in using {
+            oneshots.release(al_source);
+        }
+    }
+}
+
+/**
+ * Set the maximal number of sources kept by the pool of the one-shots once
+ * their one-shot ended.
+ *
+ * The sources released beyond it are deleted, leaving them to the Sounds
+ * and Musics. Default is 16.
+ *
+ * # Argument
+ * * `max` - The new maximal number of idle sources
+ */
+pub fn set_max_oneshot_sources(max: usize) {
+    check_openal_context!(());
+
+    if let Ok(mut oneshots) = ONESHOTS.lock() {
+        oneshots.max_free = max;
+        oneshots.shrink();
+    }
+}
+
+/**
+ * Get the maximal number of sources kept by the pool of the one-shots once
+ * their one-shot ended.
+ *
+ * # Return
+ * The maximal number of idle sources
+ */
+pub fn max_oneshot_sources() -> usize {
+    match ONESHOTS.lock() {
+        Ok(oneshots) => oneshots.max_free,
+        Err(_) => 0,
+    }
+}
+
+/// Give the sources of the ended one-shots back to the pool.
+pub(crate) fn update() {
+    if let Ok(mut oneshots) = ONESHOTS.lock() {
+        oneshots.reap();
+    }
+}
+
+impl OneShotHandle {
+    fn with_source<F: FnOnce(u32)>(&self, f: F) {
+        check_openal_context!(());
+
+        if let Ok(oneshots) = ONESHOTS.lock() {
+            if oneshots.is_current(self) {
+                f(self.al_source);
+            }
+        }
+    }
+
+    /// Check if the one-shot is still playing.
+    pub fn is_playing(&self) -> bool {
+        check_openal_context!(false);
+
+        match ONESHOTS.lock() {
+            Ok(oneshots) => {
+                let mut state = 0;
+                al::alGetSourcei(self.al_source, ffi::AL_SOURCE_STATE, &mut state);
+                oneshots.is_current(self) && state == ffi::AL_PLAYING
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Stop the one-shot and give its source back to the pool.
+    pub fn stop(&self) {
+        check_openal_context!(());
+
+        if let Ok(mut oneshots) = ONESHOTS.lock() {
+            if oneshots.is_current(self) {
+                oneshots.release(self.al_source);
+            }
+        }
+    }
+
+    /**
+     * Move the one-shot.
+     *
+     * # Argument
+     * * `position` - The new position [x, y, z]
+     */
+    pub fn set_position(&self, position: [f32; 3]) {
+        self.with_source(|al_source| al::alSourcefv(al_source, ffi::AL_POSITION, &position[0]));
+    }
+
+    /**
+     * Set the volume of the one-shot.
+     *
+     * # Argument
+     * * `volume` - The new volume
+     */
+    pub fn set_volume(&self, volume: f32) {
+        self.with_source(|al_source| al::alSourcef(al_source, ffi::AL_GAIN, volume));
+    }
+
+    /**
+     * Set the pitch of the one-shot.
+     *
+     * # Argument
+     * * `pitch` - The new pitch
+     */
+    pub fn set_pitch(&self, pitch: f32) {
+        self.with_source(|al_source| al::alSourcef(al_source, ffi::AL_PITCH, pitch));
+    }
+}
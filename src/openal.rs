@@ -60,6 +60,24 @@ pub mod ffi {
     pub const AL_SEC_OFFSET: i32 = 0x1024;
     pub const AL_SAMPLE_OFFSET: i32 = 0x1025;
     pub const AL_BYTE_OFFSET: i32 = 0x1026;
+    pub const AL_CONE_INNER_ANGLE: i32 = 0x1001;
+    pub const AL_CONE_OUTER_ANGLE: i32 = 0x1002;
+    pub const AL_CONE_OUTER_GAIN: i32 = 0x1022;
+
+    /// Distance models
+    pub const AL_NONE: i32 = 0;
+    pub const AL_DISTANCE_MODEL: i32 = 0xD000;
+    pub const AL_INVERSE_DISTANCE: i32 = 0xD001;
+    pub const AL_INVERSE_DISTANCE_CLAMPED: i32 = 0xD002;
+    pub const AL_LINEAR_DISTANCE: i32 = 0xD003;
+    pub const AL_LINEAR_DISTANCE_CLAMPED: i32 = 0xD004;
+    pub const AL_EXPONENT_DISTANCE: i32 = 0xD005;
+    pub const AL_EXPONENT_DISTANCE_CLAMPED: i32 = 0xD006;
+    pub const AL_SOURCE_DISTANCE_MODEL: i32 = 0x200;
+
+    /// Doppler
+    pub const AL_DOPPLER_FACTOR: i32 = 0xC000;
+    pub const AL_SPEED_OF_SOUND: i32 = 0xC003;
 
     /// Sound format
     pub const AL_FORMAT_MONO16: i32 = 0x1101;
@@ -74,11 +92,16 @@ pub mod ffi {
     pub const AL_BUFFERS_PROCESSED: i32 = 0x1016;
     pub const AL_BUFFERS_QUEUED: i32 = 0x1015;
     pub const AL_DIRECT_CHANNELS_SOFT: i32 = 0x1033;
+    pub const AL_SOURCE_SPATIALIZE_SOFT: i32 = 0x1214;
+    pub const AL_AUTO_SOFT: i32 = 0x0002;
+    pub const AL_STEREO_ANGLES: i32 = 0x1030;
+    pub const AL_SOURCE_RADIUS: i32 = 0x1031;
 
     /// Source object extensions
     pub const AL_DIRECT_FILTER: i32 = 0x20005;
     pub const AL_AUXILIARY_SEND_FILTER: i32 = 0x20006;
     pub const AL_AIR_ABSORPTION_FACTOR: i32 = 0x20007;
+    pub const AL_CONE_OUTER_GAINHF: i32 = 0x20009;
 
     /// Effects
     pub const AL_EFFECT_NULL: i32 = 0x0000;
@@ -86,6 +109,7 @@ pub mod ffi {
     pub const AL_EFFECT_REVERB: i32 = 0x0001;
     pub const AL_EFFECTSLOT_NULL: i32 = 0x0000;
     pub const AL_EFFECTSLOT_EFFECT: i32 = 0x0001;
+    pub const AL_EFFECTSLOT_GAIN: i32 = 0x0002;
     pub const AL_EFFECTSLOT_AUXILIARY_SEND_AUTO: i32 = 0x0003;
     pub const AL_REVERB_DENSITY: i32 = 0x0001;
     pub const AL_REVERB_DIFFUSION: i32 = 0x0002;
@@ -100,9 +124,44 @@ pub mod ffi {
     pub const AL_REVERB_AIR_ABSORPTION_GAINHF: i32 = 0x000B;
     pub const AL_REVERB_ROOM_ROLLOFF_FACTOR: i32 = 0x000C;
     pub const AL_REVERB_DECAY_HFLIMIT: i32 = 0x000D;
+    pub const AL_EFFECT_EAXREVERB: i32 = 0x8000;
+    pub const AL_EAXREVERB_DENSITY: i32 = 0x0001;
+    pub const AL_EAXREVERB_DIFFUSION: i32 = 0x0002;
+    pub const AL_EAXREVERB_GAIN: i32 = 0x0003;
+    pub const AL_EAXREVERB_GAINHF: i32 = 0x0004;
+    pub const AL_EAXREVERB_GAINLF: i32 = 0x0005;
+    pub const AL_EAXREVERB_DECAY_TIME: i32 = 0x0006;
+    pub const AL_EAXREVERB_DECAY_HFRATIO: i32 = 0x0007;
+    pub const AL_EAXREVERB_DECAY_LFRATIO: i32 = 0x0008;
+    pub const AL_EAXREVERB_REFLECTIONS_GAIN: i32 = 0x0009;
+    pub const AL_EAXREVERB_REFLECTIONS_DELAY: i32 = 0x000A;
+    pub const AL_EAXREVERB_REFLECTIONS_PAN: i32 = 0x000B;
+    pub const AL_EAXREVERB_LATE_REVERB_GAIN: i32 = 0x000C;
+    pub const AL_EAXREVERB_LATE_REVERB_DELAY: i32 = 0x000D;
+    pub const AL_EAXREVERB_LATE_REVERB_PAN: i32 = 0x000E;
+    pub const AL_EAXREVERB_ECHO_TIME: i32 = 0x000F;
+    pub const AL_EAXREVERB_ECHO_DEPTH: i32 = 0x0010;
+    pub const AL_EAXREVERB_MODULATION_TIME: i32 = 0x0011;
+    pub const AL_EAXREVERB_MODULATION_DEPTH: i32 = 0x0012;
+    pub const AL_EAXREVERB_AIR_ABSORPTION_GAINHF: i32 = 0x0013;
+    pub const AL_EAXREVERB_HFREFERENCE: i32 = 0x0014;
+    pub const AL_EAXREVERB_LFREFERENCE: i32 = 0x0015;
+    pub const AL_EAXREVERB_ROOM_ROLLOFF_FACTOR: i32 = 0x0016;
+    pub const AL_EAXREVERB_DECAY_HFLIMIT: i32 = 0x0017;
 
     // Filters
     pub const AL_FILTER_NULL: i32 = 0x0000;
+    pub const AL_FILTER_TYPE: i32 = 0x8001;
+    pub const AL_FILTER_LOWPASS: i32 = 0x0001;
+    pub const AL_LOWPASS_GAIN: i32 = 0x0001;
+    pub const AL_LOWPASS_GAINHF: i32 = 0x0002;
+    pub const AL_FILTER_HIGHPASS: i32 = 0x0002;
+    pub const AL_HIGHPASS_GAIN: i32 = 0x0001;
+    pub const AL_HIGHPASS_GAINLF: i32 = 0x0002;
+    pub const AL_FILTER_BANDPASS: i32 = 0x0003;
+    pub const AL_BANDPASS_GAIN: i32 = 0x0001;
+    pub const AL_BANDPASS_GAINLF: i32 = 0x0002;
+    pub const AL_BANDPASS_GAINHF: i32 = 0x0003;
 
     /// Error identifiers
     pub const AL_NO_ERROR: i32 = 0;
@@ -122,12 +181,45 @@ pub mod ffi {
     /// ALC
     pub const ALC_CAPTURE_SAMPLES: i32 = 0x312;
 
+    /// Context attributes
+    pub const ALC_MONO_SOURCES: i32 = 0x1010;
+    pub const ALC_STEREO_SOURCES: i32 = 0x1011;
+
+    /// ALC_EXT_EFX
+    pub const ALC_MAX_AUXILIARY_SENDS: i32 = 0x20003;
+
+    /// AL_SOFT_source_latency
+    pub const AL_SEC_OFFSET_LATENCY_SOFT: i32 = 0x1201;
+
+    /// AL_SOFT_source_resampler
+    pub const AL_NUM_RESAMPLERS_SOFT: i32 = 0x1210;
+    pub const AL_DEFAULT_RESAMPLER_SOFT: i32 = 0x1211;
+    pub const AL_SOURCE_RESAMPLER_SOFT: i32 = 0x1212;
+    pub const AL_RESAMPLER_NAME_SOFT: i32 = 0x1213;
+
+    /// ALC_SOFT_device_clock
+    pub const ALC_DEVICE_CLOCK_SOFT: i32 = 0x1600;
+    pub const ALC_DEVICE_LATENCY_SOFT: i32 = 0x1601;
+
+    /// Extension functions, loaded at runtime, named like in the OpenAL
+    /// headers
+    #[allow(clippy::upper_case_acronyms)]
+    pub type LPALSOURCEPLAYATTIMEVSOFT =
+        unsafe extern "C" fn(n: i32, sources: *const u32, start_time: i64);
+    #[allow(clippy::upper_case_acronyms)]
+    pub type LPALGETSOURCEDVSOFT = unsafe extern "C" fn(source: u32, param: i32, values: *mut f64);
+    #[allow(clippy::upper_case_acronyms)]
+    pub type LPALGETSTRINGISOFT = unsafe extern "C" fn(param: i32, index: i32) -> *const c_char;
+    pub type LPALCGETINTEGER64VSOFT =
+        unsafe extern "C" fn(device: ALCdevicePtr, param: i32, size: i32, values: *mut i64);
+
     extern "C" {
         /// Context functions
         pub fn alcCreateContext(device: ALCdevicePtr, attrlist: *mut i32) -> ALCcontextPtr;
         pub fn alcMakeContextCurrent(context: ALCcontextPtr) -> ALCboolean;
         pub fn alcDestroyContext(context: ALCcontextPtr);
         pub fn alcGetCurrentContext() -> ALCcontextPtr;
+        pub fn alcGetContextsDevice(context: ALCcontextPtr) -> ALCdevicePtr;
 
         /// Device functions
         pub fn alcOpenDevice(devicename: *mut c_char) -> ALCdevicePtr;
@@ -146,6 +238,14 @@ pub mod ffi {
         pub fn alListenerfv(param: i32, values: *const f32) -> ();
         pub fn alGetListenerfv(param: i32, values: *mut f32) -> ();
 
+        /// State functions
+        pub fn alDistanceModel(value: i32) -> ();
+        pub fn alGetInteger(param: i32) -> i32;
+        pub fn alGetFloat(param: i32) -> f32;
+        pub fn alDopplerFactor(value: f32) -> ();
+        pub fn alSpeedOfSound(value: f32) -> ();
+        pub fn alEnable(capability: i32) -> ();
+
         /// Sources functions
         pub fn alGenSources(n: i32, sources: *mut u32) -> ();
         pub fn alDeleteSources(n: i32, buffers: *mut u32) -> ();
@@ -153,6 +253,8 @@ pub mod ffi {
         pub fn alSource3i(source: u32, param: i32, value1: i32, value2: i32, value3: i32);
         pub fn alSourcef(source: u32, param: i32, value: f32) -> ();
         pub fn alSourcePlay(source: u32) -> ();
+        pub fn alSourcePlayv(n: i32, sources: *const u32) -> ();
+        pub fn alSourceRewind(source: u32) -> ();
         pub fn alSourcePause(source: u32) -> ();
         pub fn alSourceStop(source: u32) -> ();
         pub fn alGetSourcei(source: u32, param: i32, value: *mut i32) -> ();
@@ -182,12 +284,22 @@ pub mod ffi {
         pub fn alDeleteEffects(n: i32, effects: *mut u32) -> ();
         pub fn alIsAuxiliaryEffectSlot(source: u32) -> ALboolean;
         pub fn alAuxiliaryEffectSloti(source: u32, param: i32, value: u32) -> ();
+        pub fn alAuxiliaryEffectSlotf(source: u32, param: i32, value: f32) -> ();
         pub fn alEffecti(source: u32, param: i32, value: i32);
         pub fn alEffectf(source: u32, param: i32, value: f32);
+        pub fn alEffectfv(source: u32, param: i32, values: *const f32);
+
+        /// Filters functions
+        pub fn alGenFilters(n: i32, filters: *mut u32) -> ();
+        pub fn alDeleteFilters(n: i32, filters: *mut u32) -> ();
+        pub fn alFilteri(filter: u32, param: i32, value: i32);
+        pub fn alFilterf(filter: u32, param: i32, value: f32);
 
         /// extension check
         pub fn alIsExtensionPresent(extension: *const c_char) -> ALboolean;
         pub fn alcIsExtensionPresent(device: ALCdevicePtr, extension: *const c_char) -> ALCboolean;
+        pub fn alGetProcAddress(fname: *const c_char) -> *mut c_void;
+        pub fn alcGetProcAddress(device: ALCdevicePtr, fname: *const c_char) -> *mut c_void;
 
         /// Buffers functions
         pub fn alGenBuffers(n: i32, buffers: *mut u32) -> ();
@@ -215,7 +327,9 @@ pub mod al {
     use super::ffi;
     use libc::c_void;
     use std::error::Error;
+    use std::ffi::{CStr, CString};
     use std::fmt;
+    use std::mem;
 
     pub fn alBufferData(buffer: u32, format: i32, data: *mut c_void, size: i32, freq: i32) -> () {
         unsafe {
@@ -235,6 +349,18 @@ pub mod al {
         }
     }
 
+    pub fn alSourcePlayv(n: i32, sources: *const u32) {
+        unsafe {
+            ffi::alSourcePlayv(n, sources);
+        }
+    }
+
+    pub fn alSourceRewind(source: u32) {
+        unsafe {
+            ffi::alSourceRewind(source);
+        }
+    }
+
     pub fn alGetSourcei(source: u32, param: i32, value: *mut i32) -> () {
         unsafe {
             ffi::alGetSourcei(source, param, value);
@@ -291,6 +417,38 @@ pub mod al {
         }
     }
 
+    pub fn alDistanceModel(value: i32) {
+        unsafe {
+            ffi::alDistanceModel(value);
+        }
+    }
+
+    pub fn alGetInteger(param: i32) -> i32 {
+        unsafe { ffi::alGetInteger(param) }
+    }
+
+    pub fn alGetFloat(param: i32) -> f32 {
+        unsafe { ffi::alGetFloat(param) }
+    }
+
+    pub fn alDopplerFactor(value: f32) {
+        unsafe {
+            ffi::alDopplerFactor(value);
+        }
+    }
+
+    pub fn alSpeedOfSound(value: f32) {
+        unsafe {
+            ffi::alSpeedOfSound(value);
+        }
+    }
+
+    pub fn alEnable(capability: i32) {
+        unsafe {
+            ffi::alEnable(capability);
+        }
+    }
+
     pub fn alGenSources(n: i32, sources: *mut u32) -> () {
         unsafe {
             ffi::alGenSources(n, sources);
@@ -370,6 +528,12 @@ pub mod al {
         }
     }
 
+    pub fn alAuxiliaryEffectSlotf(source: u32, param: i32, value: f32) {
+        unsafe {
+            ffi::alAuxiliaryEffectSlotf(source, param, value);
+        }
+    }
+
     pub fn alEffecti(source: u32, param: i32, value: i32) {
         unsafe {
             ffi::alEffecti(source, param, value);
@@ -382,6 +546,162 @@ pub mod al {
         }
     }
 
+    pub fn alEffectfv(source: u32, param: i32, values: &[f32]) {
+        unsafe {
+            ffi::alEffectfv(source, param, values.as_ptr());
+        }
+    }
+
+    /// Filters functions
+    pub fn alGenFilters(n: i32, filters: *mut u32) {
+        unsafe {
+            ffi::alGenFilters(n, filters);
+        }
+    }
+
+    pub fn alFilteri(filter: u32, param: i32, value: i32) {
+        unsafe {
+            ffi::alFilteri(filter, param, value);
+        }
+    }
+
+    pub fn alFilterf(filter: u32, param: i32, value: f32) {
+        unsafe {
+            ffi::alFilterf(filter, param, value);
+        }
+    }
+
+    // Extension functions
+    //
+    // They are looked up the first time they are used, which must happen
+    // once the OpenAL context is created.
+    lazy_static! {
+        static ref SOURCE_PLAY_AT_TIMEV: Option<ffi::LPALSOURCEPLAYATTIMEVSOFT> = unsafe {
+            let extension = CString::new("AL_SOFT_source_start_delay").unwrap();
+            let name = CString::new("alSourcePlayAtTimevSOFT").unwrap();
+            let function = ffi::alGetProcAddress(name.as_ptr());
+            if ffi::alIsExtensionPresent(extension.as_ptr()) == ffi::AL_TRUE && !function.is_null()
+            {
+                Some(mem::transmute::<*mut c_void, ffi::LPALSOURCEPLAYATTIMEVSOFT>(function))
+            } else {
+                None
+            }
+        };
+        static ref GET_SOURCEDV: Option<ffi::LPALGETSOURCEDVSOFT> = unsafe {
+            let extension = CString::new("AL_SOFT_source_latency").unwrap();
+            let name = CString::new("alGetSourcedvSOFT").unwrap();
+            let function = ffi::alGetProcAddress(name.as_ptr());
+            if ffi::alIsExtensionPresent(extension.as_ptr()) == ffi::AL_TRUE && !function.is_null()
+            {
+                Some(mem::transmute::<*mut c_void, ffi::LPALGETSOURCEDVSOFT>(
+                    function,
+                ))
+            } else {
+                None
+            }
+        };
+        static ref GET_STRINGI: Option<ffi::LPALGETSTRINGISOFT> = unsafe {
+            let extension = CString::new("AL_SOFT_source_resampler").unwrap();
+            let name = CString::new("alGetStringiSOFT").unwrap();
+            let function = ffi::alGetProcAddress(name.as_ptr());
+            if ffi::alIsExtensionPresent(extension.as_ptr()) == ffi::AL_TRUE && !function.is_null()
+            {
+                Some(mem::transmute::<*mut c_void, ffi::LPALGETSTRINGISOFT>(
+                    function,
+                ))
+            } else {
+                None
+            }
+        };
+        static ref GET_INTEGER64V: Option<ffi::LPALCGETINTEGER64VSOFT> = unsafe {
+            let device = ffi::alcGetContextsDevice(ffi::alcGetCurrentContext());
+            let extension = CString::new("ALC_SOFT_device_clock").unwrap();
+            let name = CString::new("alcGetInteger64vSOFT").unwrap();
+            let function = ffi::alcGetProcAddress(device, name.as_ptr());
+            if ffi::alcIsExtensionPresent(device, extension.as_ptr()) == ffi::ALC_TRUE
+                && !function.is_null()
+            {
+                Some(mem::transmute::<*mut c_void, ffi::LPALCGETINTEGER64VSOFT>(
+                    function,
+                ))
+            } else {
+                None
+            }
+        };
+    }
+
+    /// Start sources at a time of the device clock, in nanoseconds.
+    ///
+    /// Return false without doing anything if AL_SOFT_source_start_delay
+    /// isn't available.
+    pub fn alSourcePlayAtTimevSOFT(n: i32, sources: *const u32, start_time: i64) -> bool {
+        match *SOURCE_PLAY_AT_TIMEV {
+            Some(function) => {
+                unsafe {
+                    function(n, sources, start_time);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get double precision source parameters.
+    ///
+    /// Return false without doing anything if AL_SOFT_source_latency isn't
+    /// available.
+    pub fn alGetSourcedvSOFT(source: u32, param: i32, values: *mut f64) -> bool {
+        match *GET_SOURCEDV {
+            Some(function) => {
+                unsafe {
+                    function(source, param, values);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get an indexed string parameter, like the name of a resampler.
+    ///
+    /// Return None if AL_SOFT_source_resampler isn't available or if the
+    /// index is invalid.
+    pub fn alGetStringiSOFT(param: i32, index: i32) -> Option<String> {
+        GET_STRINGI.and_then(|function| unsafe {
+            let string = function(param, index);
+            if string.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(string).to_string_lossy().into_owned())
+            }
+        })
+    }
+
+    /// Get an integer parameter of the device of the current context.
+    pub fn alcGetInteger(param: i32) -> i32 {
+        let mut value = 0;
+        unsafe {
+            let device = ffi::alcGetContextsDevice(ffi::alcGetCurrentContext());
+            ffi::alcGetIntegerv(device, param, 1, &mut value);
+        }
+        value
+    }
+
+    /// Get a 64 bits parameter of the current device, like its clock in
+    /// nanoseconds.
+    ///
+    /// Return None if ALC_SOFT_device_clock isn't available.
+    pub fn alcGetInteger64vSOFT(param: i32) -> Option<i64> {
+        GET_INTEGER64V.map(|function| {
+            let mut value = 0;
+            unsafe {
+                let device = ffi::alcGetContextsDevice(ffi::alcGetCurrentContext());
+                function(device, param, 1, &mut value);
+            }
+            value
+        })
+    }
+
     /// Any error that can happen during an OpenAL call.
     pub struct AlError(i32);
 
@@ -0,0 +1,480 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Named reverb presets, loaded from and saved to data files with the
+//! `serde` feature.
+
+use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "serde")]
+use std::error::Error;
+#[cfg(feature = "serde")]
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::fs;
+#[cfg(feature = "serde")]
+use std::io;
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::presets::{ReverbPreset, ReverbProperties};
+
+/// The file formats of the reverb presets.
+///
+/// Each format requires its feature of __ears__: `toml`, `json` or `ron`.
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresetFormat {
+    Toml,
+    Json,
+    Ron,
+}
+
+#[cfg(feature = "serde")]
+impl PresetFormat {
+    /**
+     * Guess the format of a file from its extension.
+     *
+     * # Argument
+     * * `path` - The path of the file
+     *
+     * # Return
+     * The format, or None if the extension isn't `toml`, `json` nor `ron`
+     */
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<PresetFormat> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "toml" => Some(PresetFormat::Toml),
+            "json" => Some(PresetFormat::Json),
+            "ron" => Some(PresetFormat::Ron),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+/// Errors that might occur when loading or saving reverb presets.
+pub enum PresetError {
+    /// The file couldn't be read or written.
+    IoError(io::Error),
+    /// The format of the file can't be guessed from its extension.
+    UnknownFormat(String),
+    /// The feature of the format isn't enabled.
+    UnsupportedFormat(PresetFormat),
+    /// The presets couldn't be parsed, with the message of the parser.
+    ParseError(String),
+    /// The presets couldn't be written, with the message of the serializer.
+    SerializeError(String),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for PresetError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "{}",
+            match self {
+                PresetError::IoError(err) => format!("error while accessing presets: {}", err),
+                PresetError::UnknownFormat(path) => format!("unknown preset format: {}", path),
+                PresetError::UnsupportedFormat(format) =>
+                    format!("preset format not enabled: {:?}", format),
+                PresetError::ParseError(err) => format!("invalid presets: {}", err),
+                PresetError::SerializeError(err) => format!("error while writing presets: {}", err),
+            }
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Debug for PresetError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, fmt)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for PresetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PresetError::IoError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<io::Error> for PresetError {
+    fn from(err: io::Error) -> PresetError {
+        PresetError::IoError(err)
+    }
+}
+
+lazy_static! {
+    static ref PRESETS: Mutex<HashMap<String, ReverbProperties>> = Mutex::new(HashMap::new());
+}
+
+/**
+ * Parse named reverb presets.
+ *
+ * The presets are a map from their names to their ReverbProperties. The
+ * missing fields of a preset take the values of `ReverbPreset::Generic`.
+ *
+ * # Arguments
+ * * `text` - The content of a presets file
+ * * `format` - The format of the presets
+ *
+ * # Return
+ * The presets by name, or an error if they can't be parsed
+ *
+ * # Example
+ * ```no_run
+ * use ears::PresetFormat;
+ *
+ * let presets = ears::parse_reverb_presets(
+ *     "[Crypt]\ndecay_time = 4.2\ngainhf = 0.3\n",
+ *     PresetFormat::Toml,
+ * ).unwrap();
+ * ```
+ */
+#[cfg(feature = "serde")]
+#[cfg_attr(
+    not(all(feature = "toml", feature = "json", feature = "ron")),
+    allow(unused_variables)
+)]
+pub fn parse_reverb_presets(
+    text: &str,
+    format: PresetFormat,
+) -> Result<BTreeMap<String, ReverbProperties>, PresetError> {
+    match format {
+        #[cfg(feature = "toml")]
+        PresetFormat::Toml => {
+            toml::from_str(text).map_err(|err| PresetError::ParseError(err.to_string()))
+        }
+        #[cfg(feature = "json")]
+        PresetFormat::Json => {
+            serde_json::from_str(text).map_err(|err| PresetError::ParseError(err.to_string()))
+        }
+        #[cfg(feature = "ron")]
+        PresetFormat::Ron => {
+            ron::from_str(text).map_err(|err| PresetError::ParseError(err.to_string()))
+        }
+        #[allow(unreachable_patterns)]
+        format => Err(PresetError::UnsupportedFormat(format)),
+    }
+}
+
+/**
+ * Write named reverb presets.
+ *
+ * # Arguments
+ * * `presets` - The presets by name
+ * * `format` - The format to write
+ *
+ * # Return
+ * The content of a presets file, or an error if the presets can't be
+ * written
+ */
+#[cfg(feature = "serde")]
+#[cfg_attr(
+    not(all(feature = "toml", feature = "json", feature = "ron")),
+    allow(unused_variables)
+)]
+pub fn write_reverb_presets(
+    presets: &BTreeMap<String, ReverbProperties>,
+    format: PresetFormat,
+) -> Result<String, PresetError> {
+    match format {
+        #[cfg(feature = "toml")]
+        PresetFormat::Toml => toml::to_string_pretty(presets)
+            .map_err(|err| PresetError::SerializeError(err.to_string())),
+        #[cfg(feature = "json")]
+        PresetFormat::Json => serde_json::to_string_pretty(presets)
+            .map_err(|err| PresetError::SerializeError(err.to_string())),
+        #[cfg(feature = "ron")]
+        PresetFormat::Ron => ron::ser::to_string_pretty(presets, ron::ser::PrettyConfig::default())
+            .map_err(|err| PresetError::SerializeError(err.to_string())),
+        #[allow(unreachable_patterns)]
+        format => Err(PresetError::UnsupportedFormat(format)),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn format_of(path: &Path) -> Result<PresetFormat, PresetError> {
+    PresetFormat::from_path(path)
+        .ok_or_else(|| PresetError::UnknownFormat(path.display().to_string()))
+}
+
+/**
+ * Load named reverb presets from a file and register them.
+ *
+ * The format is guessed from the extension of the file: `.toml`, `.json`
+ * or `.ron`. The presets replace the registered ones with the same name.
+ *
+ * # Argument
+ * * `path` - The path of the presets file
+ *
+ * # Return
+ * The names of the loaded presets, or an error if the file can't be read
+ * or parsed
+ *
+ * # Example
+ * ```no_run
+ * use ears::ReverbEffect;
+ *
+ * fn main() -> Result<(), Box<dyn std::error::Error>> {
+ *     ears::load_reverb_presets("path/to/the/rooms.toml")?;
+ *
+ *     let crypt = ears::reverb_preset("Crypt").unwrap();
+ *     let effect = ReverbEffect::preset(crypt)?;
+ *     Ok(())
+ * }
+ * ```
+ */
+#[cfg(feature = "serde")]
+pub fn load_reverb_presets<P: AsRef<Path>>(path: P) -> Result<Vec<String>, PresetError> {
+    let path = path.as_ref();
+    let format = format_of(path)?;
+    let presets = parse_reverb_presets(&fs::read_to_string(path)?, format)?;
+
+    let names = presets.keys().cloned().collect();
+    if let Ok(mut registered) = PRESETS.lock() {
+        registered.extend(presets);
+    }
+    Ok(names)
+}
+
+/**
+ * Save named reverb presets to a file.
+ *
+ * The format is guessed from the extension of the file: `.toml`, `.json`
+ * or `.ron`.
+ *
+ * # Arguments
+ * * `path` - The path of the presets file
+ * * `presets` - The presets by name, for example
+ *   `ears::builtin_reverb_presets()` to get started
+ *
+ * # Return
+ * An error if the file can't be written
+ *
+ * # Example
+ * ```no_run
+ * fn main() -> Result<(), ears::PresetError> {
+ *     ears::save_reverb_presets("path/to/the/builtin.json", &ears::builtin_reverb_presets())
+ * }
+ * ```
+ */
+#[cfg(feature = "serde")]
+pub fn save_reverb_presets<P: AsRef<Path>>(
+    path: P,
+    presets: &BTreeMap<String, ReverbProperties>,
+) -> Result<(), PresetError> {
+    let path = path.as_ref();
+    let text = write_reverb_presets(presets, format_of(path)?)?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+/// Get the built-in presets of `ReverbPreset` by name.
+pub fn builtin_reverb_presets() -> BTreeMap<String, ReverbProperties> {
+    ReverbPreset::all()
+        .iter()
+        .map(|preset| (preset.name(), preset.properties()))
+        .collect()
+}
+
+/**
+ * Register a named reverb preset.
+ *
+ * # Arguments
+ * * `name` - The name of the preset, replacing the registered preset with
+ *   the same name
+ * * `properties` - The properties of the preset
+ */
+pub fn register_reverb_preset(name: &str, properties: ReverbProperties) {
+    if let Ok(mut presets) = PRESETS.lock() {
+        presets.insert(name.to_string(), properties);
+    }
+}
+
+/**
+ * Remove a registered reverb preset.
+ *
+ * # Argument
+ * * `name` - The name of the preset
+ *
+ * # Return
+ * The properties of the removed preset, or None if no preset has this name
+ */
+pub fn unregister_reverb_preset(name: &str) -> Option<ReverbProperties> {
+    match PRESETS.lock() {
+        Ok(mut presets) => presets.remove(name),
+        Err(_) => None,
+    }
+}
+
+/**
+ * Get a reverb preset by name.
+ *
+ * The registered presets are looked up first, then the built-in ones, so
+ * a registered preset can replace a built-in one.
+ *
+ * # Argument
+ * * `name` - The name of the preset, like `"Crypt"` or `"Cave"`
+ *
+ * # Return
+ * The properties of the preset, or None if no preset has this name
+ */
+pub fn reverb_preset(name: &str) -> Option<ReverbProperties> {
+    let registered = match PRESETS.lock() {
+        Ok(presets) => presets.get(name).cloned(),
+        Err(_) => None,
+    };
+    registered.or_else(|| {
+        ReverbPreset::all()
+            .iter()
+            .find(|preset| preset.name() == name)
+            .map(ReverbPreset::properties)
+    })
+}
+
+/// Get the names of the registered reverb presets, sorted.
+pub fn reverb_preset_names() -> Vec<String> {
+    let mut names: Vec<String> = match PRESETS.lock() {
+        Ok(presets) => presets.keys().cloned().collect(),
+        Err(_) => Vec::new(),
+    };
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::preset_registry::{
+        builtin_reverb_presets, register_reverb_preset, reverb_preset, unregister_reverb_preset,
+    };
+    use crate::presets::ReverbPreset;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn preset_registry_format_OK() {
+        use crate::preset_registry::PresetFormat;
+
+        assert_eq!(
+            PresetFormat::from_path("rooms.TOML"),
+            Some(PresetFormat::Toml)
+        );
+        assert_eq!(
+            PresetFormat::from_path("dir/rooms.ron"),
+            Some(PresetFormat::Ron)
+        );
+        assert_eq!(PresetFormat::from_path("rooms.yaml"), None);
+        assert_eq!(PresetFormat::from_path("rooms"), None);
+    }
+
+    #[test]
+    fn preset_registry_builtin_OK() {
+        let presets = builtin_reverb_presets();
+        assert_eq!(presets.len(), ReverbPreset::all().len());
+        assert_eq!(presets["Cave"], ReverbPreset::Cave.properties());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn preset_registry_toml_OK() {
+        use crate::preset_registry::{parse_reverb_presets, write_reverb_presets, PresetFormat};
+
+        let presets = parse_reverb_presets(
+            "[Crypt]\ndecay_time = 4.0\ngainhf = 0.25\n",
+            PresetFormat::Toml,
+        )
+        .unwrap();
+        let crypt = presets["Crypt"];
+        assert_eq!(crypt.decay_time, 4.);
+        assert_eq!(crypt.gainhf, 0.25);
+        assert_eq!(crypt.density, ReverbPreset::Generic.properties().density);
+
+        let text = write_reverb_presets(&presets, PresetFormat::Toml).unwrap();
+        assert_eq!(
+            parse_reverb_presets(&text, PresetFormat::Toml).unwrap(),
+            presets
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn preset_registry_json_OK() {
+        use crate::preset_registry::{parse_reverb_presets, write_reverb_presets, PresetFormat};
+
+        let presets = parse_reverb_presets(
+            r#"{ "Crypt": { "decay_time": 4.0, "reflections_pan": [0.0, 0.5, 0.0] } }"#,
+            PresetFormat::Json,
+        )
+        .unwrap();
+        let crypt = presets["Crypt"];
+        assert_eq!(crypt.decay_time, 4.);
+        assert_eq!(crypt.reflections_pan, [0., 0.5, 0.]);
+        assert_eq!(crypt.density, ReverbPreset::Generic.properties().density);
+
+        let text = write_reverb_presets(&presets, PresetFormat::Json).unwrap();
+        assert_eq!(
+            parse_reverb_presets(&text, PresetFormat::Json).unwrap(),
+            presets
+        );
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn preset_registry_ron_OK() {
+        use crate::preset_registry::{parse_reverb_presets, write_reverb_presets, PresetFormat};
+
+        let presets = parse_reverb_presets(
+            "{ \"Crypt\": (decay_time: 4.0, decay_hflimit: 0) }",
+            PresetFormat::Ron,
+        )
+        .unwrap();
+        let crypt = presets["Crypt"];
+        assert_eq!(crypt.decay_time, 4.);
+        assert_eq!(crypt.decay_hflimit, 0);
+        assert_eq!(crypt.density, ReverbPreset::Generic.properties().density);
+
+        let text = write_reverb_presets(&presets, PresetFormat::Ron).unwrap();
+        assert_eq!(
+            parse_reverb_presets(&text, PresetFormat::Ron).unwrap(),
+            presets
+        );
+    }
+
+    #[test]
+    fn preset_registry_register_OK() {
+        let mut crypt = ReverbPreset::Cave.properties();
+        crypt.decay_time = 4.;
+
+        register_reverb_preset("Cave", crypt);
+        assert_eq!(reverb_preset("Cave"), Some(crypt));
+
+        assert_eq!(unregister_reverb_preset("Cave"), Some(crypt));
+        assert_eq!(reverb_preset("Cave"), Some(ReverbPreset::Cave.properties()));
+        assert_eq!(reverb_preset("Crypt"), None);
+    }
+}
@@ -1,4 +1,12 @@
-#[derive(Debug)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::automation::Interpolate;
+use crate::fade::lerp;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ReverbProperties {
     pub density: f32,
     pub diffusion: f32,
@@ -25,7 +33,15 @@ pub struct ReverbProperties {
     pub decay_hflimit: i32,
 }
 
-#[derive(Debug)]
+impl Default for ReverbProperties {
+    /// The properties of `ReverbPreset::Generic`, the defaults of OpenAL.
+    fn default() -> ReverbProperties {
+        ReverbPreset::Generic.properties()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ReverbPreset {
     Generic,
     Paddedcell,
@@ -142,6 +158,134 @@ pub enum ReverbPreset {
     Smallwaterroom,
 }
 
+const ALL: [ReverbPreset; 113] = [
+    ReverbPreset::Generic,
+    ReverbPreset::Paddedcell,
+    ReverbPreset::Room,
+    ReverbPreset::Bathroom,
+    ReverbPreset::Livingroom,
+    ReverbPreset::Stoneroom,
+    ReverbPreset::Auditorium,
+    ReverbPreset::Concerthall,
+    ReverbPreset::Cave,
+    ReverbPreset::Arena,
+    ReverbPreset::Hangar,
+    ReverbPreset::Carpetedhallway,
+    ReverbPreset::Hallway,
+    ReverbPreset::Stonecorridor,
+    ReverbPreset::Alley,
+    ReverbPreset::Forest,
+    ReverbPreset::City,
+    ReverbPreset::Mountains,
+    ReverbPreset::Quarry,
+    ReverbPreset::Plain,
+    ReverbPreset::Parkinglot,
+    ReverbPreset::Sewerpipe,
+    ReverbPreset::Underwater,
+    ReverbPreset::Drugged,
+    ReverbPreset::Dizzy,
+    ReverbPreset::Psychotic,
+    ReverbPreset::CastleSmallroom,
+    ReverbPreset::CastleShortpassage,
+    ReverbPreset::CastleMediumroom,
+    ReverbPreset::CastleLargeroom,
+    ReverbPreset::CastleLongpassage,
+    ReverbPreset::CastleHall,
+    ReverbPreset::CastleCupboard,
+    ReverbPreset::CastleCourtyard,
+    ReverbPreset::CastleAlcove,
+    ReverbPreset::FactorySmallroom,
+    ReverbPreset::FactoryShortpassage,
+    ReverbPreset::FactoryMediumroom,
+    ReverbPreset::FactoryLargeroom,
+    ReverbPreset::FactoryLongpassage,
+    ReverbPreset::FactoryHall,
+    ReverbPreset::FactoryCupboard,
+    ReverbPreset::FactoryCourtyard,
+    ReverbPreset::FactoryAlcove,
+    ReverbPreset::IcepalaceSmallroom,
+    ReverbPreset::IcepalaceShortpassage,
+    ReverbPreset::IcepalaceMediumroom,
+    ReverbPreset::IcepalaceLargeroom,
+    ReverbPreset::IcepalaceLongpassage,
+    ReverbPreset::IcepalaceHall,
+    ReverbPreset::IcepalaceCupboard,
+    ReverbPreset::IcepalaceCourtyard,
+    ReverbPreset::IcepalaceAlcove,
+    ReverbPreset::SpacestationSmallroom,
+    ReverbPreset::SpacestationShortpassage,
+    ReverbPreset::SpacestationMediumroom,
+    ReverbPreset::SpacestationLargeroom,
+    ReverbPreset::SpacestationLongpassage,
+    ReverbPreset::SpacestationHall,
+    ReverbPreset::SpacestationCupboard,
+    ReverbPreset::SpacestationAlcove,
+    ReverbPreset::WoodenSmallroom,
+    ReverbPreset::WoodenShortpassage,
+    ReverbPreset::WoodenMediumroom,
+    ReverbPreset::WoodenLargeroom,
+    ReverbPreset::WoodenLongpassage,
+    ReverbPreset::WoodenHall,
+    ReverbPreset::WoodenCupboard,
+    ReverbPreset::WoodenCourtyard,
+    ReverbPreset::WoodenAlcove,
+    ReverbPreset::SportEmptystadium,
+    ReverbPreset::SportSquashcourt,
+    ReverbPreset::SportSmallswimmingpool,
+    ReverbPreset::SportLargeswimmingpool,
+    ReverbPreset::SportGymnasium,
+    ReverbPreset::SportFullstadium,
+    ReverbPreset::SportStadiumtannoy,
+    ReverbPreset::PrefabWorkshop,
+    ReverbPreset::PrefabSchoolroom,
+    ReverbPreset::PrefabPractiseroom,
+    ReverbPreset::PrefabOuthouse,
+    ReverbPreset::PrefabCaravan,
+    ReverbPreset::DomeTomb,
+    ReverbPreset::PipeSmall,
+    ReverbPreset::DomeSaintpauls,
+    ReverbPreset::PipeLongthin,
+    ReverbPreset::PipeLarge,
+    ReverbPreset::PipeResonant,
+    ReverbPreset::OutdoorsBackyard,
+    ReverbPreset::OutdoorsRollingplains,
+    ReverbPreset::OutdoorsDeepcanyon,
+    ReverbPreset::OutdoorsCreek,
+    ReverbPreset::OutdoorsValley,
+    ReverbPreset::MoodHeaven,
+    ReverbPreset::MoodHell,
+    ReverbPreset::MoodMemory,
+    ReverbPreset::DrivingCommentator,
+    ReverbPreset::DrivingPitgarage,
+    ReverbPreset::DrivingIncarRacer,
+    ReverbPreset::DrivingIncarSports,
+    ReverbPreset::DrivingIncarLuxury,
+    ReverbPreset::DrivingFullgrandstand,
+    ReverbPreset::DrivingEmptygrandstand,
+    ReverbPreset::DrivingTunnel,
+    ReverbPreset::CityStreets,
+    ReverbPreset::CitySubway,
+    ReverbPreset::CityMuseum,
+    ReverbPreset::CityLibrary,
+    ReverbPreset::CityUnderpass,
+    ReverbPreset::CityAbandoned,
+    ReverbPreset::Dustyroom,
+    ReverbPreset::Chapel,
+    ReverbPreset::Smallwaterroom,
+];
+
+impl ReverbPreset {
+    /// Get all the built-in presets.
+    pub fn all() -> &'static [ReverbPreset] {
+        &ALL
+    }
+
+    /// Get the name of the preset, like `"Cave"`.
+    pub fn name(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
 // Presets copied from efx-presets.h for convenience
 #[rustfmt::skip]
 impl ReverbPreset {
@@ -264,6 +408,105 @@ impl ReverbPreset {
   }
 }
 
+impl Interpolate for ReverbProperties {
+    /// Interpolate all the fields, `decay_hflimit` switching halfway.
+    fn interpolate(
+        from: ReverbProperties,
+        to: ReverbProperties,
+        progress: f32,
+    ) -> ReverbProperties {
+        ReverbProperties {
+            density: lerp(from.density, to.density, progress),
+            diffusion: lerp(from.diffusion, to.diffusion, progress),
+            gain: lerp(from.gain, to.gain, progress),
+            gainhf: lerp(from.gainhf, to.gainhf, progress),
+            gainlf: lerp(from.gainlf, to.gainlf, progress),
+            decay_time: lerp(from.decay_time, to.decay_time, progress),
+            decay_hfratio: lerp(from.decay_hfratio, to.decay_hfratio, progress),
+            decay_lfratio: lerp(from.decay_lfratio, to.decay_lfratio, progress),
+            reflections_gain: lerp(from.reflections_gain, to.reflections_gain, progress),
+            reflections_delay: lerp(from.reflections_delay, to.reflections_delay, progress),
+            reflections_pan: Interpolate::interpolate(
+                from.reflections_pan,
+                to.reflections_pan,
+                progress,
+            ),
+            late_reverb_gain: lerp(from.late_reverb_gain, to.late_reverb_gain, progress),
+            late_reverb_delay: lerp(from.late_reverb_delay, to.late_reverb_delay, progress),
+            late_reverb_pan: Interpolate::interpolate(
+                from.late_reverb_pan,
+                to.late_reverb_pan,
+                progress,
+            ),
+            echo_time: lerp(from.echo_time, to.echo_time, progress),
+            echo_depth: lerp(from.echo_depth, to.echo_depth, progress),
+            modulation_time: lerp(from.modulation_time, to.modulation_time, progress),
+            modulation_depth: lerp(from.modulation_depth, to.modulation_depth, progress),
+            air_absorption_gainhf: lerp(
+                from.air_absorption_gainhf,
+                to.air_absorption_gainhf,
+                progress,
+            ),
+            hf_reference: lerp(from.hf_reference, to.hf_reference, progress),
+            lf_reference: lerp(from.lf_reference, to.lf_reference, progress),
+            room_rolloff_factor: lerp(from.room_rolloff_factor, to.room_rolloff_factor, progress),
+            decay_hflimit: if progress < 0.5 {
+                from.decay_hflimit
+            } else {
+                to.decay_hflimit
+            },
+        }
+    }
+}
+
+/// Scale a pan vector down to a length of at most 1.
+fn clamp_pan(pan: [f32; 3]) -> [f32; 3] {
+    let length = (pan[0] * pan[0] + pan[1] * pan[1] + pan[2] * pan[2]).sqrt();
+    if length > 1. {
+        [pan[0] / length, pan[1] / length, pan[2] / length]
+    } else {
+        pan
+    }
+}
+
+impl ReverbProperties {
+    /**
+     * Get the properties with each field clamped to the range OpenAL
+     * accepts, see the setters of `ReverbEffect` for the ranges.
+     *
+     * # Return
+     * The clamped properties, the pan vectors scaled down to a length of at
+     * most 1.0 and `decay_hflimit` set to 1 if it isn't 0
+     */
+    pub fn clamped(&self) -> ReverbProperties {
+        ReverbProperties {
+            density: self.density.clamp(0., 1.),
+            diffusion: self.diffusion.clamp(0., 1.),
+            gain: self.gain.clamp(0., 1.),
+            gainhf: self.gainhf.clamp(0., 1.),
+            gainlf: self.gainlf.clamp(0., 1.),
+            decay_time: self.decay_time.clamp(0.1, 20.),
+            decay_hfratio: self.decay_hfratio.clamp(0.1, 2.),
+            decay_lfratio: self.decay_lfratio.clamp(0.1, 2.),
+            reflections_gain: self.reflections_gain.clamp(0., 3.16),
+            reflections_delay: self.reflections_delay.clamp(0., 0.3),
+            reflections_pan: clamp_pan(self.reflections_pan),
+            late_reverb_gain: self.late_reverb_gain.clamp(0., 10.),
+            late_reverb_delay: self.late_reverb_delay.clamp(0., 0.1),
+            late_reverb_pan: clamp_pan(self.late_reverb_pan),
+            echo_time: self.echo_time.clamp(0.075, 0.25),
+            echo_depth: self.echo_depth.clamp(0., 1.),
+            modulation_time: self.modulation_time.clamp(0.04, 4.),
+            modulation_depth: self.modulation_depth.clamp(0., 1.),
+            air_absorption_gainhf: self.air_absorption_gainhf.clamp(0.892, 1.),
+            hf_reference: self.hf_reference.clamp(1000., 20000.),
+            lf_reference: self.lf_reference.clamp(20., 1000.),
+            room_rolloff_factor: self.room_rolloff_factor.clamp(0., 10.),
+            decay_hflimit: (self.decay_hflimit != 0) as i32,
+        }
+    }
+}
+
 // This looks stupid but allows lazier copy pasting from efx-presets.h :)
 impl ReverbProperties {
     fn new(
@@ -318,3 +561,43 @@ impl ReverbProperties {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::automation::Interpolate;
+    use crate::presets::{ReverbPreset, ReverbProperties};
+
+    #[test]
+    fn presets_interpolate_OK() {
+        let hallway = ReverbPreset::Hallway.properties();
+        let cave = ReverbPreset::Cave.properties();
+
+        assert_eq!(ReverbProperties::interpolate(hallway, cave, 0.), hallway);
+        let end = ReverbProperties::interpolate(hallway, cave, 1.);
+        assert!((end.decay_time - cave.decay_time).abs() < 1e-6);
+
+        let halfway = ReverbProperties::interpolate(hallway, cave, 0.5);
+        assert!((halfway.decay_time - 2.2).abs() < 1e-6);
+        assert_eq!(halfway.decay_hflimit, cave.decay_hflimit);
+    }
+    #[test]
+    fn presets_clamped_OK() {
+        for preset in ReverbPreset::all() {
+            assert_eq!(preset.properties().clamped(), preset.properties());
+        }
+
+        let mut properties = ReverbPreset::Generic.properties();
+        properties.density = 2.;
+        properties.decay_time = 0.;
+        properties.reflections_pan = [0., 3., 4.];
+        properties.decay_hflimit = 5;
+
+        let clamped = properties.clamped();
+        assert_eq!(clamped.density, 1.);
+        assert_eq!(clamped.decay_time, 0.1);
+        assert_eq!(clamped.reflections_pan, [0., 0.6, 0.8]);
+        assert_eq!(clamped.decay_hflimit, 1);
+    }
+}
@@ -0,0 +1,169 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! The resamplers used to play the Audio Sources at another pitch or sample
+//! rate.
+//!
+//! The resamplers are provided by OpenAL Soft with the
+//! AL_SOFT_source_resampler extension. Without it the list is empty and the
+//! resampler of the Audio Sources can't be changed.
+
+use std::sync::Mutex;
+
+use crate::internal::OpenAlData;
+use crate::openal::{al, ffi};
+
+/**
+ * A resampler of OpenAL Soft.
+ *
+ * Resamplers trade quality for speed: a cheap one is fine for hundreds of
+ * distant Audio Sources, a better one avoids aliasing when a sound is
+ * pitched down a lot. Get the available resamplers with
+ * `ears::resamplers`.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Sound, SoundError};
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     // Use the last resampler, usually the best one, for the slow motion
+ *     let mut snd = Sound::new("path/to/the/explosion.ogg")?;
+ *     if let Some(resampler) = ears::resamplers().last() {
+ *         snd.set_resampler(resampler);
+ *     }
+ *     snd.set_pitch(0.25);
+ *     snd.play();
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resampler {
+    index: i32,
+    name: String,
+}
+
+impl Resampler {
+    /// The index of the resampler in the list of OpenAL Soft.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// The name of the resampler, like "Linear" or "Cubic".
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn from_al(index: i32) -> Option<Resampler> {
+        al::alGetStringiSOFT(ffi::AL_RESAMPLER_NAME_SOFT, index)
+            .map(|name| Resampler { index, name })
+    }
+}
+
+lazy_static! {
+    /// The resampler of the new sources, None to use the default of
+    /// OpenAL Soft
+    static ref DEFAULT: Mutex<Option<i32>> = Mutex::new(None);
+}
+
+/**
+ * List the resamplers of OpenAL Soft.
+ *
+ * # Return
+ * The available resamplers, usually from the cheapest to the best one.
+ * Empty if the AL_SOFT_source_resampler extension is not present.
+ */
+pub fn resamplers() -> Vec<Resampler> {
+    check_openal_context!(Vec::new());
+
+    if !OpenAlData::source_resampler_capable() {
+        return Vec::new();
+    }
+
+    let count = al::alGetInteger(ffi::AL_NUM_RESAMPLERS_SOFT);
+    (0..count).filter_map(Resampler::from_al).collect()
+}
+
+/**
+ * Get the resampler used by the new Audio Sources.
+ *
+ * # Return
+ * The resampler set with `set_default_resampler`, otherwise the default
+ * resampler of OpenAL Soft. None if the AL_SOFT_source_resampler extension
+ * is not present.
+ */
+pub fn default_resampler() -> Option<Resampler> {
+    check_openal_context!(None);
+
+    if !OpenAlData::source_resampler_capable() {
+        return None;
+    }
+
+    let index = match DEFAULT.lock() {
+        Ok(default) => *default,
+        Err(_) => None,
+    };
+    Resampler::from_al(index.unwrap_or_else(|| al::alGetInteger(ffi::AL_DEFAULT_RESAMPLER_SOFT)))
+}
+
+/**
+ * Set the resampler used by the Audio Sources created from now on.
+ *
+ * The existing Audio Sources keep their resampler.
+ *
+ * # Argument
+ * * `resampler` - The resampler of the new Audio Sources, or None to use
+ *   the default resampler of OpenAL Soft
+ */
+pub fn set_default_resampler(resampler: Option<&Resampler>) {
+    if let Ok(mut default) = DEFAULT.lock() {
+        *default = resampler.map(|resampler| resampler.index);
+    }
+}
+
+/// Apply the default resampler to a new source.
+pub(crate) fn register(al_source: u32) {
+    if let Ok(default) = DEFAULT.lock() {
+        if let Some(index) = *default {
+            al::alSourcei(al_source, ffi::AL_SOURCE_RESAMPLER_SOFT, index);
+        }
+    }
+}
+
+/// Set the resampler of a source.
+///
+/// Does nothing if AL_SOFT_source_resampler isn't available.
+pub(crate) fn set(al_source: u32, resampler: &Resampler) {
+    if OpenAlData::source_resampler_capable() {
+        al::alSourcei(al_source, ffi::AL_SOURCE_RESAMPLER_SOFT, resampler.index);
+    }
+}
+
+/// The resampler of a source.
+pub(crate) fn source(al_source: u32) -> Option<Resampler> {
+    if !OpenAlData::source_resampler_capable() {
+        return None;
+    }
+
+    let mut index = 0;
+    al::alGetSourcei(al_source, ffi::AL_SOURCE_RESAMPLER_SOFT, &mut index);
+    Resampler::from_al(index)
+}
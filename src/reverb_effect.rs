@@ -1,8 +1,14 @@
+use crate::automation::Interpolate;
 use crate::internal::OpenAlData;
 use crate::openal::{al, ffi};
-use crate::presets::ReverbProperties;
+use crate::presets::{ReverbPreset, ReverbProperties};
+use crate::sends;
+use crate::snapshot;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
 
 /// All possible errors when opening a Music.
 pub enum ReverbEffectError {
@@ -54,6 +60,9 @@ impl Error for ReverbEffectError {
  * Internally it creates an OpenAL Effect Object with an Auxiliary Effect
  * Slot Object pair.
  *
+ * The EAX reverb is used when the OpenAL implementation supports it,
+ * applying all the fields of the ReverbProperties, see `ReverbType`.
+ *
  * **Note:** the effects API may change as it's implemented fully, but I'll
  * try not to make the changes too drastic.
  *
@@ -87,10 +96,277 @@ impl Error for ReverbEffectError {
 pub struct ReverbEffect {
     effect_id: u32,
     effect_slot_id: u32,
+    reverb_type: ReverbType,
+}
+
+/**
+ * The OpenAL reverb a ReverbEffect is made of.
+ *
+ * The EAX reverb supports all the fields of the ReverbProperties, the
+ * standard reverb ignores `gainlf`, `decay_lfratio`, `reflections_pan`,
+ * `late_reverb_pan`, `echo_time`, `echo_depth`, `modulation_time`,
+ * `modulation_depth`, `hf_reference` and `lf_reference`.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReverbType {
+    /// The EAX reverb if the OpenAL implementation supports it, the
+    /// standard reverb otherwise
+    #[default]
+    Auto,
+    /// The EAX reverb
+    Eax,
+    /// The standard reverb
+    Standard,
+}
+
+struct EffectState {
+    effect_slot_id: u32,
+    reverb_type: ReverbType,
+    properties: ReverbProperties,
+    morph: Option<Morph>,
+}
+
+struct Morph {
+    from: ReverbProperties,
+    to: ReverbProperties,
+    elapsed: Duration,
+    duration: Duration,
+}
+
+lazy_static! {
+    static ref EFFECTS: Mutex<HashMap<u32, EffectState>> = Mutex::new(HashMap::new());
+}
+
+/// Set a parameter, with its standard reverb name if the standard reverb has
+/// it, and its EAX reverb name.
+fn set_param(effect_id: u32, reverb_type: ReverbType, standard: Option<i32>, eax: i32, value: f32) {
+    match (reverb_type, standard) {
+        (ReverbType::Eax, _) => al::alEffectf(effect_id, eax, value),
+        (_, Some(standard)) => al::alEffectf(effect_id, standard, value),
+        (_, None) => {}
+    }
+}
+
+/// Set all the parameters of an effect and load it in its slot, the slot
+/// copying the parameters of the effect when it is attached.
+///
+/// The properties are expected to be clamped, the errors are left to the
+/// caller.
+fn apply(effect_id: u32, state: &EffectState) {
+    let reverb_type = state.reverb_type;
+    let properties = &state.properties;
+
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_DENSITY),
+        ffi::AL_EAXREVERB_DENSITY,
+        properties.density,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_DIFFUSION),
+        ffi::AL_EAXREVERB_DIFFUSION,
+        properties.diffusion,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_GAIN),
+        ffi::AL_EAXREVERB_GAIN,
+        properties.gain,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_GAINHF),
+        ffi::AL_EAXREVERB_GAINHF,
+        properties.gainhf,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        None,
+        ffi::AL_EAXREVERB_GAINLF,
+        properties.gainlf,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_DECAY_TIME),
+        ffi::AL_EAXREVERB_DECAY_TIME,
+        properties.decay_time,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_DECAY_HFRATIO),
+        ffi::AL_EAXREVERB_DECAY_HFRATIO,
+        properties.decay_hfratio,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        None,
+        ffi::AL_EAXREVERB_DECAY_LFRATIO,
+        properties.decay_lfratio,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_REFLECTIONS_GAIN),
+        ffi::AL_EAXREVERB_REFLECTIONS_GAIN,
+        properties.reflections_gain,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_REFLECTIONS_DELAY),
+        ffi::AL_EAXREVERB_REFLECTIONS_DELAY,
+        properties.reflections_delay,
+    );
+    if reverb_type == ReverbType::Eax {
+        al::alEffectfv(
+            effect_id,
+            ffi::AL_EAXREVERB_REFLECTIONS_PAN,
+            &properties.reflections_pan,
+        );
+    }
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_LATE_REVERB_GAIN),
+        ffi::AL_EAXREVERB_LATE_REVERB_GAIN,
+        properties.late_reverb_gain,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_LATE_REVERB_DELAY),
+        ffi::AL_EAXREVERB_LATE_REVERB_DELAY,
+        properties.late_reverb_delay,
+    );
+    if reverb_type == ReverbType::Eax {
+        al::alEffectfv(
+            effect_id,
+            ffi::AL_EAXREVERB_LATE_REVERB_PAN,
+            &properties.late_reverb_pan,
+        );
+    }
+    set_param(
+        effect_id,
+        reverb_type,
+        None,
+        ffi::AL_EAXREVERB_ECHO_TIME,
+        properties.echo_time,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        None,
+        ffi::AL_EAXREVERB_ECHO_DEPTH,
+        properties.echo_depth,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        None,
+        ffi::AL_EAXREVERB_MODULATION_TIME,
+        properties.modulation_time,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        None,
+        ffi::AL_EAXREVERB_MODULATION_DEPTH,
+        properties.modulation_depth,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_AIR_ABSORPTION_GAINHF),
+        ffi::AL_EAXREVERB_AIR_ABSORPTION_GAINHF,
+        properties.air_absorption_gainhf,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        None,
+        ffi::AL_EAXREVERB_HFREFERENCE,
+        properties.hf_reference,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        None,
+        ffi::AL_EAXREVERB_LFREFERENCE,
+        properties.lf_reference,
+    );
+    set_param(
+        effect_id,
+        reverb_type,
+        Some(ffi::AL_REVERB_ROOM_ROLLOFF_FACTOR),
+        ffi::AL_EAXREVERB_ROOM_ROLLOFF_FACTOR,
+        properties.room_rolloff_factor,
+    );
+    let decay_hflimit = match reverb_type {
+        ReverbType::Eax => ffi::AL_EAXREVERB_DECAY_HFLIMIT,
+        _ => ffi::AL_REVERB_DECAY_HFLIMIT,
+    };
+    al::alEffecti(effect_id, decay_hflimit, properties.decay_hflimit);
+
+    al::alAuxiliaryEffectSloti(state.effect_slot_id, ffi::AL_EFFECTSLOT_EFFECT, effect_id);
 }
 
 impl ReverbEffect {
+    /// Create a ReverbEffect with the default parameters, using the EAX
+    /// reverb when available.
     pub fn new() -> Result<ReverbEffect, ReverbEffectError> {
+        ReverbEffect::with_type(ReverbType::Auto)
+    }
+
+    /**
+     * Create a ReverbEffect with the default parameters.
+     *
+     * The default parameters are the ones of `ReverbPreset::Generic`.
+     *
+     * # Argument
+     * * `reverb_type` - The OpenAL reverb to use
+     *
+     * # Return
+     * The ReverbEffect, or an error if the reverb type isn't supported
+     */
+    pub fn with_type(reverb_type: ReverbType) -> Result<ReverbEffect, ReverbEffectError> {
+        ReverbEffect::preset_with_type(ReverbPreset::Generic.properties(), reverb_type)
+    }
+
+    /**
+     * Create a ReverbEffect from a set of properties, using the EAX reverb
+     * when available.
+     *
+     * # Argument
+     * * `reverb_properties` - The properties of the reverb, for example
+     *   `ReverbPreset::Cave.properties()`
+     */
+    pub fn preset(reverb_properties: ReverbProperties) -> Result<ReverbEffect, ReverbEffectError> {
+        ReverbEffect::preset_with_type(reverb_properties, ReverbType::Auto)
+    }
+
+    /**
+     * Create a ReverbEffect from a set of properties.
+     *
+     * # Arguments
+     * * `reverb_properties` - The properties of the reverb
+     * * `reverb_type` - The OpenAL reverb to use
+     *
+     * # Return
+     * The ReverbEffect, or an error if the reverb type isn't supported
+     */
+    pub fn preset_with_type(
+        reverb_properties: ReverbProperties,
+        reverb_type: ReverbType,
+    ) -> Result<ReverbEffect, ReverbEffectError> {
         check_openal_context!(Err(ReverbEffectError::InvalidOpenALContext));
 
         // Can't seem to find a way to query whether or not EFX extension is available
@@ -105,158 +381,580 @@ impl ReverbEffect {
         let mut effect_id = 0;
         al::alGenEffects(1, &mut effect_id);
 
-        // Assume only "standard reverb" for now. May add EAX reverb at some point.
-        al::alEffecti(effect_id, ffi::AL_EFFECT_TYPE, ffi::AL_EFFECT_REVERB);
-
         // Check if there is OpenAL internal error
         if let Some(err) = al::openal_has_error() {
             return Err(ReverbEffectError::InternalOpenALError(err));
         };
 
-        Ok(ReverbEffect {
+        let mut effect = ReverbEffect {
             effect_id,
             effect_slot_id,
-        })
-    }
+            reverb_type: ReverbType::Standard,
+        };
 
-    pub fn preset(reverb_properties: ReverbProperties) -> Result<ReverbEffect, ReverbEffectError> {
-        match Self::new() {
-            Ok(mut effect) => {
-                effect.set_density(reverb_properties.density);
-                effect.set_diffusion(reverb_properties.diffusion);
-                effect.set_gain(reverb_properties.gain);
-                effect.set_gainhf(reverb_properties.gainhf);
-                effect.set_decay_time(reverb_properties.decay_time);
-                effect.set_decay_hfratio(reverb_properties.decay_hfratio);
-                effect.set_reflections_gain(reverb_properties.reflections_gain);
-                effect.set_reflections_delay(reverb_properties.reflections_delay);
-                effect.set_late_reverb_gain(reverb_properties.late_reverb_gain);
-                effect.set_late_reverb_delay(reverb_properties.late_reverb_delay);
-                effect.set_air_absorption_gainhf(reverb_properties.air_absorption_gainhf);
-                effect.set_room_rolloff_factor(reverb_properties.room_rolloff_factor);
-                effect.set_decay_hflimit(reverb_properties.decay_hflimit);
-
-                // Check if there is OpenAL internal error
-                if let Some(err) = al::openal_has_error() {
+        // The EAX reverb is rejected by the implementations without it
+        if reverb_type != ReverbType::Standard {
+            al::alEffecti(effect_id, ffi::AL_EFFECT_TYPE, ffi::AL_EFFECT_EAXREVERB);
+            match al::openal_has_error() {
+                None => effect.reverb_type = ReverbType::Eax,
+                Some(err) if reverb_type == ReverbType::Eax => {
                     return Err(ReverbEffectError::InternalOpenALError(err));
-                };
+                }
+                Some(_) => {}
+            }
+        }
+        if effect.reverb_type == ReverbType::Standard {
+            al::alEffecti(effect_id, ffi::AL_EFFECT_TYPE, ffi::AL_EFFECT_REVERB);
+        }
 
-                effect.update_slot();
+        let state = EffectState {
+            effect_slot_id,
+            reverb_type: effect.reverb_type,
+            properties: reverb_properties.clamped(),
+            morph: None,
+        };
+        apply(effect_id, &state);
 
-                Ok(effect)
-            }
-            Err(e) => Err(e),
+        // Check if there is OpenAL internal error
+        if let Some(err) = al::openal_has_error() {
+            return Err(ReverbEffectError::InternalOpenALError(err));
+        };
+
+        if let Ok(mut effects) = EFFECTS.lock() {
+            effects.insert(effect_id, state);
         }
+
+        Ok(effect)
     }
 
     pub fn slot(&self) -> u32 {
         self.effect_slot_id
     }
 
-    fn update_slot(&mut self) {
-        check_openal_context!(());
-        al::alAuxiliaryEffectSloti(
-            self.effect_slot_id,
-            ffi::AL_EFFECTSLOT_EFFECT,
-            self.effect_id,
-        );
+    /// Get the OpenAL reverb the ReverbEffect is made of, `Eax` or
+    /// `Standard`.
+    pub fn get_type(&self) -> ReverbType {
+        self.reverb_type
     }
 
-    fn set_density(&mut self, density: f32) {
+    fn update<F: FnOnce(&mut ReverbProperties)>(&mut self, change: F) {
         check_openal_context!(());
-        al::alEffectf(self.effect_id, ffi::AL_REVERB_DENSITY, density);
+
+        if let Ok(mut effects) = EFFECTS.lock() {
+            if let Some(state) = effects.get_mut(&self.effect_id) {
+                state.morph = None;
+                change(&mut state.properties);
+                state.properties = state.properties.clamped();
+                apply(self.effect_id, state);
+            }
+        }
+
+        // The setters don't fail, don't leave the error to the next call
+        let _ = al::openal_has_error();
     }
 
-    fn set_diffusion(&mut self, diffusion: f32) {
-        check_openal_context!(());
-        al::alEffectf(self.effect_id, ffi::AL_REVERB_DIFFUSION, diffusion);
+    fn read<T, F: FnOnce(&ReverbProperties) -> T>(&self, read: F) -> T {
+        let effects = EFFECTS.lock().ok();
+        let state = effects
+            .as_ref()
+            .and_then(|effects| effects.get(&self.effect_id));
+        match state {
+            Some(state) => read(&state.properties),
+            None => read(&ReverbPreset::Generic.properties()),
+        }
     }
 
-    fn set_gain(&mut self, gain: f32) {
-        check_openal_context!(());
-        al::alEffectf(self.effect_id, ffi::AL_REVERB_GAIN, gain);
+    /**
+     * Set all the parameters of the ReverbEffect at once.
+     *
+     * Cancels the running morph, if any. The properties are clamped to their
+     * ranges, see `ReverbProperties::clamped`.
+     *
+     * # Argument
+     * * `reverb_properties` - The new properties of the reverb
+     */
+    pub fn set_properties(&mut self, reverb_properties: &ReverbProperties) {
+        self.update(|properties| *properties = *reverb_properties);
     }
 
-    fn set_gainhf(&mut self, gainhf: f32) {
-        check_openal_context!(());
-        al::alEffectf(self.effect_id, ffi::AL_REVERB_GAINHF, gainhf);
+    /// Get all the current parameters of the ReverbEffect.
+    pub fn get_properties(&self) -> ReverbProperties {
+        self.read(|properties| *properties)
     }
 
-    fn set_decay_time(&mut self, decay_time: f32) {
-        check_openal_context!(());
-        al::alEffectf(self.effect_id, ffi::AL_REVERB_DECAY_TIME, decay_time);
+    /**
+     * Move all the parameters of the ReverbEffect towards a set of
+     * properties over time.
+     *
+     * The parameters are interpolated each time `ears::update` is called, or
+     * by the background ticker. Setting a parameter cancels the morph.
+     *
+     * # Arguments
+     * * `reverb_properties` - The properties to reach
+     * * `duration` - The duration of the morph, 0 to apply the properties
+     *   at once
+     *
+     * # Example
+     * ```no_run
+     * use ears::{ReverbEffect, ReverbPreset};
+     * use std::time::Duration;
+     *
+     * let mut effect = ReverbEffect::preset(ReverbPreset::Hallway.properties()).unwrap();
+     * ears::start_ticker(Duration::from_millis(10));
+     *
+     * // Walking into the cave
+     * effect.morph_to(&ReverbPreset::Cave.properties(), Duration::from_secs(3));
+     * ```
+     */
+    pub fn morph_to(&mut self, reverb_properties: &ReverbProperties, duration: Duration) {
+        if duration == Duration::from_secs(0) {
+            self.set_properties(reverb_properties);
+            return;
+        }
+
+        if let Ok(mut effects) = EFFECTS.lock() {
+            if let Some(state) = effects.get_mut(&self.effect_id) {
+                state.morph = Some(Morph {
+                    from: state.properties,
+                    to: reverb_properties.clamped(),
+                    elapsed: Duration::from_secs(0),
+                    duration,
+                });
+            }
+        }
     }
 
-    fn set_decay_hfratio(&mut self, decay_hfratio: f32) {
-        check_openal_context!(());
-        al::alEffectf(self.effect_id, ffi::AL_REVERB_DECAY_HFRATIO, decay_hfratio);
+    /// Check if the ReverbEffect is morphing towards other properties.
+    pub fn is_morphing(&self) -> bool {
+        match EFFECTS.lock() {
+            Ok(effects) => effects
+                .get(&self.effect_id)
+                .is_some_and(|state| state.morph.is_some()),
+            Err(_) => false,
+        }
     }
 
-    fn set_reflections_gain(&mut self, reflections_gain: f32) {
-        check_openal_context!(());
-        al::alEffectf(
-            self.effect_id,
-            ffi::AL_REVERB_REFLECTIONS_GAIN,
-            reflections_gain,
-        );
+    /**
+     * Set the density of the reverb.
+     *
+     * # Argument
+     * * `density` - The new value, clamped to the range [0.0, 1.0]
+     */
+    pub fn set_density(&mut self, density: f32) {
+        self.update(|properties| properties.density = density);
     }
 
-    fn set_reflections_delay(&mut self, reflections_delay: f32) {
-        check_openal_context!(());
-        al::alEffectf(
-            self.effect_id,
-            ffi::AL_REVERB_REFLECTIONS_DELAY,
-            reflections_delay,
-        );
+    /// Get the density of the reverb.
+    pub fn get_density(&self) -> f32 {
+        self.read(|properties| properties.density)
     }
 
-    fn set_late_reverb_gain(&mut self, late_reverb_gain: f32) {
-        check_openal_context!(());
-        al::alEffectf(
-            self.effect_id,
-            ffi::AL_REVERB_LATE_REVERB_GAIN,
-            late_reverb_gain,
-        );
+    /**
+     * Set the diffusion of the reverb, the echo density.
+     *
+     * # Argument
+     * * `diffusion` - The new value, clamped to the range [0.0, 1.0]
+     */
+    pub fn set_diffusion(&mut self, diffusion: f32) {
+        self.update(|properties| properties.diffusion = diffusion);
     }
 
-    fn set_late_reverb_delay(&mut self, late_reverb_delay: f32) {
-        check_openal_context!(());
-        al::alEffectf(
-            self.effect_id,
-            ffi::AL_REVERB_LATE_REVERB_DELAY,
-            late_reverb_delay,
-        );
+    /// Get the diffusion of the reverb, the echo density.
+    pub fn get_diffusion(&self) -> f32 {
+        self.read(|properties| properties.diffusion)
     }
 
-    fn set_air_absorption_gainhf(&mut self, air_absorption_gainhf: f32) {
-        check_openal_context!(());
-        al::alEffectf(
-            self.effect_id,
-            ffi::AL_REVERB_AIR_ABSORPTION_GAINHF,
-            air_absorption_gainhf,
-        );
+    /**
+     * Set the gain of the reverb.
+     *
+     * # Argument
+     * * `gain` - The new value, clamped to the range [0.0, 1.0]
+     */
+    pub fn set_gain(&mut self, gain: f32) {
+        self.update(|properties| properties.gain = gain);
     }
 
-    fn set_room_rolloff_factor(&mut self, room_rolloff_factor: f32) {
-        check_openal_context!(());
-        al::alEffectf(
-            self.effect_id,
-            ffi::AL_REVERB_ROOM_ROLLOFF_FACTOR,
-            room_rolloff_factor,
-        );
+    /// Get the gain of the reverb.
+    pub fn get_gain(&self) -> f32 {
+        self.read(|properties| properties.gain)
     }
 
-    fn set_decay_hflimit(&mut self, decay_hflimit: i32) {
-        check_openal_context!(());
-        al::alEffecti(self.effect_id, ffi::AL_REVERB_DECAY_HFLIMIT, decay_hflimit);
+    /**
+     * Set the gain of the high frequencies of the reverb.
+     *
+     * # Argument
+     * * `gainhf` - The new value, clamped to the range [0.0, 1.0]
+     */
+    pub fn set_gainhf(&mut self, gainhf: f32) {
+        self.update(|properties| properties.gainhf = gainhf);
+    }
+
+    /// Get the gain of the high frequencies of the reverb.
+    pub fn get_gainhf(&self) -> f32 {
+        self.read(|properties| properties.gainhf)
+    }
+
+    /**
+     * Set the gain of the low frequencies of the reverb.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `gainlf` - The new value, clamped to the range [0.0, 1.0]
+     */
+    pub fn set_gainlf(&mut self, gainlf: f32) {
+        self.update(|properties| properties.gainlf = gainlf);
+    }
+
+    /// Get the gain of the low frequencies of the reverb.
+    pub fn get_gainlf(&self) -> f32 {
+        self.read(|properties| properties.gainlf)
+    }
+
+    /**
+     * Set the decay time of the reverb, in seconds.
+     *
+     * # Argument
+     * * `decay_time` - The new value, clamped to the range [0.1, 20.0]
+     */
+    pub fn set_decay_time(&mut self, decay_time: f32) {
+        self.update(|properties| properties.decay_time = decay_time);
+    }
+
+    /// Get the decay time of the reverb, in seconds.
+    pub fn get_decay_time(&self) -> f32 {
+        self.read(|properties| properties.decay_time)
+    }
+
+    /**
+     * Set the ratio of the decay time of the high frequencies.
+     *
+     * # Argument
+     * * `decay_hfratio` - The new value, clamped to the range [0.1, 2.0]
+     */
+    pub fn set_decay_hfratio(&mut self, decay_hfratio: f32) {
+        self.update(|properties| properties.decay_hfratio = decay_hfratio);
+    }
+
+    /// Get the ratio of the decay time of the high frequencies.
+    pub fn get_decay_hfratio(&self) -> f32 {
+        self.read(|properties| properties.decay_hfratio)
+    }
+
+    /**
+     * Set the ratio of the decay time of the low frequencies.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `decay_lfratio` - The new value, clamped to the range [0.1, 2.0]
+     */
+    pub fn set_decay_lfratio(&mut self, decay_lfratio: f32) {
+        self.update(|properties| properties.decay_lfratio = decay_lfratio);
+    }
+
+    /// Get the ratio of the decay time of the low frequencies.
+    pub fn get_decay_lfratio(&self) -> f32 {
+        self.read(|properties| properties.decay_lfratio)
+    }
+
+    /**
+     * Set the gain of the early reflections.
+     *
+     * # Argument
+     * * `reflections_gain` - The new value, clamped to the range [0.0, 3.16]
+     */
+    pub fn set_reflections_gain(&mut self, reflections_gain: f32) {
+        self.update(|properties| properties.reflections_gain = reflections_gain);
+    }
+
+    /// Get the gain of the early reflections.
+    pub fn get_reflections_gain(&self) -> f32 {
+        self.read(|properties| properties.reflections_gain)
+    }
+
+    /**
+     * Set the delay of the early reflections, in seconds.
+     *
+     * # Argument
+     * * `reflections_delay` - The new value, clamped to the range [0.0, 0.3]
+     */
+    pub fn set_reflections_delay(&mut self, reflections_delay: f32) {
+        self.update(|properties| properties.reflections_delay = reflections_delay);
+    }
+
+    /// Get the delay of the early reflections, in seconds.
+    pub fn get_reflections_delay(&self) -> f32 {
+        self.read(|properties| properties.reflections_delay)
+    }
+
+    /**
+     * Set the direction of the early reflections.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `reflections_pan` - The new value, scaled down to a length of at most 1.0
+     */
+    pub fn set_reflections_pan(&mut self, reflections_pan: [f32; 3]) {
+        self.update(|properties| properties.reflections_pan = reflections_pan);
+    }
+
+    /// Get the direction of the early reflections.
+    pub fn get_reflections_pan(&self) -> [f32; 3] {
+        self.read(|properties| properties.reflections_pan)
+    }
+
+    /**
+     * Set the gain of the late reverb.
+     *
+     * # Argument
+     * * `late_reverb_gain` - The new value, clamped to the range [0.0, 10.0]
+     */
+    pub fn set_late_reverb_gain(&mut self, late_reverb_gain: f32) {
+        self.update(|properties| properties.late_reverb_gain = late_reverb_gain);
+    }
+
+    /// Get the gain of the late reverb.
+    pub fn get_late_reverb_gain(&self) -> f32 {
+        self.read(|properties| properties.late_reverb_gain)
+    }
+
+    /**
+     * Set the delay of the late reverb after the early reflections, in seconds.
+     *
+     * # Argument
+     * * `late_reverb_delay` - The new value, clamped to the range [0.0, 0.1]
+     */
+    pub fn set_late_reverb_delay(&mut self, late_reverb_delay: f32) {
+        self.update(|properties| properties.late_reverb_delay = late_reverb_delay);
+    }
+
+    /// Get the delay of the late reverb after the early reflections, in seconds.
+    pub fn get_late_reverb_delay(&self) -> f32 {
+        self.read(|properties| properties.late_reverb_delay)
+    }
+
+    /**
+     * Set the direction of the late reverb.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `late_reverb_pan` - The new value, scaled down to a length of at most 1.0
+     */
+    pub fn set_late_reverb_pan(&mut self, late_reverb_pan: [f32; 3]) {
+        self.update(|properties| properties.late_reverb_pan = late_reverb_pan);
+    }
+
+    /// Get the direction of the late reverb.
+    pub fn get_late_reverb_pan(&self) -> [f32; 3] {
+        self.read(|properties| properties.late_reverb_pan)
+    }
+
+    /**
+     * Set the time of the echo, in seconds.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `echo_time` - The new value, clamped to the range [0.075, 0.25]
+     */
+    pub fn set_echo_time(&mut self, echo_time: f32) {
+        self.update(|properties| properties.echo_time = echo_time);
+    }
+
+    /// Get the time of the echo, in seconds.
+    pub fn get_echo_time(&self) -> f32 {
+        self.read(|properties| properties.echo_time)
+    }
+
+    /**
+     * Set the depth of the echo.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `echo_depth` - The new value, clamped to the range [0.0, 1.0]
+     */
+    pub fn set_echo_depth(&mut self, echo_depth: f32) {
+        self.update(|properties| properties.echo_depth = echo_depth);
+    }
+
+    /// Get the depth of the echo.
+    pub fn get_echo_depth(&self) -> f32 {
+        self.read(|properties| properties.echo_depth)
+    }
+
+    /**
+     * Set the time of the pitch modulation, in seconds.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `modulation_time` - The new value, clamped to the range [0.04, 4.0]
+     */
+    pub fn set_modulation_time(&mut self, modulation_time: f32) {
+        self.update(|properties| properties.modulation_time = modulation_time);
+    }
+
+    /// Get the time of the pitch modulation, in seconds.
+    pub fn get_modulation_time(&self) -> f32 {
+        self.read(|properties| properties.modulation_time)
+    }
+
+    /**
+     * Set the depth of the pitch modulation.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `modulation_depth` - The new value, clamped to the range [0.0, 1.0]
+     */
+    pub fn set_modulation_depth(&mut self, modulation_depth: f32) {
+        self.update(|properties| properties.modulation_depth = modulation_depth);
+    }
+
+    /// Get the depth of the pitch modulation.
+    pub fn get_modulation_depth(&self) -> f32 {
+        self.read(|properties| properties.modulation_depth)
+    }
+
+    /**
+     * Set the attenuation of the high frequencies by the air, per meter.
+     *
+     * # Argument
+     * * `air_absorption_gainhf` - The new value, clamped to the range [0.892, 1.0]
+     */
+    pub fn set_air_absorption_gainhf(&mut self, air_absorption_gainhf: f32) {
+        self.update(|properties| properties.air_absorption_gainhf = air_absorption_gainhf);
+    }
+
+    /// Get the attenuation of the high frequencies by the air, per meter.
+    pub fn get_air_absorption_gainhf(&self) -> f32 {
+        self.read(|properties| properties.air_absorption_gainhf)
+    }
+
+    /**
+     * Set the reference frequency of the high frequencies, in Hz.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `hf_reference` - The new value, clamped to the range [1000.0, 20000.0]
+     */
+    pub fn set_hf_reference(&mut self, hf_reference: f32) {
+        self.update(|properties| properties.hf_reference = hf_reference);
+    }
+
+    /// Get the reference frequency of the high frequencies, in Hz.
+    pub fn get_hf_reference(&self) -> f32 {
+        self.read(|properties| properties.hf_reference)
+    }
+
+    /**
+     * Set the reference frequency of the low frequencies, in Hz.
+     *
+     * Only supported by the EAX reverb.
+     *
+     * # Argument
+     * * `lf_reference` - The new value, clamped to the range [20.0, 1000.0]
+     */
+    pub fn set_lf_reference(&mut self, lf_reference: f32) {
+        self.update(|properties| properties.lf_reference = lf_reference);
+    }
+
+    /// Get the reference frequency of the low frequencies, in Hz.
+    pub fn get_lf_reference(&self) -> f32 {
+        self.read(|properties| properties.lf_reference)
+    }
+
+    /**
+     * Set the distance attenuation of the reverb.
+     *
+     * # Argument
+     * * `room_rolloff_factor` - The new value, clamped to the range [0.0, 10.0]
+     */
+    pub fn set_room_rolloff_factor(&mut self, room_rolloff_factor: f32) {
+        self.update(|properties| properties.room_rolloff_factor = room_rolloff_factor);
+    }
+
+    /// Get the distance attenuation of the reverb.
+    pub fn get_room_rolloff_factor(&self) -> f32 {
+        self.read(|properties| properties.room_rolloff_factor)
+    }
+
+    /**
+     * Set whether the decay of the high frequencies is limited by the air absorption.
+     *
+     * # Argument
+     * * `decay_hflimit` - The new value, 0 or 1
+     */
+    pub fn set_decay_hflimit(&mut self, decay_hflimit: i32) {
+        self.update(|properties| properties.decay_hflimit = decay_hflimit);
+    }
+
+    /// Get whether the decay of the high frequencies is limited by the air absorption.
+    pub fn get_decay_hflimit(&self) -> i32 {
+        self.read(|properties| properties.decay_hflimit)
+    }
+}
+
+/// Check if an auxiliary effect slot belongs to a live ReverbEffect.
+pub(crate) fn is_slot(effect_slot_id: u32) -> bool {
+    match EFFECTS.lock() {
+        Ok(effects) => effects
+            .values()
+            .any(|state| state.effect_slot_id == effect_slot_id),
+        Err(_) => false,
+    }
+}
+
+/// Advance the morphs of the effects by `dt`.
+pub(crate) fn update(dt: Duration) {
+    check_openal_context!(());
+
+    let mut applied = false;
+    if let Ok(mut effects) = EFFECTS.lock() {
+        for (effect_id, state) in effects.iter_mut() {
+            let morph = match state.morph.as_mut() {
+                Some(morph) => morph,
+                None => continue,
+            };
+            morph.elapsed += dt;
+            let progress = morph.elapsed.as_secs_f32() / morph.duration.as_secs_f32();
+            if progress >= 1. {
+                state.properties = morph.to;
+                state.morph = None;
+            } else {
+                state.properties = ReverbProperties::interpolate(morph.from, morph.to, progress);
+            }
+            apply(*effect_id, state);
+            applied = true;
+        }
+    }
+
+    // Only clear the error raised by a morph, an idle tick must not consume
+    // the errors of the other threads
+    if applied {
+        let _ = al::openal_has_error();
     }
 }
 
 impl Drop for ReverbEffect {
     // Delete the Effect Object and Auxiliary Effect Slot Object
     fn drop(&mut self) -> () {
+        if let Ok(mut effects) = EFFECTS.lock() {
+            effects.remove(&self.effect_id);
+        }
+        snapshot::forget_effect(self.effect_slot_id);
+
         check_openal_context!(());
 
+        // Disconnect the sources still sending to the slot, like the
+        // one-shots
+        sends::disconnect_slot(self.effect_slot_id);
+
         // Disconnect the effect and slot
         al::alAuxiliaryEffectSloti(
             self.effect_slot_id,
@@ -270,8 +968,6 @@ impl Drop for ReverbEffect {
         }
 
         // Check if there is OpenAL internal error
-        //
-        // TODO: this could probably be avoided with some better design
         if let Some(err) = al::openal_has_error() {
             eprintln!("Ears failed to drop ReverbEffect completely, one or more source is probably still referencing it: {}", err);
             eprintln!("\tEffect Object: {}", self.effect_id);
@@ -279,3 +975,46 @@ impl Drop for ReverbEffect {
         };
     }
 }
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::presets::ReverbPreset;
+    use crate::reverb_effect::{ReverbEffect, ReverbType};
+
+    #[test]
+    #[ignore]
+    fn reverb_effect_standard_type_OK() {
+        let effect =
+            ReverbEffect::preset_with_type(ReverbPreset::Cave.properties(), ReverbType::Standard)
+                .unwrap();
+
+        assert_eq!(effect.get_type(), ReverbType::Standard);
+    }
+
+    #[test]
+    #[ignore]
+    fn reverb_effect_auto_type_OK() {
+        let effect =
+            ReverbEffect::preset_with_type(ReverbPreset::Cave.properties(), ReverbType::Auto)
+                .unwrap();
+
+        // Auto resolves to the reverb actually used
+        assert!(effect.get_type() != ReverbType::Auto);
+    }
+
+    #[test]
+    #[ignore]
+    fn reverb_effect_eax_type_OK() {
+        let properties = ReverbPreset::Cave.properties();
+        let auto = ReverbEffect::preset_with_type(properties, ReverbType::Auto).unwrap();
+        let eax = ReverbEffect::preset_with_type(properties, ReverbType::Eax);
+
+        // Auto only falls back to the standard reverb without EAX
+        match auto.get_type() {
+            ReverbType::Eax => assert_eq!(eax.unwrap().get_type(), ReverbType::Eax),
+            _ => assert!(eax.is_err()),
+        }
+    }
+}
@@ -0,0 +1,459 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Reverb zones blended by the position of the listener.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::automation::Interpolate;
+use crate::internal::OpenAlData;
+use crate::listener;
+use crate::openal::{al, ffi};
+use crate::presets::ReverbProperties;
+use crate::reverb_effect::ReverbEffect;
+use crate::sends;
+
+/// The shape of a ReverbZone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ZoneShape {
+    /// A sphere around its center [x, y, z].
+    Sphere { center: [f32; 3], radius: f32 },
+    /// An axis aligned box between its minimum and maximum corners
+    /// [x, y, z].
+    Box { min: [f32; 3], max: [f32; 3] },
+}
+
+impl ZoneShape {
+    /**
+     * Get the distance between a position and the ZoneShape.
+     *
+     * # Argument
+     * * `position` - The position [x, y, z]
+     *
+     * # Return
+     * The distance to the border of the shape, 0.0 when the position is
+     * inside
+     */
+    pub fn distance(&self, position: [f32; 3]) -> f32 {
+        match *self {
+            ZoneShape::Sphere { center, radius } => {
+                let offset = [
+                    position[0] - center[0],
+                    position[1] - center[1],
+                    position[2] - center[2],
+                ];
+                let distance = dot(offset, offset).sqrt();
+                (distance - radius).max(0.)
+            }
+            ZoneShape::Box { min, max } => {
+                let offset = [
+                    (min[0] - position[0]).max(position[0] - max[0]).max(0.),
+                    (min[1] - position[1]).max(position[1] - max[1]).max(0.),
+                    (min[2] - position[2]).max(position[2] - max[2]).max(0.),
+                ];
+                dot(offset, offset).sqrt()
+            }
+        }
+    }
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+struct Zone {
+    shape: ZoneShape,
+    properties: ReverbProperties,
+    priority: i32,
+    fade_distance: f32,
+    /// The weight of the zone at the last update, in the range [0.0, 1.0]
+    weight: f32,
+}
+
+/// The weight of a zone at a distance from its shape: 1.0 inside, going
+/// down to 0.0 over the fade distance outside.
+fn weight(distance: f32, fade_distance: f32) -> f32 {
+    if distance <= 0. {
+        1.
+    } else if fade_distance > 0. {
+        (1. - distance / fade_distance).max(0.)
+    } else {
+        0.
+    }
+}
+
+/// Blend the properties of the zones, sorted by priority, each zone
+/// overriding the ones of lower priority by its weight.
+///
+/// # Return
+/// The blended properties and the highest weight, or None when no zone has
+/// a weight.
+fn blend(zones: &[(i32, f32, ReverbProperties)]) -> Option<(ReverbProperties, f32)> {
+    let mut sorted: Vec<_> = zones.iter().filter(|(_, weight, _)| *weight > 0.).collect();
+    sorted.sort_by_key(|(priority, _, _)| *priority);
+
+    let (_, first_weight, first) = sorted.first()?;
+    let mut properties = *first;
+    let mut highest = *first_weight;
+    for (_, weight, target) in sorted.iter().skip(1) {
+        properties = if *weight >= 1. {
+            *target
+        } else {
+            ReverbProperties::interpolate(properties, *target, *weight)
+        };
+        highest = highest.max(*weight);
+    }
+    Some((properties, highest))
+}
+
+struct Zones {
+    zones: HashMap<u64, Zone>,
+    /// The send index of the sources following the zones
+    sources: HashMap<u32, i32>,
+    /// The effect playing the blended zones, created with the first active
+    /// zone
+    effect: Option<ReverbEffect>,
+    /// Whether creating the effect failed, not retried at each update
+    effect_failed: bool,
+    /// Whether the sources are connected to the effect
+    connected: bool,
+}
+
+lazy_static! {
+    static ref ZONES: Mutex<Zones> = Mutex::new(Zones {
+        zones: HashMap::new(),
+        sources: HashMap::new(),
+        effect: None,
+        effect_failed: false,
+        connected: false,
+    });
+    static ref NEXT_ZONE: AtomicU64 = AtomicU64::new(0);
+}
+
+/**
+ * A space with its own reverb, like a room, a cave or a hallway.
+ *
+ * Each time `ears::update` is called, or by the background ticker, the
+ * zones are evaluated against the position of the listener: a zone fully
+ * applies when the listener is inside it, and fades out over its fade
+ * distance around it. The properties of the zones are blended in order of
+ * priority into a single ReverbEffect, the zones of higher priority winning
+ * where zones overlap.
+ *
+ * The Audio Sources following the zones, see
+ * `AudioController::set_reverb_zone_send`, are connected to this
+ * ReverbEffect while the listener is in a zone, and disconnected outside
+ * all the zones.
+ *
+ * Dropping the ReverbZone removes it.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, ReverbPreset, ReverbZone, Sound, SoundError, ZoneShape};
+ * use std::time::Duration;
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let hallway = ReverbZone::new(
+ *         ZoneShape::Box { min: [0., 0., 0.], max: [20., 3., 4.] },
+ *         ReverbPreset::Hallway.properties(),
+ *     );
+ *     let cave = ReverbZone::new(
+ *         ZoneShape::Sphere { center: [30., 0., 2.], radius: 10. },
+ *         ReverbPreset::Cave.properties(),
+ *     );
+ *     cave.set_priority(1);
+ *     cave.set_fade_distance(5.);
+ *
+ *     let mut snd = Sound::new("path/to/the/steps.ogg")?;
+ *     snd.set_reverb_zone_send(Some(0));
+ *
+ *     ears::start_ticker(Duration::from_millis(10));
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Debug)]
+pub struct ReverbZone {
+    id: u64,
+}
+
+impl ReverbZone {
+    /**
+     * Create a ReverbZone.
+     *
+     * Its priority and fade distance default to 0.
+     *
+     * # Arguments
+     * * `shape` - The shape of the zone
+     * * `properties` - The properties of the reverb in the zone, for
+     *   example `ReverbPreset::Cave.properties()`
+     */
+    pub fn new(shape: ZoneShape, properties: ReverbProperties) -> ReverbZone {
+        let id = NEXT_ZONE.fetch_add(1, Ordering::Relaxed);
+
+        if let Ok(mut zones) = ZONES.lock() {
+            zones.zones.insert(
+                id,
+                Zone {
+                    shape,
+                    properties,
+                    priority: 0,
+                    fade_distance: 0.,
+                    weight: 0.,
+                },
+            );
+        }
+
+        ReverbZone { id }
+    }
+
+    fn with_zone<T, F: FnOnce(&mut Zone) -> T>(&self, f: F, default: T) -> T {
+        match ZONES.lock() {
+            Ok(mut zones) => zones.zones.get_mut(&self.id).map(f).unwrap_or(default),
+            Err(_) => default,
+        }
+    }
+
+    /**
+     * Set the shape of the ReverbZone.
+     *
+     * # Argument
+     * * `shape` - The new shape
+     */
+    pub fn set_shape(&self, shape: ZoneShape) {
+        self.with_zone(|zone| zone.shape = shape, ());
+    }
+
+    /// Get the shape of the ReverbZone.
+    pub fn get_shape(&self) -> Option<ZoneShape> {
+        self.with_zone(|zone| Some(zone.shape), None)
+    }
+
+    /**
+     * Set the properties of the reverb in the ReverbZone.
+     *
+     * # Argument
+     * * `properties` - The new properties
+     */
+    pub fn set_properties(&self, properties: &ReverbProperties) {
+        self.with_zone(|zone| zone.properties = *properties, ());
+    }
+
+    /// Get the properties of the reverb in the ReverbZone.
+    pub fn get_properties(&self) -> Option<ReverbProperties> {
+        self.with_zone(|zone| Some(zone.properties), None)
+    }
+
+    /**
+     * Set the priority of the ReverbZone.
+     *
+     * Where zones overlap, the zones of higher priority are blended last
+     * and win.
+     *
+     * # Argument
+     * * `priority` - The new priority
+     */
+    pub fn set_priority(&self, priority: i32) {
+        self.with_zone(|zone| zone.priority = priority, ());
+    }
+
+    /// Get the priority of the ReverbZone.
+    pub fn get_priority(&self) -> i32 {
+        self.with_zone(|zone| zone.priority, 0)
+    }
+
+    /**
+     * Set the distance around the ReverbZone over which it fades out.
+     *
+     * # Argument
+     * * `fade_distance` - The fade distance, 0.0 to stop the zone at its
+     *   border
+     */
+    pub fn set_fade_distance(&self, fade_distance: f32) {
+        self.with_zone(|zone| zone.fade_distance = fade_distance.max(0.), ());
+    }
+
+    /// Get the distance around the ReverbZone over which it fades out.
+    pub fn get_fade_distance(&self) -> f32 {
+        self.with_zone(|zone| zone.fade_distance, 0.)
+    }
+
+    /**
+     * Get how much the ReverbZone applied at the last update.
+     *
+     * # Return
+     * The weight of the zone, 1.0 when the listener is inside and 0.0 when
+     * the listener is beyond its fade distance
+     */
+    pub fn get_weight(&self) -> f32 {
+        self.with_zone(|zone| zone.weight, 0.)
+    }
+}
+
+impl Drop for ReverbZone {
+    fn drop(&mut self) {
+        if let Ok(mut zones) = ZONES.lock() {
+            zones.zones.remove(&self.id);
+        }
+    }
+}
+
+/// Connect or disconnect the sources following the zones.
+fn connect(zones: &Zones, al_source: u32, index: i32) {
+    let slot = match (&zones.effect, zones.connected) {
+        (Some(effect), true) => Some(effect.slot()),
+        _ => None,
+    };
+    sends::connect(al_source, index, slot);
+}
+
+/// Make a source follow the zones on a send, or stop following them with
+/// None.
+pub(crate) fn set_send(al_source: u32, index: Option<i32>) {
+    if let Ok(mut zones) = ZONES.lock() {
+        if let Some(previous) = zones.sources.remove(&al_source) {
+            sends::connect(al_source, previous, None);
+        }
+        if let Some(index) = index {
+            zones.sources.insert(al_source, index);
+            connect(&zones, al_source, index);
+        }
+    }
+}
+
+/// The send index of a source following the zones.
+pub(crate) fn send(al_source: u32) -> Option<i32> {
+    match ZONES.lock() {
+        Ok(zones) => zones.sources.get(&al_source).cloned(),
+        Err(_) => None,
+    }
+}
+
+/// Stop following the zones with a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut zones) = ZONES.lock() {
+        zones.sources.remove(&al_source);
+    }
+}
+
+/// Evaluate the zones at the position of the listener, apply their blend
+/// and connect or disconnect the sources following them.
+pub(crate) fn update() {
+    check_openal_context!(());
+
+    if let Ok(mut zones) = ZONES.lock() {
+        let position = listener::get_position();
+        for zone in zones.zones.values_mut() {
+            zone.weight = weight(zone.shape.distance(position), zone.fade_distance);
+        }
+
+        let weighted: Vec<_> = zones
+            .zones
+            .values()
+            .map(|zone| (zone.priority, zone.weight, zone.properties))
+            .collect();
+        let blended = blend(&weighted);
+
+        if let Some((properties, gain)) = blended {
+            let effect_failed = zones.effect_failed;
+            match zones.effect.as_mut() {
+                Some(effect) if effect.get_properties() != properties => {
+                    effect.set_properties(&properties);
+                }
+                Some(_) => {}
+                None if !effect_failed => match ReverbEffect::preset(properties) {
+                    Ok(effect) => zones.effect = Some(effect),
+                    Err(err) => {
+                        eprintln!(
+                            "Ears failed to create the ReverbEffect of the zones: {}",
+                            err
+                        );
+                        zones.effect_failed = true;
+                    }
+                },
+                None => {}
+            }
+            if let Some(effect) = &zones.effect {
+                al::alAuxiliaryEffectSlotf(effect.slot(), ffi::AL_EFFECTSLOT_GAIN, gain);
+            }
+        }
+
+        let connected = blended.is_some() && zones.effect.is_some();
+        if connected != zones.connected {
+            zones.connected = connected;
+            for (al_source, index) in zones.sources.iter() {
+                connect(&zones, *al_source, *index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::presets::ReverbPreset;
+    use crate::reverb_zone::{blend, weight, ZoneShape};
+
+    #[test]
+    fn reverb_zone_distance_OK() {
+        let sphere = ZoneShape::Sphere {
+            center: [0., 0., 0.],
+            radius: 2.,
+        };
+        assert_eq!(sphere.distance([1., 0., 0.]), 0.);
+        assert_eq!(sphere.distance([0., 5., 0.]), 3.);
+
+        let area = ZoneShape::Box {
+            min: [0., 0., 0.],
+            max: [4., 4., 4.],
+        };
+        assert_eq!(area.distance([2., 2., 2.]), 0.);
+        assert_eq!(area.distance([7., 8., 2.]), 5.);
+    }
+
+    #[test]
+    fn reverb_zone_weight_OK() {
+        assert_eq!(weight(0., 0.), 1.);
+        assert_eq!(weight(1., 0.), 0.);
+        assert_eq!(weight(1., 4.), 0.75);
+        assert_eq!(weight(5., 4.), 0.);
+    }
+
+    #[test]
+    fn reverb_zone_blend_OK() {
+        let hallway = ReverbPreset::Hallway.properties();
+        let cave = ReverbPreset::Cave.properties();
+
+        assert_eq!(blend(&[(0, 0., hallway)]), None);
+        assert_eq!(blend(&[(1, 1., cave), (0, 0.5, hallway)]), Some((cave, 1.)));
+        assert_eq!(
+            blend(&[(1, 0., cave), (0, 0.5, hallway)]),
+            Some((hallway, 0.5))
+        );
+    }
+}
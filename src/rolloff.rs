@@ -0,0 +1,411 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Custom distance attenuation of the Audio Sources.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::fade::lerp;
+use crate::filter;
+use crate::internal::OpenAlData;
+use crate::mixer;
+use crate::mixer::Stage;
+use crate::openal::{al, ffi};
+
+/**
+ * A custom attenuation of an Audio Source with its distance to the listener.
+ *
+ * The curve is made of points mapping a distance to a gain, linearly
+ * interpolated in between. Before the first point the gain of the first
+ * point is used, after the last point the gain of the last point. A curve
+ * without points doesn't attenuate.
+ *
+ * Optionally, a second set of points maps the distance to the gain of the
+ * high frequencies, muffling far away Audio Sources with a low-pass filter.
+ *
+ * While an Audio Source uses a RolloffCurve, the distance attenuation of
+ * OpenAL is disabled for it and the curve is evaluated by __ears__ against
+ * `listener::get_position` each time `ears::update` is called, or by the
+ * background ticker.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, RolloffCurve, Sound, SoundError};
+ * use std::time::Duration;
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     // Full volume up to 5 meters, a shelf, then a steep drop to silence
+ *     let mut curve = RolloffCurve::new();
+ *     curve.add_point(5., 1.);
+ *     curve.add_point(15., 0.6);
+ *     curve.add_point(25., 0.5);
+ *     curve.add_point(40., 0.);
+ *     curve.add_lowpass_point(10., 1.);
+ *     curve.add_lowpass_point(40., 0.2);
+ *
+ *     ears::start_ticker(Duration::from_millis(20));
+ *     let mut snd = Sound::new("path/to/the/sound.ogg")?;
+ *     snd.set_rolloff_curve(Some(curve));
+ *     snd.play();
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "RawRolloffCurve"))]
+pub struct RolloffCurve {
+    points: Vec<(f32, f32)>,
+    lowpass_points: Vec<(f32, f32)>,
+}
+
+/// The points of a deserialized RolloffCurve, before they are sorted and
+/// checked.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct RawRolloffCurve {
+    points: Vec<(f32, f32)>,
+    lowpass_points: Vec<(f32, f32)>,
+}
+
+#[cfg(feature = "serde")]
+impl From<RawRolloffCurve> for RolloffCurve {
+    fn from(raw: RawRolloffCurve) -> RolloffCurve {
+        let mut curve = RolloffCurve::new();
+        for (distance, gain) in raw.points {
+            curve.add_point(distance, gain);
+        }
+        for (distance, gainhf) in raw.lowpass_points {
+            curve.add_lowpass_point(distance, gainhf);
+        }
+        curve
+    }
+}
+
+fn insert_point(points: &mut Vec<(f32, f32)>, distance: f32, value: f32) {
+    if distance.is_nan() || value.is_nan() {
+        return;
+    }
+    let value = value.clamp(0., 1.);
+
+    match points.iter().position(|point| point.0 >= distance) {
+        Some(index) if points[index].0 == distance => points[index] = (distance, value),
+        Some(index) => points.insert(index, (distance, value)),
+        None => points.push((distance, value)),
+    }
+}
+
+fn evaluate(points: &[(f32, f32)], distance: f32) -> Option<f32> {
+    let next = points.iter().position(|point| point.0 > distance);
+
+    match next {
+        None => points.last().map(|point| point.1),
+        Some(0) => Some(points[0].1),
+        Some(index) => {
+            let (from_distance, from) = points[index - 1];
+            let (to_distance, to) = points[index];
+            let progress = (distance - from_distance) / (to_distance - from_distance);
+            Some(lerp(from, to, progress))
+        }
+    }
+}
+
+impl RolloffCurve {
+    /// Create a RolloffCurve without points.
+    pub fn new() -> RolloffCurve {
+        RolloffCurve {
+            points: Vec::new(),
+            lowpass_points: Vec::new(),
+        }
+    }
+
+    /**
+     * Add a point to the gain curve.
+     *
+     * Points are kept sorted by distance, a point added at the distance of
+     * an existing point replaces it. A point with a NaN distance or gain is
+     * ignored.
+     *
+     * # Arguments
+     * * `distance` - The distance to the listener
+     * * `gain` - The gain at this distance, clamped to the range [0.0, 1.0]
+     */
+    pub fn add_point(&mut self, distance: f32, gain: f32) {
+        insert_point(&mut self.points, distance, gain);
+    }
+
+    /**
+     * Add a point to the low-pass curve.
+     *
+     * OpenAL low-pass filters don't have a cutoff frequency, they attenuate
+     * the frequencies above 5kHz by a gain instead. A point with a NaN
+     * distance or gain is ignored.
+     *
+     * # Arguments
+     * * `distance` - The distance to the listener
+     * * `gainhf` - The gain of the high frequencies at this distance,
+     *   clamped to the range [0.0, 1.0]
+     */
+    pub fn add_lowpass_point(&mut self, distance: f32, gainhf: f32) {
+        insert_point(&mut self.lowpass_points, distance, gainhf);
+    }
+
+    /// Get the points of the gain curve, as (distance, gain) sorted by
+    /// distance.
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    /// Get the points of the low-pass curve, as (distance, gainhf) sorted by
+    /// distance.
+    pub fn lowpass_points(&self) -> &[(f32, f32)] {
+        &self.lowpass_points
+    }
+
+    /**
+     * Evaluate the gain curve.
+     *
+     * # Argument
+     * * `distance` - The distance to the listener
+     *
+     * # Return
+     * The gain at this distance, 1.0 if the curve has no points
+     */
+    pub fn gain_at(&self, distance: f32) -> f32 {
+        evaluate(&self.points, distance).unwrap_or(1.)
+    }
+
+    /**
+     * Evaluate the low-pass curve.
+     *
+     * # Argument
+     * * `distance` - The distance to the listener
+     *
+     * # Return
+     * The gain of the high frequencies at this distance, or None if the
+     * low-pass curve has no points
+     */
+    pub fn lowpass_at(&self, distance: f32) -> Option<f32> {
+        evaluate(&self.lowpass_points, distance)
+    }
+}
+
+struct Rolloff {
+    curve: RolloffCurve,
+    /// The OpenAL rolloff factor of the source, applied once the curve is
+    /// removed
+    attenuation: f32,
+}
+
+lazy_static! {
+    static ref ROLLOFFS: Mutex<HashMap<u32, Rolloff>> = Mutex::new(HashMap::new());
+}
+
+fn distance_to_listener(al_source: u32) -> f32 {
+    let mut position = [0.; 3];
+    al::alGetSourcefv(al_source, ffi::AL_POSITION, &mut position[0]);
+
+    let mut relative = 0;
+    al::alGetSourcei(al_source, ffi::AL_SOURCE_RELATIVE, &mut relative);
+
+    let listener = if relative == ffi::AL_TRUE as i32 {
+        [0.; 3]
+    } else {
+        let mut listener = [0.; 3];
+        al::alGetListenerfv(ffi::AL_POSITION, &mut listener[0]);
+        listener
+    };
+
+    let delta = [
+        position[0] - listener[0],
+        position[1] - listener[1],
+        position[2] - listener[2],
+    ];
+    (delta[0] * delta[0] + delta[1] * delta[1] + delta[2] * delta[2]).sqrt()
+}
+
+fn apply(al_source: u32, rolloff: &Rolloff) {
+    let distance = distance_to_listener(al_source);
+
+    mixer::set_gain(
+        al_source,
+        Stage::Rolloff,
+        Some(rolloff.curve.gain_at(distance)),
+    );
+
+    filter::set_rolloff_gainhf(al_source, rolloff.curve.lowpass_at(distance));
+}
+
+/// Set the rolloff curve of a source, or go back to the OpenAL distance
+/// attenuation with None.
+pub(crate) fn set(al_source: u32, curve: Option<RolloffCurve>) {
+    let mut rolloffs = match ROLLOFFS.lock() {
+        Ok(rolloffs) => rolloffs,
+        Err(_) => return,
+    };
+
+    match curve {
+        Some(curve) => {
+            let rolloff = rolloffs.entry(al_source).or_insert_with(|| {
+                let mut attenuation = 0.;
+                al::alGetSourcef(al_source, ffi::AL_ROLLOFF_FACTOR, &mut attenuation);
+                Rolloff {
+                    curve: RolloffCurve::new(),
+                    attenuation,
+                }
+            });
+            rolloff.curve = curve;
+            al::alSourcef(al_source, ffi::AL_ROLLOFF_FACTOR, 0.);
+            apply(al_source, rolloff);
+        }
+        None => {
+            if let Some(rolloff) = rolloffs.remove(&al_source) {
+                filter::set_rolloff_gainhf(al_source, None);
+                mixer::set_gain(al_source, Stage::Rolloff, None);
+                al::alSourcef(al_source, ffi::AL_ROLLOFF_FACTOR, rolloff.attenuation);
+            }
+        }
+    }
+}
+
+/// The rolloff curve of a source.
+pub(crate) fn curve(al_source: u32) -> Option<RolloffCurve> {
+    match ROLLOFFS.lock() {
+        Ok(rolloffs) => rolloffs
+            .get(&al_source)
+            .map(|rolloff| rolloff.curve.clone()),
+        Err(_) => None,
+    }
+}
+
+/// Set the OpenAL attenuation of a source, kept aside until the rolloff
+/// curve is removed while a curve is set.
+pub(crate) fn set_attenuation(al_source: u32, attenuation: f32) {
+    if let Ok(mut rolloffs) = ROLLOFFS.lock() {
+        match rolloffs.get_mut(&al_source) {
+            Some(rolloff) => rolloff.attenuation = attenuation,
+            None => al::alSourcef(al_source, ffi::AL_ROLLOFF_FACTOR, attenuation),
+        }
+    }
+}
+
+/// The OpenAL attenuation of a source, the one it gets back when its
+/// rolloff curve is removed while a curve is set.
+pub(crate) fn attenuation(al_source: u32) -> f32 {
+    if let Ok(rolloffs) = ROLLOFFS.lock() {
+        if let Some(rolloff) = rolloffs.get(&al_source) {
+            return rolloff.attenuation;
+        }
+    }
+
+    let mut attenuation = 0.;
+    al::alGetSourcef(al_source, ffi::AL_ROLLOFF_FACTOR, &mut attenuation);
+    attenuation
+}
+
+/// Forget the rolloff curve of a source about to be deleted.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut rolloffs) = ROLLOFFS.lock() {
+        rolloffs.remove(&al_source);
+    }
+}
+
+/// Evaluate the rolloff curves against the current position of the
+/// listener.
+pub(crate) fn update() {
+    check_openal_context!(());
+
+    if let Ok(rolloffs) = ROLLOFFS.lock() {
+        for (al_source, rolloff) in rolloffs.iter() {
+            apply(*al_source, rolloff);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::rolloff::RolloffCurve;
+
+    #[test]
+    fn rolloff_curve_empty_OK() {
+        let curve = RolloffCurve::new();
+
+        assert_eq!(curve.gain_at(10.), 1.);
+        assert_eq!(curve.lowpass_at(10.), None);
+    }
+
+    #[test]
+    fn rolloff_curve_gain_OK() {
+        let mut curve = RolloffCurve::new();
+        curve.add_point(40., 0.);
+        curve.add_point(5., 1.);
+        curve.add_point(15., 0.5);
+
+        assert_eq!(curve.points(), &[(5., 1.), (15., 0.5), (40., 0.)]);
+        assert_eq!(curve.gain_at(0.), 1.);
+        assert_eq!(curve.gain_at(10.), 0.75);
+        assert_eq!(curve.gain_at(100.), 0.);
+    }
+
+    #[test]
+    fn rolloff_curve_lowpass_OK() {
+        let mut curve = RolloffCurve::new();
+        curve.add_lowpass_point(0., 1.);
+        curve.add_lowpass_point(10., 0.);
+        curve.add_lowpass_point(10., 0.5);
+
+        assert_eq!(curve.lowpass_points().len(), 2);
+        assert_eq!(curve.lowpass_at(5.), Some(0.75));
+    }
+
+    #[test]
+    fn rolloff_curve_invalid_OK() {
+        let mut curve = RolloffCurve::new();
+        curve.add_point(f32::NAN, 0.5);
+        curve.add_point(10., f32::NAN);
+        curve.add_point(20., 2.);
+        curve.add_lowpass_point(10., -1.);
+
+        assert_eq!(curve.points(), &[(20., 1.)]);
+        assert_eq!(curve.lowpass_points(), &[(10., 0.)]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn rolloff_curve_json_OK() {
+        let json = r#"{"points": [[40, 0], [5, 1], [40, 0.5]], "lowpass_points": [[10, 3]]}"#;
+        let curve: RolloffCurve = serde_json::from_str(json).unwrap();
+
+        assert_eq!(curve.points(), &[(5., 1.), (40., 0.5)]);
+        assert_eq!(curve.lowpass_points(), &[(10., 1.)]);
+        assert_eq!(
+            serde_json::from_str::<RolloffCurve>("{}").unwrap(),
+            RolloffCurve::new()
+        );
+    }
+}
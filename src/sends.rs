@@ -0,0 +1,232 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Internal bookkeeping of the auxiliary sends of the sources.
+//!
+//! OpenAL doesn't allow to query which effect slot a source is connected to,
+//! and the gain of a send can only be changed through a filter, so the state
+//! of each send is kept here and applied in one go.
+//!
+//! The sends are connected by `AudioController::connect` and `connect_send`,
+//! the one-shots and the ReverbZones, and disconnected when their
+//! ReverbEffect is dropped. Their gain comes from `set_reverb_send` and the
+//! automations, their filter from the Filters, and the factor of the mix
+//! snapshots from the Buses.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::filter;
+use crate::filter::FilterParams;
+use crate::internal::OpenAlData;
+use crate::openal::{al, ffi};
+
+struct SendState {
+    /// The connected auxiliary effect slot, if any
+    slot: Option<u32>,
+    /// The gain applied to the send
+    gain: f32,
+    /// The Filter set on the send
+    filter_id: Option<u64>,
+    /// The OpenAL filter applying the gain and the Filter, created on demand
+    filter: u32,
+}
+
+struct SourceSends {
+    /// The factor of the mix snapshots, applied on top of the gain of each
+    /// send
+    factor: f32,
+    /// The sends, by index
+    sends: HashMap<i32, SendState>,
+}
+
+lazy_static! {
+    static ref SENDS: Mutex<HashMap<u32, SourceSends>> = Mutex::new(HashMap::new());
+}
+
+fn apply(al_source: u32, index: i32, factor: f32, send: &mut SendState) {
+    let mut params = match send.filter_id.and_then(filter::params) {
+        Some(params) => params,
+        None => {
+            send.filter_id = None;
+            FilterParams::IDENTITY
+        }
+    };
+    params.gain *= send.gain * factor;
+    let filter = filter::configure(&mut send.filter, &params);
+
+    let slot = send
+        .slot
+        .map_or(ffi::AL_EFFECTSLOT_NULL, |slot| slot as i32);
+    al::alSource3i(
+        al_source,
+        ffi::AL_AUXILIARY_SEND_FILTER,
+        slot,
+        index,
+        filter,
+    );
+}
+
+fn source_sends(sends: &mut HashMap<u32, SourceSends>, al_source: u32) -> &mut SourceSends {
+    sends.entry(al_source).or_insert(SourceSends {
+        factor: 1.,
+        sends: HashMap::new(),
+    })
+}
+
+fn with_send<F: FnOnce(&mut SendState)>(al_source: u32, index: i32, f: F) {
+    if let Ok(mut sends) = SENDS.lock() {
+        let source = source_sends(&mut sends, al_source);
+        let send = source.sends.entry(index).or_insert(SendState {
+            slot: None,
+            gain: 1.,
+            filter_id: None,
+            filter: 0,
+        });
+        f(send);
+        apply(al_source, index, source.factor, send);
+    }
+}
+
+fn read<T, F: FnOnce(&SendState) -> T>(al_source: u32, index: i32, read: F, default: T) -> T {
+    match SENDS.lock() {
+        Ok(sends) => sends
+            .get(&al_source)
+            .and_then(|source| source.sends.get(&index))
+            .map(read)
+            .unwrap_or(default),
+        Err(_) => default,
+    }
+}
+
+/**
+ * Get the number of auxiliary sends of each Audio Source.
+ *
+ * __ears__ requests 4 sends when creating its OpenAL context, or the count
+ * given to `ears::init_with_sends`. The device may grant less.
+ *
+ * # Return
+ * The number of sends granted by the device, 0 without OpenAL context
+ *
+ * # Example
+ * ```no_run
+ * fn main() -> Result<(), ears::OpenAlContextError> {
+ *     ears::init_with_sends(2)?;
+ *     println!("{} sends per source", ears::max_auxiliary_sends());
+ *     Ok(())
+ * }
+ * ```
+ */
+pub fn max_auxiliary_sends() -> i32 {
+    check_openal_context!(0);
+
+    al::alcGetInteger(ffi::ALC_MAX_AUXILIARY_SENDS)
+}
+
+/// Connect a send of a source to an auxiliary effect slot, or disconnect it
+/// with None.
+pub(crate) fn connect(al_source: u32, index: i32, slot: Option<u32>) {
+    with_send(al_source, index, |send| send.slot = slot);
+}
+
+/// Set the gain of a send of a source.
+pub(crate) fn set_gain(al_source: u32, index: i32, gain: f32) {
+    with_send(al_source, index, |send| send.gain = gain);
+}
+
+/// Set the Filter of a send of a source, or remove it with None.
+pub(crate) fn set_filter(al_source: u32, index: i32, filter: Option<u64>) {
+    with_send(al_source, index, |send| send.filter_id = filter);
+}
+
+/// Set the factor applied by the mix snapshots to the gain of all the sends
+/// of a source.
+pub(crate) fn set_factor(al_source: u32, factor: f32) {
+    if let Ok(mut sends) = SENDS.lock() {
+        let source = source_sends(&mut sends, al_source);
+        source.factor = factor;
+        for (index, send) in source.sends.iter_mut() {
+            apply(al_source, *index, factor, send);
+        }
+    }
+}
+
+/// Apply the new gains of a Filter to the sends using it.
+pub(crate) fn refresh(filter: u64) {
+    if let Ok(mut sends) = SENDS.lock() {
+        for (al_source, source) in sends.iter_mut() {
+            for (index, send) in source.sends.iter_mut() {
+                if send.filter_id == Some(filter) {
+                    apply(*al_source, *index, source.factor, send);
+                }
+            }
+        }
+    }
+}
+
+/// Disconnect the sends connected to an auxiliary effect slot about to be
+/// deleted.
+pub(crate) fn disconnect_slot(slot: u32) {
+    if let Ok(mut sends) = SENDS.lock() {
+        for (al_source, source) in sends.iter_mut() {
+            for (index, send) in source.sends.iter_mut() {
+                if send.slot == Some(slot) {
+                    send.slot = None;
+                    apply(*al_source, *index, source.factor, send);
+                }
+            }
+        }
+    }
+}
+
+/// The auxiliary effect slot a send of a source is connected to.
+pub(crate) fn slot(al_source: u32, index: i32) -> Option<u32> {
+    read(al_source, index, |send| send.slot, None)
+}
+
+/// The gain of a send of a source.
+pub(crate) fn gain(al_source: u32, index: i32) -> f32 {
+    read(al_source, index, |send| send.gain, 1.)
+}
+
+/// The Filter of a send of a source.
+pub(crate) fn filter(al_source: u32, index: i32) -> Option<u64> {
+    read(al_source, index, |send| send.filter_id, None)
+}
+
+/// Disconnect the sends of a source about to be deleted and release their
+/// filters.
+pub(crate) fn forget(al_source: u32) {
+    if let Ok(mut sends) = SENDS.lock() {
+        if let Some(source) = sends.remove(&al_source) {
+            for (index, mut send) in source.sends.into_iter() {
+                al::alSource3i(
+                    al_source,
+                    ffi::AL_AUXILIARY_SEND_FILTER,
+                    ffi::AL_EFFECTSLOT_NULL,
+                    index,
+                    ffi::AL_FILTER_NULL,
+                );
+                filter::release(&mut send.filter);
+            }
+        }
+    }
+}
@@ -0,0 +1,475 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Mix snapshots blending the volume, pitch and reverb sends of Buses.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::bus::Bus;
+use crate::fade::step;
+use crate::internal::OpenAlData;
+use crate::openal::{al, ffi};
+use crate::reverb_effect::ReverbEffect;
+
+/// How an active MixSnapshot combines with the other active ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SnapshotBlend {
+    /// Blend from the snapshots of lower priority towards the values of the
+    /// snapshot, overriding them once fully active
+    #[default]
+    Override,
+    /// Multiply the values of the snapshot with the ones of the other
+    /// snapshots
+    Additive,
+}
+
+/**
+ * The values a MixSnapshot gives to a Bus.
+ *
+ * The values multiply the ones set on the Bus itself, 1.0 leaves them
+ * unchanged.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BusSnapshot {
+    /// The volume factor, see `Bus::set_volume`
+    pub volume: f32,
+    /// The pitch factor, see `Bus::set_pitch`
+    pub pitch: f32,
+    /// The factor of the reverb send of the Audio Sources of the Bus, see
+    /// `AudioController::set_reverb_send`
+    pub reverb_send: f32,
+}
+
+impl Default for BusSnapshot {
+    fn default() -> BusSnapshot {
+        BusSnapshot {
+            volume: 1.,
+            pitch: 1.,
+            reverb_send: 1.,
+        }
+    }
+}
+
+struct Snapshot {
+    name: String,
+    priority: i32,
+    blend: SnapshotBlend,
+    buses: Vec<(Bus, BusSnapshot)>,
+    /// The target gain of auxiliary effect slots
+    effects: Vec<(u32, f32)>,
+    /// How much the snapshot is applied, in the range [0.0, 1.0]
+    weight: f32,
+    active: bool,
+    fade: Duration,
+}
+
+struct Snapshots {
+    snapshots: HashMap<u64, Snapshot>,
+    /// The Buses and effect slots with values set by the snapshots
+    buses: Vec<Bus>,
+    effects: Vec<u32>,
+}
+
+lazy_static! {
+    static ref SNAPSHOTS: Mutex<Snapshots> = Mutex::new(Snapshots {
+        snapshots: HashMap::new(),
+        buses: Vec::new(),
+        effects: Vec::new(),
+    });
+    static ref NEXT_SNAPSHOT: AtomicU64 = AtomicU64::new(0);
+}
+
+/// The targets of the snapshots defining a value, sorted by priority, as
+/// (blend, weight, target).
+type Targets<T> = Vec<(SnapshotBlend, f32, T)>;
+
+/// Blend the targets of the snapshots defining a value.
+fn blend(targets: &[(SnapshotBlend, f32, f32)]) -> f32 {
+    let mut value = 1.;
+    for (_, weight, target) in targets
+        .iter()
+        .filter(|(blend, _, _)| *blend == SnapshotBlend::Override)
+    {
+        value += (target - value) * weight;
+    }
+    for (_, weight, target) in targets
+        .iter()
+        .filter(|(blend, _, _)| *blend == SnapshotBlend::Additive)
+    {
+        value *= 1. + (target - 1.) * weight;
+    }
+    value
+}
+
+/// Apply the blended values of the snapshots to their Buses and effect
+/// slots.
+fn apply(snapshots: &mut Snapshots) {
+    let mut sorted: Vec<(&u64, &Snapshot)> = snapshots.snapshots.iter().collect();
+    sorted.sort_by_key(|(id, snapshot)| (snapshot.priority, **id));
+
+    let mut buses: Vec<(Bus, Targets<BusSnapshot>)> = Vec::new();
+    let mut effects: Vec<(u32, Targets<f32>)> = Vec::new();
+    for (_, snapshot) in sorted.iter() {
+        for (bus, values) in snapshot.buses.iter() {
+            let entry = (snapshot.blend, snapshot.weight, *values);
+            match buses.iter_mut().find(|(other, _)| other == bus) {
+                Some((_, targets)) => targets.push(entry),
+                None => buses.push((bus.clone(), vec![entry])),
+            }
+        }
+        for (slot, gain) in snapshot.effects.iter() {
+            let entry = (snapshot.blend, snapshot.weight, *gain);
+            match effects.iter_mut().find(|(other, _)| other == slot) {
+                Some((_, targets)) => targets.push(entry),
+                None => effects.push((*slot, vec![entry])),
+            }
+        }
+    }
+
+    for bus in snapshots.buses.iter() {
+        if !buses.iter().any(|(other, _)| other == bus) {
+            bus.set_snapshot(1., 1., 1.);
+        }
+    }
+    for (bus, targets) in buses.iter() {
+        let value = |field: fn(&BusSnapshot) -> f32| {
+            let values: Targets<f32> = targets
+                .iter()
+                .map(|(blend, weight, values)| (*blend, *weight, field(values)))
+                .collect();
+            blend(&values)
+        };
+        bus.set_snapshot(
+            value(|values| values.volume),
+            value(|values| values.pitch),
+            value(|values| values.reverb_send),
+        );
+    }
+
+    for slot in snapshots.effects.iter() {
+        if !effects.iter().any(|(other, _)| other == slot) {
+            set_slot_gain(*slot, 1.);
+        }
+    }
+    for (slot, targets) in effects.iter() {
+        set_slot_gain(*slot, blend(targets));
+    }
+
+    snapshots.buses = buses.into_iter().map(|(bus, _)| bus).collect();
+    snapshots.effects = effects.into_iter().map(|(slot, _)| slot).collect();
+}
+
+/// Remove the gains the snapshots give to an effect slot about to be
+/// deleted, so an effect reusing the slot id doesn't get them.
+pub(crate) fn forget_effect(slot: u32) {
+    if let Ok(mut snapshots) = SNAPSHOTS.lock() {
+        for snapshot in snapshots.snapshots.values_mut() {
+            snapshot.effects.retain(|(other, _)| *other != slot);
+        }
+        snapshots.effects.retain(|other| *other != slot);
+    }
+}
+
+/// Set the gain of an effect slot, unless its ReverbEffect was dropped.
+fn set_slot_gain(slot: u32, gain: f32) {
+    if unsafe { ffi::alIsAuxiliaryEffectSlot(slot) } == ffi::AL_TRUE {
+        al::alAuxiliaryEffectSlotf(slot, ffi::AL_EFFECTSLOT_GAIN, gain);
+    }
+}
+
+/**
+ * A named set of target values for Buses and ReverbEffects.
+ *
+ * Activating a MixSnapshot blends the values it holds in over a duration,
+ * deactivating it blends them out. Several snapshots can be active at once:
+ * the `Override` ones are blended in order of priority, the highest one
+ * winning once fully active, then the `Additive` ones are multiplied on
+ * top. The transitions progress when `ears::update` is called, or with the
+ * background ticker.
+ *
+ * Dropping the MixSnapshot removes its values.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{Bus, BusSnapshot, MixSnapshot};
+ * use std::time::Duration;
+ *
+ * let music = Bus::new();
+ * let sfx = Bus::new();
+ *
+ * // Muffle the game while the pause menu is open
+ * let paused = MixSnapshot::new("paused");
+ * paused.set_bus(&music, BusSnapshot { volume: 0.5, ..Default::default() });
+ * paused.set_bus(&sfx, BusSnapshot { volume: 0.2, pitch: 0.8, reverb_send: 0. });
+ *
+ * ears::start_ticker(Duration::from_millis(10));
+ * paused.activate(Duration::from_millis(500));
+ * ```
+ */
+#[derive(Debug)]
+pub struct MixSnapshot {
+    id: u64,
+}
+
+impl MixSnapshot {
+    /**
+     * Create an inactive MixSnapshot without values.
+     *
+     * Its priority defaults to 0 and its blending to `Override`.
+     *
+     * # Argument
+     * * `name` - The name of the snapshot
+     */
+    pub fn new(name: &str) -> MixSnapshot {
+        let id = NEXT_SNAPSHOT.fetch_add(1, Ordering::Relaxed);
+
+        if let Ok(mut snapshots) = SNAPSHOTS.lock() {
+            snapshots.snapshots.insert(
+                id,
+                Snapshot {
+                    name: name.to_string(),
+                    priority: 0,
+                    blend: SnapshotBlend::Override,
+                    buses: Vec::new(),
+                    effects: Vec::new(),
+                    weight: 0.,
+                    active: false,
+                    fade: Duration::from_secs(0),
+                },
+            );
+        }
+
+        MixSnapshot { id }
+    }
+
+    fn update<F: FnOnce(&mut Snapshot)>(&self, change: F) {
+        check_openal_context!(());
+
+        if let Ok(mut snapshots) = SNAPSHOTS.lock() {
+            if let Some(snapshot) = snapshots.snapshots.get_mut(&self.id) {
+                change(snapshot);
+            }
+            apply(&mut snapshots);
+        }
+    }
+
+    fn read<T, F: FnOnce(&Snapshot) -> T>(&self, read: F, default: T) -> T {
+        match SNAPSHOTS.lock() {
+            Ok(snapshots) => snapshots
+                .snapshots
+                .get(&self.id)
+                .map(read)
+                .unwrap_or(default),
+            Err(_) => default,
+        }
+    }
+
+    /// Get the name of the MixSnapshot.
+    pub fn name(&self) -> String {
+        self.read(|snapshot| snapshot.name.clone(), String::new())
+    }
+
+    /**
+     * Set the values the MixSnapshot gives to a Bus.
+     *
+     * The values apply to the Audio Sources of the Bus and of its children.
+     *
+     * # Arguments
+     * * `bus` - The Bus
+     * * `values` - The volume, pitch and reverb send factors of the Bus
+     */
+    pub fn set_bus(&self, bus: &Bus, values: BusSnapshot) {
+        self.update(|snapshot| {
+            snapshot.buses.retain(|(other, _)| other != bus);
+            snapshot.buses.push((bus.clone(), values));
+        });
+    }
+
+    /**
+     * Remove the values of a Bus from the MixSnapshot.
+     *
+     * # Argument
+     * * `bus` - The Bus
+     */
+    pub fn remove_bus(&self, bus: &Bus) {
+        self.update(|snapshot| snapshot.buses.retain(|(other, _)| other != bus));
+    }
+
+    /**
+     * Set the output gain the MixSnapshot gives to a ReverbEffect.
+     *
+     * # Arguments
+     * * `effect` - The ReverbEffect
+     * * `gain` - The gain of the wet signal of the effect, 1.0 by default
+     */
+    pub fn set_effect_gain(&self, effect: &ReverbEffect, gain: f32) {
+        let slot = effect.slot();
+        self.update(|snapshot| {
+            snapshot.effects.retain(|(other, _)| *other != slot);
+            snapshot.effects.push((slot, gain));
+        });
+    }
+
+    /**
+     * Remove the gain of a ReverbEffect from the MixSnapshot.
+     *
+     * # Argument
+     * * `effect` - The ReverbEffect
+     */
+    pub fn remove_effect(&self, effect: &ReverbEffect) {
+        let slot = effect.slot();
+        self.update(|snapshot| snapshot.effects.retain(|(other, _)| *other != slot));
+    }
+
+    /**
+     * Set the priority of the MixSnapshot.
+     *
+     * Among the active `Override` snapshots, the ones with a higher
+     * priority are blended last and win.
+     *
+     * # Argument
+     * * `priority` - The new priority
+     */
+    pub fn set_priority(&self, priority: i32) {
+        self.update(|snapshot| snapshot.priority = priority);
+    }
+
+    /// Get the priority of the MixSnapshot.
+    pub fn get_priority(&self) -> i32 {
+        self.read(|snapshot| snapshot.priority, 0)
+    }
+
+    /**
+     * Set how the MixSnapshot combines with the other active ones.
+     *
+     * # Argument
+     * * `blend` - The new blending
+     */
+    pub fn set_blend(&self, blend: SnapshotBlend) {
+        self.update(|snapshot| snapshot.blend = blend);
+    }
+
+    /// Get how the MixSnapshot combines with the other active ones.
+    pub fn get_blend(&self) -> SnapshotBlend {
+        self.read(|snapshot| snapshot.blend, SnapshotBlend::Override)
+    }
+
+    /**
+     * Blend the values of the MixSnapshot in.
+     *
+     * # Argument
+     * * `duration` - The time to reach the values, 0 to apply them at once
+     */
+    pub fn activate(&self, duration: Duration) {
+        self.update(|snapshot| {
+            snapshot.active = true;
+            snapshot.fade = duration;
+            if duration == Duration::from_secs(0) {
+                snapshot.weight = 1.;
+            }
+        });
+    }
+
+    /**
+     * Blend the values of the MixSnapshot out.
+     *
+     * # Argument
+     * * `duration` - The time to go back to the values without the
+     *   snapshot, 0 to remove them at once
+     */
+    pub fn deactivate(&self, duration: Duration) {
+        self.update(|snapshot| {
+            snapshot.active = false;
+            snapshot.fade = duration;
+            if duration == Duration::from_secs(0) {
+                snapshot.weight = 0.;
+            }
+        });
+    }
+
+    /// Check if the MixSnapshot is active, even if still blending in.
+    pub fn is_active(&self) -> bool {
+        self.read(|snapshot| snapshot.active, false)
+    }
+
+    /**
+     * Get how much the MixSnapshot is applied.
+     *
+     * # Return
+     * The weight of the snapshot, 0.0 when inactive and 1.0 when fully
+     * active
+     */
+    pub fn get_weight(&self) -> f32 {
+        self.read(|snapshot| snapshot.weight, 0.)
+    }
+}
+
+impl Drop for MixSnapshot {
+    fn drop(&mut self) {
+        if let Ok(mut snapshots) = SNAPSHOTS.lock() {
+            snapshots.snapshots.remove(&self.id);
+            apply(&mut snapshots);
+        }
+    }
+}
+
+/// Advance the transitions of the snapshots by `dt`.
+pub(crate) fn update(dt: Duration) {
+    check_openal_context!(());
+
+    if let Ok(mut snapshots) = SNAPSHOTS.lock() {
+        let mut changed = false;
+        for snapshot in snapshots.snapshots.values_mut() {
+            let target = if snapshot.active { 1. } else { 0. };
+            if snapshot.weight != target {
+                let direction = if snapshot.active { 1. } else { -1. };
+                snapshot.weight = step(snapshot.weight, dt, snapshot.fade, direction);
+                changed = true;
+            }
+        }
+        if changed {
+            apply(&mut snapshots);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::snapshot::{blend, SnapshotBlend};
+
+    #[test]
+    fn snapshot_blend_OK() {
+        let half_override = (SnapshotBlend::Override, 0.5, 0.25);
+        let full_override = (SnapshotBlend::Override, 1., 0.5);
+        let additive = (SnapshotBlend::Additive, 1., 0.5);
+
+        assert_eq!(blend(&[]), 1.);
+        assert_eq!(blend(&[half_override]), 0.625);
+        assert_eq!(blend(&[half_override, full_override]), 0.5);
+        assert_eq!(blend(&[full_override, half_override]), 0.375);
+        assert_eq!(blend(&[additive, full_override]), 0.25);
+    }
+}
@@ -27,14 +27,24 @@ use std::time::Duration;
 
 use crate::audio_controller::AudioController;
 use crate::audio_tags::{AudioTags, Tags};
+use crate::automation::PlaybackClock;
+use crate::clock;
+use crate::cone::Cone;
+use crate::distance_model;
 use crate::error::SoundError;
 use crate::internal::OpenAlData;
+use crate::mixer;
 use crate::openal::{al, ffi};
+use crate::resampler;
 use crate::reverb_effect::ReverbEffect;
+use crate::rolloff;
+use crate::sends;
 use crate::sound_data; //::*;//{SoundData};
 use crate::sound_data::SoundData;
+use crate::spatialize::{Spatialize, DEFAULT_STEREO_ANGLES};
 use crate::states::State;
 use crate::states::State::{Initial, Paused, Playing, Stopped};
+use crate::ticker;
 
 /**
  * Play Sounds easily.
@@ -127,6 +137,8 @@ impl Sound {
         let mut source_id = 0;
         // create the source
         al::alGenSources(1, &mut source_id);
+        distance_model::register(source_id);
+        resampler::register(source_id);
         // set the buffer
         al::alSourcei(
             source_id,
@@ -232,36 +244,6 @@ impl Sound {
 
         factor
     }
-
-    /**
-     * Set the velocity of a Sound.
-     *
-     * Default velocity is [0.0, 0.0, 0.0].
-     *
-     * # Argument
-     * * `velocity` - A three dimensional vector of f32 containing the velocity
-     * of the sound [x, y, z].
-     */
-    pub fn set_velocity(&mut self, velocity: [f32; 3]) -> () {
-        check_openal_context!(());
-
-        al::alSourcefv(self.al_source, ffi::AL_VELOCITY, &velocity[0]);
-    }
-
-    /**
-     * Get the velocity of a Sound.
-     *
-     * # Return
-     * A three dimensional vector of f32 containing the velocity
-     * of the sound [x, y, z].
-     */
-    pub fn get_velocity(&self) -> [f32; 3] {
-        check_openal_context!([0.0; 3]);
-
-        let mut velocity: [f32; 3] = [0.0; 3];
-        al::alGetSourcefv(self.al_source, ffi::AL_VELOCITY, &mut velocity[0]);
-        velocity
-    }
 }
 
 impl AudioTags for Sound {
@@ -294,7 +276,9 @@ impl AudioController for Sound {
     fn play(&mut self) -> () {
         check_openal_context!(());
 
-        al::alSourcePlay(self.al_source);
+        // Replaces a pending `play_at`
+        self.cue();
+        clock::start(&[self.al_source], None);
 
         match al::openal_has_error() {
             None => {}
@@ -343,6 +327,7 @@ impl AudioController for Sound {
     fn stop(&mut self) -> () {
         check_openal_context!(());
 
+        clock::cancel(self.al_source);
         al::alSourceStop(self.al_source)
     }
 
@@ -364,26 +349,11 @@ impl AudioController for Sound {
     fn connect(&mut self, reverb_effect: &Option<ReverbEffect>) {
         check_openal_context!(());
 
-        match reverb_effect {
-            Some(reverb_effect) => {
-                al::alSource3i(
-                    self.al_source,
-                    ffi::AL_AUXILIARY_SEND_FILTER,
-                    reverb_effect.slot() as i32,
-                    0,
-                    ffi::AL_FILTER_NULL,
-                );
-            }
-            None => {
-                al::alSource3i(
-                    self.al_source,
-                    ffi::AL_AUXILIARY_SEND_FILTER,
-                    ffi::AL_EFFECTSLOT_NULL,
-                    0,
-                    ffi::AL_FILTER_NULL,
-                );
-            }
-        }
+        sends::connect(
+            self.al_source,
+            0,
+            reverb_effect.as_ref().map(|effect| effect.slot()),
+        );
     }
 
     /**
@@ -492,7 +462,7 @@ impl AudioController for Sound {
     fn set_volume(&mut self, volume: f32) -> () {
         check_openal_context!(());
 
-        al::alSourcef(self.al_source, ffi::AL_GAIN, volume);
+        mixer::set_volume(self.al_source, volume);
     }
 
     /**
@@ -504,9 +474,7 @@ impl AudioController for Sound {
     fn get_volume(&self) -> f32 {
         check_openal_context!(0.);
 
-        let mut volume: f32 = 0.;
-        al::alGetSourcef(self.al_source, ffi::AL_GAIN, &mut volume);
-        volume
+        mixer::volume(self.al_source)
     }
 
     /**
@@ -618,7 +586,7 @@ impl AudioController for Sound {
     fn set_pitch(&mut self, pitch: f32) -> () {
         check_openal_context!(());
 
-        al::alSourcef(self.al_source, ffi::AL_PITCH, pitch)
+        mixer::set_pitch(self.al_source, pitch)
     }
 
     /**
@@ -630,9 +598,7 @@ impl AudioController for Sound {
     fn get_pitch(&self) -> f32 {
         check_openal_context!(0.);
 
-        let mut pitch = 0.;
-        al::alGetSourcef(self.al_source, ffi::AL_PITCH, &mut pitch);
-        pitch
+        mixer::pitch(self.al_source)
     }
 
     /**
@@ -667,7 +633,7 @@ impl AudioController for Sound {
      * # Return
      * True if the sound is relative to the listener false otherwise
      */
-    fn is_relative(&mut self) -> bool {
+    fn is_relative(&self) -> bool {
         check_openal_context!(false);
 
         let mut boolean = 0;
@@ -716,6 +682,39 @@ impl AudioController for Sound {
         position
     }
 
+    /**
+     * Set the velocity of the Sound.
+     *
+     * The velocity is only used for the Doppler effect, it doesn't move the
+     * Sound. See `listener::set_doppler_factor`.
+     *
+     * Default velocity is [0.0, 0.0, 0.0].
+     *
+     * # Argument
+     * * `velocity` - A three dimensional vector of f32 containing the velocity
+     *   of the sound [x, y, z].
+     */
+    fn set_velocity(&mut self, velocity: [f32; 3]) {
+        check_openal_context!(());
+
+        al::alSourcefv(self.al_source, ffi::AL_VELOCITY, &velocity[0]);
+    }
+
+    /**
+     * Get the velocity of the Sound.
+     *
+     * # Return
+     * A three dimensional vector of f32 containing the velocity
+     * of the sound [x, y, z].
+     */
+    fn get_velocity(&self) -> [f32; 3] {
+        check_openal_context!([0.0; 3]);
+
+        let mut velocity: [f32; 3] = [0.0; 3];
+        al::alGetSourcefv(self.al_source, ffi::AL_VELOCITY, &mut velocity[0]);
+        velocity
+    }
+
     /**
      * Set the direction of the Sound.
      *
@@ -746,6 +745,58 @@ impl AudioController for Sound {
         direction
     }
 
+    /**
+     * Set the sound cone of the Sound.
+     *
+     * The cone only has an effect once a direction is set.
+     *
+     * The default cone is omnidirectional, see `Cone::default`.
+     *
+     * # Argument
+     * `cone` - The new cone of the Sound.
+     */
+    fn set_cone(&mut self, cone: Cone) {
+        check_openal_context!(());
+
+        al::alSourcef(self.al_source, ffi::AL_CONE_INNER_ANGLE, cone.inner_angle);
+        al::alSourcef(self.al_source, ffi::AL_CONE_OUTER_ANGLE, cone.outer_angle);
+        al::alSourcef(self.al_source, ffi::AL_CONE_OUTER_GAIN, cone.outer_gain);
+        al::alSourcef(self.al_source, ffi::AL_CONE_OUTER_GAINHF, cone.outer_gainhf);
+    }
+
+    /**
+     * Get the sound cone of the Sound.
+     *
+     * # Return
+     * The current cone of the Sound.
+     */
+    fn get_cone(&self) -> Cone {
+        check_openal_context!(Cone::default());
+
+        let mut cone = Cone::default();
+        al::alGetSourcef(
+            self.al_source,
+            ffi::AL_CONE_INNER_ANGLE,
+            &mut cone.inner_angle,
+        );
+        al::alGetSourcef(
+            self.al_source,
+            ffi::AL_CONE_OUTER_ANGLE,
+            &mut cone.outer_angle,
+        );
+        al::alGetSourcef(
+            self.al_source,
+            ffi::AL_CONE_OUTER_GAIN,
+            &mut cone.outer_gain,
+        );
+        al::alGetSourcef(
+            self.al_source,
+            ffi::AL_CONE_OUTER_GAINHF,
+            &mut cone.outer_gainhf,
+        );
+        cone
+    }
+
     /**
      * Set the maximum distance of the Sound.
      *
@@ -753,6 +804,11 @@ impl AudioController for Sound {
      * clamped distance model, or where attenuation reaches 0.0 gain for linear
      * distance models with a default rolloff factor.
      *
+     * The attenuation depends on the distance model of the Sound, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     * While a rolloff curve is set, the attenuation is kept aside and only
+     * applies once the curve is removed, see `set_rolloff_curve`.
+     *
      * The default maximum distance is +inf.
      *
      * # Argument
@@ -785,6 +841,11 @@ impl AudioController for Sound {
      * At 0.0, no distance attenuation ever occurs on non-linear attenuation
      * models.
      *
+     * The attenuation depends on the distance model of the Sound, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     * While a rolloff curve is set, the attenuation is kept aside and only
+     * applies once the curve is removed, see `set_rolloff_curve`.
+     *
      * The default distance reference is 1.
      *
      * # Argument
@@ -820,6 +881,11 @@ impl AudioController for Sound {
      * Multiplier to exaggerate or diminish distance attenuation.
      * At 0.0, no distance attenuation ever occurs.
      *
+     * The attenuation depends on the distance model of the Sound, see
+     * `listener::set_distance_model` and `set_distance_model`.
+     * While a rolloff curve is set, the attenuation is kept aside and only
+     * applies once the curve is removed, see `set_rolloff_curve`.
+     *
      * The default attenuation is 1.
      *
      * # Arguments
@@ -828,7 +894,7 @@ impl AudioController for Sound {
     fn set_attenuation(&mut self, attenuation: f32) -> () {
         check_openal_context!(());
 
-        al::alSourcef(self.al_source, ffi::AL_ROLLOFF_FACTOR, attenuation);
+        rolloff::set_attenuation(self.al_source, attenuation);
     }
 
     /**
@@ -840,9 +906,7 @@ impl AudioController for Sound {
     fn get_attenuation(&self) -> f32 {
         check_openal_context!(1.);
 
-        let mut attenuation = 0.;
-        al::alGetSourcef(self.al_source, ffi::AL_ROLLOFF_FACTOR, &mut attenuation);
-        attenuation
+        rolloff::attenuation(self.al_source)
     }
 
     /**
@@ -905,6 +969,142 @@ impl AudioController for Sound {
         }
     }
 
+    /**
+     * Set whether the Sound is positioned in the scene.
+     *
+     * With `Spatialize::On` a stereo or multichannel Sound is positioned
+     * like a mono one, its channels placed around its position, instead of
+     * playing on the matching speakers. Applies only when the
+     * AL_SOFT_source_spatialize extension exists.
+     *
+     * [https://openal-soft.org/openal-extensions/SOFT_source_spatialize.txt]()
+     *
+     * The default is `Spatialize::Auto`.
+     *
+     * # Argument
+     * * `spatialize` - The new spatialization mode
+     */
+    fn set_spatialize(&mut self, spatialize: Spatialize) {
+        check_openal_context!(());
+
+        if OpenAlData::source_spatialize_capable() {
+            al::alSourcei(
+                self.al_source,
+                ffi::AL_SOURCE_SPATIALIZE_SOFT,
+                spatialize.to_al(),
+            );
+        }
+    }
+
+    /**
+     * Get whether the Sound is positioned in the scene.
+     *
+     * Will always return `Spatialize::Auto` if the AL_SOFT_source_spatialize
+     * extension is not present.
+     *
+     * # Return
+     * The current spatialization mode
+     */
+    fn get_spatialize(&self) -> Spatialize {
+        check_openal_context!(Spatialize::Auto);
+
+        if !OpenAlData::source_spatialize_capable() {
+            return Spatialize::Auto;
+        }
+
+        let mut spatialize = 0;
+        al::alGetSourcei(
+            self.al_source,
+            ffi::AL_SOURCE_SPATIALIZE_SOFT,
+            &mut spatialize,
+        );
+        Spatialize::from_al(spatialize)
+    }
+
+    /**
+     * Set the angles of the channels of a stereo Sound.
+     *
+     * The angles are in radians, counter-clockwise from the front of the
+     * listener: positive angles are on the left. Applies only when the
+     * AL_EXT_STEREO_ANGLES extension exists and when playing stereo
+     * buffers which aren't spatialized (see `set_spatialize`).
+     *
+     * The default is [PI / 6, -PI / 6], 30 degrees on each side.
+     *
+     * # Argument
+     * * `angles` - The angles of the [left, right] channels
+     */
+    fn set_stereo_angles(&mut self, angles: [f32; 2]) {
+        check_openal_context!(());
+
+        if OpenAlData::stereo_angles_capable() {
+            al::alSourcefv(self.al_source, ffi::AL_STEREO_ANGLES, &angles[0]);
+        }
+    }
+
+    /**
+     * Get the angles of the channels of a stereo Sound.
+     *
+     * Will always return the default angles if the AL_EXT_STEREO_ANGLES
+     * extension is not present.
+     *
+     * # Return
+     * The angles of the [left, right] channels, in radians
+     */
+    fn get_stereo_angles(&self) -> [f32; 2] {
+        check_openal_context!(DEFAULT_STEREO_ANGLES);
+
+        if !OpenAlData::stereo_angles_capable() {
+            return DEFAULT_STEREO_ANGLES;
+        }
+
+        let mut angles = [0.; 2];
+        al::alGetSourcefv(self.al_source, ffi::AL_STEREO_ANGLES, &mut angles[0]);
+        angles
+    }
+
+    /**
+     * Set the radius of the Sound.
+     *
+     * A Sound with a radius is a sphere instead of a point: as the listener
+     * gets inside the sphere the sound spreads around them instead of coming
+     * from a single direction. Applies only when the AL_EXT_SOURCE_RADIUS
+     * extension exists.
+     *
+     * The default radius is 0.0.
+     *
+     * # Argument
+     * * `radius` - The new radius, in the range [0.0, +inf]
+     */
+    fn set_radius(&mut self, radius: f32) {
+        check_openal_context!(());
+
+        if OpenAlData::source_radius_capable() {
+            al::alSourcef(self.al_source, ffi::AL_SOURCE_RADIUS, radius);
+        }
+    }
+
+    /**
+     * Get the radius of the Sound.
+     *
+     * Will always return 0.0 if the AL_EXT_SOURCE_RADIUS extension is not
+     * present.
+     *
+     * # Return
+     * The current radius of the Sound
+     */
+    fn get_radius(&self) -> f32 {
+        check_openal_context!(0.);
+
+        if !OpenAlData::source_radius_capable() {
+            return 0.;
+        }
+
+        let mut radius = 0.;
+        al::alGetSourcef(self.al_source, ffi::AL_SOURCE_RADIUS, &mut radius);
+        radius
+    }
+
     /**
      * Returns the duration of the Sound.
      */
@@ -920,12 +1120,28 @@ impl AudioController for Sound {
 
         Duration::new(seconds, nanoseconds as u32)
     }
+
+    #[doc(hidden)]
+    fn al_source(&self) -> u32 {
+        self.al_source
+    }
+
+    #[doc(hidden)]
+    fn playback_clock(&self) -> PlaybackClock {
+        PlaybackClock::Source(self.al_source)
+    }
+
+    #[doc(hidden)]
+    fn cue(&mut self) {
+        // The buffer is already attached to the source
+    }
 }
 
 //#[unsafe_destructor]
 impl Drop for Sound {
     ///Destroy all the resources attached to the Sound.
     fn drop(&mut self) -> () {
+        ticker::forget_source(self.al_source);
         unsafe {
             ffi::alDeleteSources(1, &mut self.al_source);
         }
@@ -936,9 +1152,11 @@ impl Drop for Sound {
 mod test {
     #![allow(non_snake_case)]
 
-    use audio_controller::AudioController;
-    use sound::Sound;
-    use states::State::{Paused, Playing, Stopped};
+    use crate::audio_controller::AudioController;
+    use crate::cone::Cone;
+    use crate::sound::Sound;
+    use crate::spatialize::Spatialize;
+    use crate::states::State::{Paused, Playing, Stopped};
 
     #[test]
     #[ignore]
@@ -1036,6 +1254,32 @@ mod test {
         assert_eq!(snd.get_volume(), -1.);
     }
 
+    #[test]
+    #[ignore]
+    fn sound_try_set_volume_OK() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+
+        assert!(snd.try_set_volume(0.7).is_ok());
+        assert_eq!(snd.get_volume(), 0.7);
+    }
+
+    #[test]
+    #[ignore]
+    fn sound_try_set_volume_low_FAIL() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+
+        assert!(snd.try_set_volume(-1.).is_err());
+    }
+
+    #[test]
+    #[ignore]
+    fn sound_try_set_volume_infinite_FAIL() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+
+        assert!(snd.try_set_volume(f32::INFINITY).is_err());
+        assert_eq!(snd.get_volume(), 1.);
+    }
+
     #[test]
     #[ignore]
     fn sound_set_min_volume_OK() -> () {
@@ -1181,6 +1425,76 @@ mod test {
         assert_eq!([res[0], res[1], res[2]], [50f32, 150f32, 250f32]);
     }
 
+    #[test]
+    #[ignore]
+    fn sound_set_cone_OK() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+
+        let cone = Cone {
+            inner_angle: 45.,
+            outer_angle: 90.,
+            outer_gain: 0.5,
+            outer_gainhf: 0.25,
+        };
+        snd.set_cone(cone);
+        assert_eq!(snd.get_cone(), cone);
+    }
+
+    #[test]
+    #[ignore]
+    fn sound_set_velocity_OK() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+
+        snd.set_velocity([50., 150., 250.]);
+        let res = snd.get_velocity();
+        assert_eq!([res[0], res[1], res[2]], [50f32, 150f32, 250f32]);
+    }
+
+    #[test]
+    #[ignore]
+    fn sound_set_spatialize_OK() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+
+        snd.set_spatialize(Spatialize::On);
+        assert_eq!(snd.get_spatialize(), Spatialize::On);
+        snd.set_stereo_angles([1., -1.]);
+        assert_eq!(snd.get_stereo_angles(), [1., -1.]);
+    }
+
+    #[test]
+    #[ignore]
+    fn sound_set_radius_OK() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+
+        snd.set_radius(2.5);
+        assert_eq!(snd.get_radius(), 2.5);
+    }
+
+    #[test]
+    #[ignore]
+    fn sound_connect_send_OK() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+        let effect = crate::ReverbEffect::new().expect("Cannot create effect");
+        let last = crate::max_auxiliary_sends() - 1;
+
+        snd.connect_send(last, Some(&effect), None);
+        snd.set_send_gain(last, 0.5);
+        assert_eq!(snd.get_send_slot(last), Some(effect.slot()));
+        assert_eq!(snd.get_send_gain(last), 0.5);
+        assert!(snd.try_connect_send(last + 1, Some(&effect), None).is_err());
+    }
+
+    #[test]
+    #[ignore]
+    fn sound_set_resampler_OK() {
+        let mut snd = Sound::new("res/shot.wav").expect("Cannot create sound");
+
+        for resampler in crate::resamplers() {
+            snd.set_resampler(&resampler);
+            assert_eq!(snd.get_resampler(), Some(resampler));
+        }
+    }
+
     #[test]
     #[ignore]
     fn sound_set_max_distance_OK() -> () {
@@ -28,6 +28,8 @@ use std::vec::Vec;
 use crate::audio_tags::{get_sound_tags, AudioTags, Tags};
 use crate::error::SoundError;
 use crate::internal::OpenAlData;
+use crate::oneshot;
+use crate::oneshot::{OneShotHandle, OneShotParams};
 use crate::openal::{al, ffi};
 use crate::sndfile::OpenMode::Read;
 use crate::sndfile::{SndFile, SndInfo};
@@ -139,6 +141,41 @@ impl SoundData {
 
         Ok(sound_data)
     }
+
+    /**
+     * Play the SoundData once, without keeping a Sound alive.
+     *
+     * The one-shot plays on a source taken from a pool shared by all the
+     * SoundDatas, and the source goes back to the pool as soon as the
+     * one-shot ends: when `ears::update` is called, by the background
+     * ticker, or when the next one-shot is played. Dropping the SoundData
+     * stops its one-shots.
+     *
+     * # Argument
+     * * `params` - The position, volume, pitch and reverb of the one-shot
+     *
+     * # Return
+     * A weak handle to adjust or stop the one-shot, which can be ignored,
+     * or `Err(SoundError)` if no OpenAL source is available
+     *
+     * # Example
+     * ```no_run
+     * use ears::{OneShotParams, SoundData, SoundError};
+     *
+     * fn main() -> Result<(), SoundError> {
+     *     let shot = SoundData::new("path/to/the/shot.wav")?;
+     *     shot.play_oneshot(OneShotParams {
+     *         position: [4., 0., -2.],
+     *         pitch: 1.1,
+     *         ..Default::default()
+     *     })?;
+     *     Ok(())
+     * }
+     * ```
+     */
+    pub fn play_oneshot(&self, params: OneShotParams) -> Result<OneShotHandle, SoundError> {
+        oneshot::play(self.al_buffer, &params)
+    }
 }
 
 /**
@@ -177,6 +214,7 @@ impl AudioTags for SoundData {
 impl Drop for SoundData {
     /// Destroy all the resources attached to the SoundData
     fn drop(&mut self) -> () {
+        oneshot::release_buffer(self.al_buffer);
         unsafe {
             ffi::alDeleteBuffers(1, &mut self.al_buffer);
         }
@@ -188,7 +226,7 @@ mod test {
     #![allow(non_snake_case)]
 
     #[allow(unused_variables)]
-    use sound_data::SoundData;
+    use crate::sound_data::SoundData;
 
     #[test]
     #[ignore]
@@ -204,4 +242,38 @@ mod test {
         #![allow(unused_variables)]
         let snd_data = SoundData::new("toto.wav").unwrap();
     }
+
+    #[test]
+    #[ignore]
+    fn sounddata_play_oneshot_OK() {
+        let snd_data = SoundData::new("res/shot.wav").unwrap();
+
+        let oneshot = snd_data.play_oneshot(Default::default()).unwrap();
+        assert!(oneshot.is_playing());
+        oneshot.stop();
+        assert!(!oneshot.is_playing());
+
+        // The source of the first one-shot is reused
+        let other = snd_data.play_oneshot(Default::default()).unwrap();
+        assert!(other != oneshot);
+        oneshot.set_volume(0.);
+        assert!(other.is_playing());
+    }
+
+    #[test]
+    #[ignore]
+    fn sounddata_oneshot_pool_OK() {
+        let snd_data = SoundData::new("res/shot.wav").unwrap();
+
+        // The released source is deleted instead of pooled
+        crate::set_max_oneshot_sources(0);
+        assert_eq!(crate::max_oneshot_sources(), 0);
+        let oneshot = snd_data.play_oneshot(Default::default()).unwrap();
+        oneshot.stop();
+        assert!(!oneshot.is_playing());
+
+        crate::set_max_oneshot_sources(16);
+        let other = snd_data.play_oneshot(Default::default()).unwrap();
+        assert!(other.is_playing());
+    }
 }
@@ -0,0 +1,201 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! A snapshot of all the properties of an Audio Source.
+
+use crate::area::Area;
+use crate::bus::Bus;
+use crate::cone::Cone;
+use crate::distance_model::DistanceModel;
+use crate::filter::Filter;
+use crate::resampler::Resampler;
+use crate::rolloff::RolloffCurve;
+use crate::spatialize::{Spatialize, DEFAULT_STEREO_ANGLES};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/**
+ * All the properties of an Audio Source, as exposed by AudioController.
+ *
+ * Get them with `AudioController::get_properties` and set them all at once
+ * with `AudioController::apply_properties`, to copy the configuration of a
+ * Sound or a Music to another one, or to save it with your game state.
+ *
+ * With the `serde` feature enabled, SourceProperties can be serialized and
+ * deserialized. The connected ReverbEffect isn't serialized, as it only
+ * makes sense while the effect exists.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{AudioController, Sound, SoundError};
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let mut snd1 = Sound::new("path/to/the/sound.ogg")?;
+ *     snd1.set_pitch(1.2);
+ *     snd1.set_position([3., 0., -2.]);
+ *
+ *     let mut snd2 = Sound::new("path/to/the/other/sound.ogg")?;
+ *     snd2.apply_properties(&snd1.get_properties());
+ *     Ok(())
+ * }
+ * ```
+ */
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SourceProperties {
+    /// The volume, see `AudioController::set_volume`
+    pub volume: f32,
+    /// The minimal volume, see `AudioController::set_min_volume`
+    pub min_volume: f32,
+    /// The maximal volume, see `AudioController::set_max_volume`
+    pub max_volume: f32,
+    /// The pitch, see `AudioController::set_pitch`
+    pub pitch: f32,
+    /// The position [x, y, z], see `AudioController::set_position`
+    pub position: [f32; 3],
+    /// The velocity [x, y, z], see `AudioController::set_velocity`
+    pub velocity: [f32; 3],
+    /// The direction [x, y, z], see `AudioController::set_direction`
+    pub direction: [f32; 3],
+    /// The sound cone, see `AudioController::set_cone`
+    pub cone: Cone,
+    /// The maximum distance, see `AudioController::set_max_distance`
+    pub max_distance: f32,
+    /// The reference distance, see `AudioController::set_reference_distance`
+    pub reference_distance: f32,
+    /// The attenuation, see `AudioController::set_attenuation`
+    pub attenuation: f32,
+    /// The distance model of the source, or None to follow the global
+    /// model, see `AudioController::set_distance_model`
+    pub distance_model: Option<DistanceModel>,
+    /// The custom attenuation curve, see `AudioController::set_rolloff_curve`
+    pub rolloff_curve: Option<RolloffCurve>,
+    /// The looping state, see `AudioController::set_looping`
+    pub looping: bool,
+    /// Whether the position is relative to the listener, see
+    /// `AudioController::set_relative`
+    pub relative: bool,
+    /// The direct channel mode, see `AudioController::set_direct_channel`
+    pub direct_channel: bool,
+    /// The spatialization mode, see `AudioController::set_spatialize`
+    pub spatialize: Spatialize,
+    /// The angles of the stereo channels in radians, see
+    /// `AudioController::set_stereo_angles`
+    pub stereo_angles: [f32; 2],
+    /// The radius, see `AudioController::set_radius`
+    pub radius: f32,
+    /// The Area the source is spread over, see `AudioController::set_area`
+    pub area: Option<Area>,
+    /// The resampler, see `AudioController::set_resampler`. Resamplers
+    /// depend on the OpenAL implementation, None keeps the current one
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub resampler: Option<Resampler>,
+    /// The Bus of the source, see `AudioController::set_bus`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub bus: Option<Bus>,
+    /// The auxiliary effect slot of the connected ReverbEffect (see
+    /// `ReverbEffect::slot`), or None if no ReverbEffect is connected.
+    /// Ignored while the send 0 follows the ReverbZones, or once the
+    /// ReverbEffect is dropped
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub reverb_slot: Option<u32>,
+    /// The gain of the reverb send, see `AudioController::set_reverb_send`
+    pub reverb_send: f32,
+    /// The Filter of the direct signal, see
+    /// `AudioController::set_direct_filter`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub direct_filter: Option<Filter>,
+    /// The Filter of the reverb send, see `AudioController::set_send_filter`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub send_filter: Option<Filter>,
+    /// The send following the ReverbZones, see
+    /// `AudioController::set_reverb_zone_send`
+    pub reverb_zone_send: Option<i32>,
+}
+
+impl Default for SourceProperties {
+    /// The default properties of an OpenAL source.
+    fn default() -> SourceProperties {
+        SourceProperties {
+            volume: 1.,
+            min_volume: 0.,
+            max_volume: 1.,
+            pitch: 1.,
+            position: [0.; 3],
+            velocity: [0.; 3],
+            direction: [0.; 3],
+            cone: Cone::default(),
+            max_distance: f32::MAX,
+            reference_distance: 1.,
+            attenuation: 1.,
+            distance_model: None,
+            rolloff_curve: None,
+            looping: false,
+            relative: false,
+            direct_channel: false,
+            spatialize: Spatialize::default(),
+            stereo_angles: DEFAULT_STEREO_ANGLES,
+            radius: 0.,
+            area: None,
+            resampler: None,
+            bus: None,
+            reverb_slot: None,
+            reverb_send: 1.,
+            direct_filter: None,
+            send_filter: None,
+            reverb_zone_send: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn source_properties_serde_OK() {
+        use crate::area::Area;
+        use crate::distance_model::DistanceModel;
+        use crate::source_properties::SourceProperties;
+
+        let properties = SourceProperties {
+            pitch: 1.5,
+            position: [1., 2., 3.],
+            velocity: [0., 0., -4.],
+            distance_model: Some(DistanceModel::InverseClamped),
+            looping: true,
+            area: Some(Area::Segment {
+                start: [0., 0., 0.],
+                end: [10., 0., 0.],
+            }),
+            reverb_send: 0.5,
+            reverb_zone_send: Some(1),
+            ..Default::default()
+        };
+
+        let text = serde_json::to_string(&properties).unwrap();
+        let read: SourceProperties = serde_json::from_str(&text).unwrap();
+        assert_eq!(read, properties);
+    }
+}
@@ -0,0 +1,90 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! The spatialization of the multichannel Audio Sources.
+
+use std::f32::consts::FRAC_PI_6;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::openal::ffi;
+
+/**
+ * Whether an Audio Source is positioned in the scene.
+ *
+ * By default OpenAL only positions mono Audio Sources: the channels of
+ * stereo and multichannel Audio Sources play on the matching speakers
+ * whatever their position. With the AL_SOFT_source_spatialize extension a
+ * multichannel Audio Source can be positioned too, each channel being
+ * placed around the position of the Audio Source.
+ *
+ * [https://openal-soft.org/openal-extensions/SOFT_source_spatialize.txt]()
+ *
+ * The default is `Auto`.
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Spatialize {
+    /// Never positioned, the channels play on the matching speakers.
+    Off,
+    /// Always positioned, whatever the number of channels.
+    On,
+    /// Positioned only when the Audio Source is mono.
+    #[default]
+    Auto,
+}
+
+impl Spatialize {
+    pub(crate) fn to_al(self) -> i32 {
+        match self {
+            Spatialize::Off => ffi::AL_FALSE as i32,
+            Spatialize::On => ffi::AL_TRUE as i32,
+            Spatialize::Auto => ffi::AL_AUTO_SOFT,
+        }
+    }
+
+    pub(crate) fn from_al(spatialize: i32) -> Spatialize {
+        match spatialize {
+            x if x == ffi::AL_FALSE as i32 => Spatialize::Off,
+            x if x == ffi::AL_TRUE as i32 => Spatialize::On,
+            _ => Spatialize::Auto,
+        }
+    }
+}
+
+/// The default angles of the left and right channels of a stereo Audio
+/// Source, in radians.
+pub(crate) const DEFAULT_STEREO_ANGLES: [f32; 2] = [FRAC_PI_6, -FRAC_PI_6];
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use crate::spatialize::Spatialize;
+
+    #[test]
+    fn spatialize_al_OK() {
+        for spatialize in [Spatialize::Off, Spatialize::On, Spatialize::Auto].iter() {
+            assert_eq!(Spatialize::from_al(spatialize.to_al()), *spatialize);
+        }
+    }
+}
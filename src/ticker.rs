@@ -0,0 +1,171 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Drive the time based features of __ears__.
+//!
+//! Fades, automations, rolloff curves, ducking, mix snapshots, reverb morphs
+//! and everything else evolving over time only progress when `update` is
+//! called. Call it yourself once per frame of your game loop, or let a
+//! background ticker call it for you with `start_ticker`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::area;
+use crate::automation;
+use crate::bus;
+use crate::clock;
+use crate::distance_model;
+use crate::ducking;
+use crate::fade;
+use crate::filter;
+use crate::internal::OpenAlData;
+use crate::mixer;
+use crate::oneshot;
+use crate::reverb_effect;
+use crate::reverb_zone;
+use crate::rolloff;
+use crate::sends;
+use crate::snapshot;
+
+struct Ticker {
+    running: Arc<AtomicBool>,
+    thread_handle: thread::JoinHandle<()>,
+}
+
+lazy_static! {
+    static ref TICKER: Mutex<Option<Ticker>> = Mutex::new(None);
+    // Held during an update, so the OpenAL errors it raises and clears don't
+    // mix with the ones checked by the try_ setters
+    pub(crate) static ref UPDATE: Mutex<()> = Mutex::new(());
+}
+
+/**
+ * Advance all the time based features by `dt`.
+ *
+ * # Argument
+ * * `dt` - The time elapsed since the last update
+ *
+ * # Example
+ * ```no_run
+ * use std::time::Duration;
+ *
+ * // In the game loop
+ * ears::update(Duration::from_millis(16));
+ * ```
+ */
+pub fn update(dt: Duration) {
+    check_openal_context!(());
+    let _update = UPDATE.lock();
+
+    fade::update(dt);
+    automation::update();
+    rolloff::update();
+    ducking::update(dt);
+    snapshot::update(dt);
+    reverb_zone::update();
+    reverb_effect::update(dt);
+    oneshot::update();
+}
+
+/**
+ * Start a background thread calling `update` periodically.
+ *
+ * Does nothing if the ticker is already running.
+ *
+ * # Argument
+ * * `interval` - The time to wait between two updates
+ *
+ * # Example
+ * ```no_run
+ * use std::time::Duration;
+ *
+ * ears::start_ticker(Duration::from_millis(10));
+ * ```
+ */
+pub fn start_ticker(interval: Duration) {
+    let mut ticker = match TICKER.lock() {
+        Ok(ticker) => ticker,
+        Err(_) => return,
+    };
+
+    if ticker.is_some() {
+        return;
+    }
+
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = running.clone();
+
+    let thread = thread::Builder::new().name(String::from("ears-ticker"));
+    let thread_handle = thread
+        .spawn(move || {
+            let mut last = Instant::now();
+            while running_clone.load(Ordering::Relaxed) {
+                sleep(interval);
+                let now = Instant::now();
+                update(now - last);
+                last = now;
+            }
+        })
+        .unwrap();
+
+    *ticker = Some(Ticker {
+        running,
+        thread_handle,
+    });
+}
+
+/**
+ * Stop the background thread started by `start_ticker`.
+ *
+ * Blocks until the thread has exited. Does nothing if the ticker isn't
+ * running.
+ */
+pub fn stop_ticker() {
+    let ticker = match TICKER.lock() {
+        Ok(mut ticker) => ticker.take(),
+        Err(_) => return,
+    };
+
+    if let Some(ticker) = ticker {
+        ticker.running.store(false, Ordering::Relaxed);
+        ticker.thread_handle.join();
+    }
+}
+
+/// Remove every reference to a source about to be deleted.
+pub(crate) fn forget_source(al_source: u32) {
+    area::forget(al_source);
+    clock::forget(al_source);
+    distance_model::forget(al_source);
+    fade::forget(al_source);
+    automation::forget(al_source);
+    rolloff::forget(al_source);
+    filter::forget(al_source);
+    bus::forget(al_source);
+    ducking::forget(al_source);
+    mixer::forget(al_source);
+    sends::forget(al_source);
+    reverb_zone::forget(al_source);
+}
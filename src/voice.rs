@@ -0,0 +1,748 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang (letang.jeremy@gmail.com)
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+//! Sharing a limited number of OpenAL sources between many sound instances.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use crate::audio_controller::AudioController;
+use crate::bus::Bus;
+use crate::internal::OpenAlData;
+use crate::listener;
+use crate::openal::{al, ffi};
+use crate::sound::Sound;
+use crate::sound_data;
+use crate::sound_data::SoundData;
+use crate::states::State::Stopped;
+
+/**
+ * The parameters of a sound instance played by a VoiceManager.
+ */
+#[derive(Debug, Clone)]
+pub struct VoiceParams {
+    /// The importance of the instance, higher priorities get an OpenAL
+    /// source first
+    pub priority: i32,
+    /// The volume, see `AudioController::set_volume`
+    pub volume: f32,
+    /// The pitch, see `AudioController::set_pitch`
+    pub pitch: f32,
+    /// The position [x, y, z], see `AudioController::set_position`
+    pub position: [f32; 3],
+    /// Whether the position is relative to the listener, see
+    /// `AudioController::set_relative`
+    pub relative: bool,
+    /// The looping state, see `AudioController::set_looping`
+    pub looping: bool,
+    /// The Bus of the instance, see `AudioController::set_bus`
+    pub bus: Option<Bus>,
+}
+
+impl Default for VoiceParams {
+    fn default() -> VoiceParams {
+        VoiceParams {
+            priority: 0,
+            volume: 1.,
+            pitch: 1.,
+            position: [0.; 3],
+            relative: false,
+            looping: false,
+            bus: None,
+        }
+    }
+}
+
+/// A sound instance played by a VoiceManager.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Voice(u64);
+
+struct VoiceState {
+    data: Rc<RefCell<SoundData>>,
+    params: VoiceParams,
+    /// The OpenAL source of the instance, None while it is virtual
+    sound: Option<Sound>,
+    /// The playback position of a virtual instance, in frames
+    offset: f64,
+    frames: f64,
+    samplerate: f64,
+    stereo: bool,
+}
+
+impl VoiceState {
+    /// How loud the instance is at the position of the listener.
+    ///
+    /// The distance attenuation is estimated with the default inverse
+    /// clamped distance model.
+    fn audibility(&self, listener: [f32; 3]) -> f32 {
+        let position = self.params.position;
+        let origin = if self.params.relative {
+            [0.; 3]
+        } else {
+            listener
+        };
+        let delta = [
+            position[0] - origin[0],
+            position[1] - origin[1],
+            position[2] - origin[2],
+        ];
+        let distance = (delta[0] * delta[0] + delta[1] * delta[1] + delta[2] * delta[2]).sqrt();
+        self.params.volume / distance.max(1.)
+    }
+}
+
+/**
+ * Play many sound instances with a limited number of OpenAL sources.
+ *
+ * OpenAL implementations only provide a limited number of sources, and
+ * creating a Sound fails once they are all used. A VoiceManager plays sound
+ * instances (Voices) without this limit: the instances with the highest
+ * priority, and then the loudest ones, get an OpenAL source, the others are
+ * virtual. A virtual Voice isn't heard but its playback position keeps
+ * moving, so it resumes in place as soon as it gets a source again.
+ *
+ * The number of instances of a SoundData can be limited too, a new instance
+ * replacing the oldest one with the lowest priority.
+ *
+ * The VoiceManager only progresses when `update` is called, once per frame
+ * of your game loop for example.
+ *
+ * # Example
+ * ```no_run
+ * use ears::{SoundData, SoundError, VoiceManager, VoiceParams};
+ * use std::cell::RefCell;
+ * use std::rc::Rc;
+ * use std::time::Duration;
+ *
+ * fn main() -> Result<(), SoundError> {
+ *     let footstep = Rc::new(RefCell::new(SoundData::new("path/to/the/footstep.ogg")?));
+ *
+ *     // Keep 32 sources for the Sounds and Musics created outside
+ *     let mut voices = VoiceManager::new();
+ *     voices.reserve(32);
+ *     voices.set_instance_limit(&footstep, Some(8));
+ *
+ *     let voice = voices.play(&footstep, VoiceParams {
+ *         position: [10., 0., 2.],
+ *         ..Default::default()
+ *     });
+ *
+ *     // In the game loop
+ *     voices.update(Duration::from_millis(16));
+ *     Ok(())
+ * }
+ * ```
+ */
+pub struct VoiceManager {
+    max_mono: usize,
+    max_stereo: usize,
+    threshold: f32,
+    voices: HashMap<u64, VoiceState>,
+    /// Sources which aren't used by a Voice anymore, ready to be reused
+    free: Vec<Sound>,
+    /// The maximal number of instances of each SoundData, the Weak keeps
+    /// the address from being reused by another SoundData
+    limits: Vec<(Weak<RefCell<SoundData>>, usize)>,
+    next_voice: u64,
+}
+
+impl VoiceManager {
+    /**
+     * Create a VoiceManager using all the sources of the device.
+     *
+     * The limits are the ALC_MONO_SOURCES and ALC_STEREO_SOURCES of the
+     * device. They are only hints, OpenAL Soft reports a single stereo
+     * source for instance, so the stereo Voices use mono sources once the
+     * stereo ones are exhausted. Use `reserve` to leave sources to the
+     * Sounds and Musics created outside of the VoiceManager.
+     */
+    pub fn new() -> VoiceManager {
+        let (mono, stereo) = match OpenAlData::check_al_context() {
+            Ok(_) => (
+                al::alcGetInteger(ffi::ALC_MONO_SOURCES).max(0) as usize,
+                al::alcGetInteger(ffi::ALC_STEREO_SOURCES).max(0) as usize,
+            ),
+            Err(err) => {
+                println!("{}", err);
+                (0, 0)
+            }
+        };
+        VoiceManager::with_limits(mono, stereo)
+    }
+
+    /**
+     * Create a VoiceManager with its own limits.
+     *
+     * # Arguments
+     * * `max_mono` - The maximal number of mono Voices playing at once
+     * * `max_stereo` - The maximal number of stereo or multichannel Voices
+     *   playing at once, more can play on the mono sources left
+     */
+    pub fn with_limits(max_mono: usize, max_stereo: usize) -> VoiceManager {
+        VoiceManager {
+            max_mono,
+            max_stereo,
+            threshold: 0.001,
+            voices: HashMap::new(),
+            free: Vec::new(),
+            limits: Vec::new(),
+            next_voice: 0,
+        }
+    }
+
+    /**
+     * Leave some mono sources to the Sounds and Musics created outside of
+     * the VoiceManager.
+     *
+     * # Argument
+     * * `sources` - The number of mono sources to leave
+     */
+    pub fn reserve(&mut self, sources: usize) {
+        self.max_mono = self.max_mono.saturating_sub(sources);
+    }
+
+    /**
+     * Get the limits of the VoiceManager.
+     *
+     * # Return
+     * The maximal number of (mono, stereo) Voices playing at once
+     */
+    pub fn limits(&self) -> (usize, usize) {
+        (self.max_mono, self.max_stereo)
+    }
+
+    /**
+     * Set the audibility under which a Voice stays virtual, even if a source
+     * is available.
+     *
+     * The audibility of a Voice is its volume attenuated by its distance to
+     * the listener, estimated with the default distance model.
+     *
+     * Default threshold is 0.001.
+     *
+     * # Argument
+     * * `threshold` - The new threshold, 0.0 to only virtualize when out of
+     *   sources
+     */
+    pub fn set_audibility_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /**
+     * Limit the number of instances of a SoundData playing at once, audible
+     * or virtual.
+     *
+     * # Arguments
+     * * `data` - The SoundData to limit
+     * * `limit` - The maximal number of instances, or None for no limit
+     */
+    pub fn set_instance_limit(&mut self, data: &Rc<RefCell<SoundData>>, limit: Option<usize>) {
+        // Forget the previous limit and the ones of the dropped SoundDatas
+        let key = Rc::downgrade(data);
+        self.limits
+            .retain(|(weak, _)| weak.strong_count() > 0 && !Weak::ptr_eq(weak, &key));
+        if let Some(limit) = limit {
+            self.limits.push((key, limit));
+        }
+    }
+
+    // The instance limit of a SoundData
+    fn limit_of(&self, data: &Rc<RefCell<SoundData>>) -> Option<usize> {
+        self.limits
+            .iter()
+            .find(|(weak, _)| Weak::ptr_eq(weak, &Rc::downgrade(data)))
+            .map(|&(_, limit)| limit)
+    }
+
+    /**
+     * Play a new instance of a SoundData.
+     *
+     * If the SoundData already has as many instances as its limit, the
+     * oldest instance with the lowest priority is stopped, unless its
+     * priority is higher than the new one.
+     *
+     * # Arguments
+     * * `data` - The SoundData to play
+     * * `params` - The parameters of the instance
+     *
+     * # Return
+     * The new Voice, or None if the instance limit was reached by instances
+     * with a higher priority.
+     */
+    pub fn play(&mut self, data: &Rc<RefCell<SoundData>>, params: VoiceParams) -> Option<Voice> {
+        if let Some(limit) = self.limit_of(data) {
+            let mut instances: Vec<(u64, i32)> = self
+                .voices
+                .iter()
+                .filter(|(_, voice)| Rc::ptr_eq(&voice.data, data))
+                .map(|(id, voice)| (*id, voice.params.priority))
+                .collect();
+
+            if instances.len() >= limit {
+                // The lowest priority first, then the oldest
+                instances.sort_by_key(|&(id, priority)| (priority, id));
+                let replaced: Vec<u64> = instances
+                    .iter()
+                    .take(instances.len() + 1 - limit)
+                    .filter(|&&(_, priority)| priority <= params.priority)
+                    .map(|&(id, _)| id)
+                    .collect();
+                if limit == 0 || replaced.len() < instances.len() + 1 - limit {
+                    return None;
+                }
+                for id in replaced {
+                    self.stop(Voice(id));
+                }
+            }
+        }
+
+        let (frames, samplerate, stereo) = {
+            let data = data.borrow();
+            let info = sound_data::get_sndinfo(&data);
+            (
+                info.frames as f64,
+                info.samplerate as f64,
+                info.channels > 1,
+            )
+        };
+
+        let id = self.next_voice;
+        self.next_voice += 1;
+        self.voices.insert(
+            id,
+            VoiceState {
+                data: data.clone(),
+                params,
+                sound: None,
+                offset: 0.,
+                frames,
+                samplerate,
+                stereo,
+            },
+        );
+
+        self.assign();
+        Some(Voice(id))
+    }
+
+    /**
+     * Stop a Voice.
+     *
+     * Its source is released for the other Voices. Does nothing if the Voice
+     * already ended.
+     *
+     * # Argument
+     * * `voice` - The Voice to stop
+     */
+    pub fn stop(&mut self, voice: Voice) {
+        if let Some(state) = self.voices.remove(&voice.0) {
+            if let Some(sound) = state.sound {
+                self.release(sound);
+            }
+        }
+    }
+
+    /// Stop all the Voices.
+    pub fn stop_all(&mut self) {
+        let voices: Vec<u64> = self.voices.keys().cloned().collect();
+        for id in voices {
+            self.stop(Voice(id));
+        }
+    }
+
+    /// Check if a Voice is still playing, audible or virtual.
+    pub fn is_active(&self, voice: Voice) -> bool {
+        self.voices.contains_key(&voice.0)
+    }
+
+    /// Check if a Voice is playing without an OpenAL source.
+    pub fn is_virtual(&self, voice: Voice) -> bool {
+        match self.voices.get(&voice.0) {
+            Some(state) => state.sound.is_none(),
+            None => false,
+        }
+    }
+
+    /**
+     * Get the number of Voices playing.
+     *
+     * # Return
+     * The number of (audible, virtual) Voices
+     */
+    pub fn voice_count(&self) -> (usize, usize) {
+        let audible = self
+            .voices
+            .values()
+            .filter(|state| state.sound.is_some())
+            .count();
+        (audible, self.voices.len() - audible)
+    }
+
+    /**
+     * Move a Voice.
+     *
+     * # Arguments
+     * * `voice` - The Voice to move
+     * * `position` - The new position [x, y, z]
+     */
+    pub fn set_position(&mut self, voice: Voice, position: [f32; 3]) {
+        if let Some(state) = self.voices.get_mut(&voice.0) {
+            state.params.position = position;
+            if let Some(sound) = state.sound.as_mut() {
+                sound.set_position(position);
+            }
+        }
+    }
+
+    /**
+     * Set the volume of a Voice.
+     *
+     * # Arguments
+     * * `voice` - The Voice to change
+     * * `volume` - The new volume
+     */
+    pub fn set_volume(&mut self, voice: Voice, volume: f32) {
+        if let Some(state) = self.voices.get_mut(&voice.0) {
+            state.params.volume = volume;
+            if let Some(sound) = state.sound.as_mut() {
+                sound.set_volume(volume);
+            }
+        }
+    }
+
+    /**
+     * Set the priority of a Voice.
+     *
+     * # Arguments
+     * * `voice` - The Voice to change
+     * * `priority` - The new priority
+     */
+    pub fn set_priority(&mut self, voice: Voice, priority: i32) {
+        if let Some(state) = self.voices.get_mut(&voice.0) {
+            state.params.priority = priority;
+        }
+    }
+
+    /**
+     * Advance the virtual Voices by `dt`, release the sources of the ended
+     * Voices and give the sources to the most important Voices.
+     *
+     * # Argument
+     * * `dt` - The time elapsed since the last update
+     */
+    pub fn update(&mut self, dt: Duration) {
+        check_openal_context!(());
+
+        let mut ended = Vec::new();
+        for (id, state) in self.voices.iter_mut() {
+            match state.sound {
+                Some(ref sound) => {
+                    if sound.get_state() == Stopped {
+                        ended.push(*id);
+                    }
+                }
+                None => {
+                    state.offset += dt.as_secs_f64() * state.samplerate * state.params.pitch as f64;
+                    if state.offset >= state.frames {
+                        if state.params.looping && state.frames > 0. {
+                            state.offset %= state.frames;
+                        } else {
+                            ended.push(*id);
+                        }
+                    }
+                }
+            }
+        }
+        for id in ended {
+            self.stop(Voice(id));
+        }
+
+        self.assign();
+    }
+
+    /// Give the available sources to the most important Voices and
+    /// virtualize the others.
+    fn assign(&mut self) {
+        check_openal_context!(());
+
+        let listener = listener::get_position();
+        let candidates = self
+            .voices
+            .iter()
+            .map(|(id, state)| Candidate {
+                id: *id,
+                priority: state.params.priority,
+                audibility: state.audibility(listener),
+                stereo: state.stereo,
+            })
+            .collect();
+        let (audible, silent) = select(candidates, self.max_mono, self.max_stereo, self.threshold);
+
+        // Release the sources first, so they can be given to other Voices
+        for id in silent {
+            let sound = match self.voices.get_mut(&id) {
+                Some(state) => state.sound.take().inspect(|sound| {
+                    state.offset = sound.get_offset() as f64;
+                }),
+                None => None,
+            };
+            if let Some(sound) = sound {
+                self.release(sound);
+            }
+        }
+
+        for id in audible {
+            let (data, needs_source) = match self.voices.get(&id) {
+                Some(state) => (state.data.clone(), state.sound.is_none()),
+                None => continue,
+            };
+            if !needs_source {
+                continue;
+            }
+
+            let sound = match self.free.pop() {
+                Some(mut sound) => {
+                    sound.set_datas(data);
+                    sound
+                }
+                None => match Sound::new_with_data(data) {
+                    Ok(sound) => sound,
+                    // The device is out of sources, stay virtual
+                    Err(_) => break,
+                },
+            };
+
+            if let Some(state) = self.voices.get_mut(&id) {
+                let mut sound = sound;
+                sound.set_volume(state.params.volume);
+                sound.set_pitch(state.params.pitch);
+                sound.set_position(state.params.position);
+                sound.set_relative(state.params.relative);
+                sound.set_looping(state.params.looping);
+                sound.set_bus(state.params.bus.as_ref());
+                sound.set_offset(state.offset as i32);
+                sound.play();
+                state.sound = Some(sound);
+            }
+        }
+    }
+
+    /// Keep the source of a Voice for the next Voices.
+    fn release(&mut self, mut sound: Sound) {
+        sound.stop();
+        sound.set_bus(None);
+        self.free.push(sound);
+    }
+}
+
+/// A Voice competing for the sources.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    id: u64,
+    priority: i32,
+    audibility: f32,
+    stereo: bool,
+}
+
+/**
+ * Split the Voices between the audible ones, given a source, and the
+ * silent ones.
+ *
+ * The highest priority is served first, then the loudest, then the oldest.
+ * A stereo Voice takes a mono source once the stereo ones are exhausted. A
+ * Voice under the threshold is silent even if a source is available.
+ *
+ * # Return
+ * The ids of the audible Voices and the ids of the silent Voices, in
+ * ranking order
+ */
+fn select(
+    mut candidates: Vec<Candidate>,
+    max_mono: usize,
+    max_stereo: usize,
+    threshold: f32,
+) -> (Vec<u64>, Vec<u64>) {
+    candidates.sort_by(|a, b| {
+        b.priority
+            .cmp(&a.priority)
+            .then(
+                b.audibility
+                    .partial_cmp(&a.audibility)
+                    .unwrap_or(Ordering::Equal),
+            )
+            .then(a.id.cmp(&b.id))
+    });
+
+    let mut mono = max_mono;
+    let mut stereo = max_stereo;
+    let mut audible = Vec::new();
+    let mut silent = Vec::new();
+    for candidate in candidates {
+        let available = match candidate.stereo {
+            true if stereo > 0 => &mut stereo,
+            _ => &mut mono,
+        };
+        if candidate.audibility >= threshold && *available > 0 {
+            *available -= 1;
+            audible.push(candidate.id);
+        } else {
+            silent.push(candidate.id);
+        }
+    }
+    (audible, silent)
+}
+
+impl Default for VoiceManager {
+    fn default() -> VoiceManager {
+        VoiceManager::new()
+    }
+}
+
+impl Drop for VoiceManager {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(non_snake_case)]
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::sound_data::SoundData;
+    use crate::voice::{select, Candidate, VoiceManager, VoiceParams};
+
+    fn candidate(id: u64, priority: i32, audibility: f32, stereo: bool) -> Candidate {
+        Candidate {
+            id,
+            priority,
+            audibility,
+            stereo,
+        }
+    }
+
+    #[test]
+    fn voice_select_ranking_OK() {
+        let candidates = vec![
+            candidate(0, 0, 1., false),
+            candidate(1, 1, 0.1, false),
+            candidate(2, 0, 0.5, false),
+            candidate(3, 0, 0.5, false),
+        ];
+
+        let (audible, silent) = select(candidates, 3, 0, 0.);
+        assert_eq!(audible, vec![1, 0, 2]);
+        assert_eq!(silent, vec![3]);
+    }
+
+    #[test]
+    fn voice_select_channels_OK() {
+        let candidates = vec![
+            candidate(0, 0, 1., true),
+            candidate(1, 0, 0.9, true),
+            candidate(2, 0, 0.8, false),
+            candidate(3, 0, 0.7, false),
+        ];
+
+        // The second stereo Voice borrows a mono source
+        let (audible, silent) = select(candidates, 2, 1, 0.);
+        assert_eq!(audible, vec![0, 1, 2]);
+        assert_eq!(silent, vec![3]);
+
+        // A mono Voice never takes a stereo source
+        let candidates = vec![candidate(0, 0, 1., false), candidate(1, 0, 0.9, false)];
+        let (audible, silent) = select(candidates, 1, 1, 0.);
+        assert_eq!(audible, vec![0]);
+        assert_eq!(silent, vec![1]);
+    }
+
+    #[test]
+    fn voice_select_threshold_OK() {
+        let candidates = vec![candidate(0, 5, 0.0001, false), candidate(1, 0, 0.5, false)];
+
+        let (audible, silent) = select(candidates, 4, 4, 0.001);
+        assert_eq!(audible, vec![1]);
+        assert_eq!(silent, vec![0]);
+    }
+
+    #[test]
+    #[ignore]
+    fn voice_manager_virtualize_OK() {
+        let data = Rc::new(RefCell::new(
+            SoundData::new("res/shot.wav").expect("Cannot create SoundData"),
+        ));
+        let mut voices = VoiceManager::with_limits(1, 1);
+
+        let low = voices.play(&data, VoiceParams::default()).unwrap();
+        assert!(!voices.is_virtual(low));
+
+        let high = voices
+            .play(
+                &data,
+                VoiceParams {
+                    priority: 1,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(!voices.is_virtual(high));
+        assert!(voices.is_virtual(low));
+        assert_eq!(voices.voice_count(), (1, 1));
+    }
+
+    #[test]
+    #[ignore]
+    fn voice_manager_instance_limit_OK() {
+        let data = Rc::new(RefCell::new(
+            SoundData::new("res/shot.wav").expect("Cannot create SoundData"),
+        ));
+        let mut voices = VoiceManager::with_limits(4, 4);
+        voices.set_instance_limit(&data, Some(1));
+
+        let first = voices
+            .play(
+                &data,
+                VoiceParams {
+                    priority: 1,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(voices.play(&data, VoiceParams::default()), None);
+
+        let second = voices
+            .play(
+                &data,
+                VoiceParams {
+                    priority: 1,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(!voices.is_active(first));
+        assert!(voices.is_active(second));
+    }
+}